@@ -16,6 +16,10 @@ pub trait ToDecimal {
 	fn to_decimal(self) -> Decimal;
 }
 
+/// Error returned when a value cannot be converted to or from a decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionError;
+
 #[repr(C)]
 #[allow(dead_code)]
 enum Class {
@@ -39,6 +43,9 @@ extern "C" {
 	fn __binary32_to_bid128(result: *mut Decimal, n: &f32);
 	fn __binary64_to_bid128(result: *mut Decimal, n: &f64);
 	fn __bid128_to_string(dest: *mut u8, n: &Decimal);
+	fn __bid128_to_binary64(result: *mut f64, n: &Decimal);
+	fn __bid128_to_int64_int(result: *mut i64, n: &Decimal);
+	fn __bid128_to_uint64_int(result: *mut u64, n: &Decimal);
 	fn __bid128_from_string(dest: *mut Decimal, string: *const u8);
 	fn __bid128_add(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_sub(result: *mut Decimal, x: &Decimal, y: &Decimal);
@@ -46,10 +53,14 @@ extern "C" {
 	fn __bid128_div(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_fmod(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_modf(result: *mut Decimal, x: &Decimal, int: *mut Decimal);
+	fn __bid128_round_integral_negative(result: *mut Decimal, x: &Decimal);
+	fn __bid128_round_integral_positive(result: *mut Decimal, x: &Decimal);
+	fn __bid128_round_integral_nearest_away(result: *mut Decimal, x: &Decimal);
 	fn __bid128_fma(result: *mut Decimal, x: &Decimal, y: &Decimal, z: &Decimal);
 	fn __bid128_exp(result: *mut Decimal, x: &Decimal);
 	fn __bid128_log(result: *mut Decimal, x: &Decimal);
 	fn __bid128_pow(result: *mut Decimal, x: &Decimal, y: &Decimal);
+	fn __bid128_quantize(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_atan2(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_hypot(result: *mut Decimal, x: &Decimal, y: &Decimal);
 	fn __bid128_sin(result: *mut Decimal, x: &Decimal);
@@ -89,18 +100,25 @@ impl Decimal {
 		0.into()
 	}
 
-	pub fn from_str(string: &str) -> Self {
+	pub fn from_str(string: &str) -> Result<Self, ConversionError> {
 		let mut buf: Vec<u8> = string.as_bytes().to_vec();
 		buf.push(0);
 		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
-		unsafe {
+		let result = unsafe {
 			__bid128_from_string(result.as_mut_ptr(), buf.as_ptr());
 			result.assume_init()
+		};
+
+		// The library produces NaN for strings that are not valid numbers
+		if result.is_nan() {
+			Err(ConversionError)
+		} else {
+			Ok(result)
 		}
 	}
 
 	pub fn pi() -> Self {
-		Decimal::from_str("3.141592653589793238462643383279503")
+		Decimal::from_str("3.141592653589793238462643383279503").unwrap()
 	}
 
 	pub fn to_string(&self) -> String {
@@ -118,6 +136,14 @@ impl Decimal {
 		String::from_utf8_lossy(&buf[0..end]).to_string()
 	}
 
+	pub fn to_f64(&self) -> f64 {
+		let mut result = core::mem::MaybeUninit::<f64>::uninit();
+		unsafe {
+			__bid128_to_binary64(result.as_mut_ptr(), &self);
+			result.assume_init()
+		}
+	}
+
 	pub fn sqrt(&self) -> Self {
 		let one: Decimal = 1.into();
 		let two: Decimal = 2.into();
@@ -154,6 +180,16 @@ impl Decimal {
 		}
 	}
 
+	/// Rounds to the same exponent as `exponent`, which determines the number of
+	/// digits kept after the decimal point.
+	pub fn quantize(&self, exponent: &Self) -> Self {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
+		unsafe {
+			__bid128_quantize(result.as_mut_ptr(), &self, exponent);
+			result.assume_init()
+		}
+	}
+
 	pub fn sin(&self) -> Self {
 		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
@@ -325,6 +361,31 @@ impl Decimal {
 		}
 	}
 
+	pub fn floor(&self) -> Self {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
+		unsafe {
+			__bid128_round_integral_negative(result.as_mut_ptr(), &self);
+			result.assume_init()
+		}
+	}
+
+	pub fn ceil(&self) -> Self {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
+		unsafe {
+			__bid128_round_integral_positive(result.as_mut_ptr(), &self);
+			result.assume_init()
+		}
+	}
+
+	/// Rounds to the nearest integer, with halfway cases rounded away from zero.
+	pub fn round(&self) -> Self {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
+		unsafe {
+			__bid128_round_integral_nearest_away(result.as_mut_ptr(), &self);
+			result.assume_init()
+		}
+	}
+
 	pub fn abs(&self) -> Self {
 		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
@@ -489,6 +550,43 @@ impl From<f64> for Decimal {
 	}
 }
 
+impl core::convert::TryFrom<&Decimal> for i64 {
+	type Error = ConversionError;
+
+	fn try_from(value: &Decimal) -> Result<Self, Self::Error> {
+		// The library returns an indeterminate value when out of range, so check the
+		// range first. Conversion truncates toward zero.
+		let value = value.trunc();
+		if !value.is_finite() || value < Decimal::from(i64::MIN) || value > Decimal::from(i64::MAX)
+		{
+			return Err(ConversionError);
+		}
+		let mut result = core::mem::MaybeUninit::<i64>::uninit();
+		unsafe {
+			__bid128_to_int64_int(result.as_mut_ptr(), &value);
+			Ok(result.assume_init())
+		}
+	}
+}
+
+impl core::convert::TryFrom<&Decimal> for u64 {
+	type Error = ConversionError;
+
+	fn try_from(value: &Decimal) -> Result<Self, Self::Error> {
+		// The library returns an indeterminate value when out of range, so check the
+		// range first. Conversion truncates toward zero.
+		let value = value.trunc();
+		if !value.is_finite() || value < Decimal::zero() || value > Decimal::from(u64::MAX) {
+			return Err(ConversionError);
+		}
+		let mut result = core::mem::MaybeUninit::<u64>::uninit();
+		unsafe {
+			__bid128_to_uint64_int(result.as_mut_ptr(), &value);
+			Ok(result.assume_init())
+		}
+	}
+}
+
 impl ToDecimal for i32 {
 	fn to_decimal(self) -> Decimal {
 		self.into()
@@ -551,8 +649,10 @@ impl core::ops::Add for &Decimal {
 
 impl core::ops::AddAssign for Decimal {
 	fn add_assign(&mut self, rhs: Self) {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
-			__bid128_add(self, &self.clone(), &rhs);
+			__bid128_add(result.as_mut_ptr(), self, &rhs);
+			*self = result.assume_init();
 		}
 	}
 }
@@ -583,8 +683,10 @@ impl core::ops::Sub for &Decimal {
 
 impl core::ops::SubAssign for Decimal {
 	fn sub_assign(&mut self, rhs: Self) {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
-			__bid128_sub(self, &self.clone(), &rhs);
+			__bid128_sub(result.as_mut_ptr(), self, &rhs);
+			*self = result.assume_init();
 		}
 	}
 }
@@ -615,8 +717,10 @@ impl core::ops::Mul for &Decimal {
 
 impl core::ops::MulAssign for Decimal {
 	fn mul_assign(&mut self, rhs: Self) {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
-			__bid128_mul(self, &self.clone(), &rhs);
+			__bid128_mul(result.as_mut_ptr(), self, &rhs);
+			*self = result.assume_init();
 		}
 	}
 }
@@ -647,8 +751,10 @@ impl core::ops::Div for &Decimal {
 
 impl core::ops::DivAssign for Decimal {
 	fn div_assign(&mut self, rhs: Self) {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
-			__bid128_div(self, &self.clone(), &rhs);
+			__bid128_div(result.as_mut_ptr(), self, &rhs);
+			*self = result.assume_init();
 		}
 	}
 }
@@ -679,8 +785,10 @@ impl core::ops::Rem for &Decimal {
 
 impl core::ops::RemAssign for Decimal {
 	fn rem_assign(&mut self, rhs: Self) {
+		let mut result = core::mem::MaybeUninit::<Decimal>::uninit();
 		unsafe {
-			__bid128_fmod(self, &self.clone(), &rhs);
+			__bid128_fmod(result.as_mut_ptr(), self, &rhs);
+			*self = result.assume_init();
 		}
 	}
 }
@@ -751,3 +859,77 @@ impl core::cmp::PartialOrd for Decimal {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn values() -> Vec<Decimal> {
+		vec![
+			Decimal::from(0),
+			Decimal::from(1),
+			Decimal::from(-7),
+			Decimal::from_str("0.1").unwrap(),
+			Decimal::from_str("-2.5").unwrap(),
+			Decimal::from_str("1234567890.0987654321").unwrap(),
+		]
+	}
+
+	#[test]
+	fn add_assign_to_self() {
+		for value in values() {
+			let mut x = value.clone();
+			x += x.clone();
+			assert!(x == &value * &Decimal::from(2));
+		}
+	}
+
+	#[test]
+	fn sub_assign_to_self() {
+		for value in values() {
+			let mut x = value.clone();
+			x -= x.clone();
+			assert!(x == Decimal::zero());
+		}
+	}
+
+	#[test]
+	fn mul_assign_to_self() {
+		for value in values() {
+			let mut x = value.clone();
+			x *= x.clone();
+			assert!(x == &value * &value);
+		}
+	}
+
+	#[test]
+	fn div_and_rem_assign_to_self() {
+		for value in values() {
+			if value == Decimal::zero() {
+				continue;
+			}
+			let mut x = value.clone();
+			x /= x.clone();
+			assert!(x == Decimal::from(1));
+
+			let mut x = value.clone();
+			x %= x.clone();
+			assert!(x == Decimal::zero());
+		}
+	}
+
+	#[test]
+	fn convert_to_host_types() {
+		use core::convert::TryFrom;
+
+		assert_eq!(Decimal::from(3.5).to_f64(), 3.5);
+		assert_eq!(i64::try_from(&Decimal::from(-7.9)), Ok(-7));
+		assert_eq!(u64::try_from(&Decimal::from(42)), Ok(42));
+
+		let infinity = &Decimal::from(1) / &Decimal::zero();
+		assert!(infinity.is_infinite());
+		assert_eq!(i64::try_from(&infinity), Err(ConversionError));
+		assert_eq!(u64::try_from(&infinity), Err(ConversionError));
+		assert_eq!(u64::try_from(&Decimal::from(-1)), Err(ConversionError));
+	}
+}
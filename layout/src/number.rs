@@ -1,7 +1,7 @@
 use crate::font::{Font, FontMetrics};
 use crate::layout::{Layout, TokenType};
 use crate::string::StringLayout;
-use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_bigint::{BigInt, BigUint, Sign, ToBigInt};
 use rscalc_math::format::{Format, FormatMode, FormatResult, MAX_SHORT_DISPLAY_BITS};
 use rscalc_math::number::Number;
 
@@ -52,9 +52,13 @@ fn rational_layout(
 			if &num < &0.to_bigint().unwrap() {
 				num = -num;
 				"-".to_string()
+			} else if format.show_plus_sign {
+				"+".to_string()
 			} else {
 				"".to_string()
 			}
+		} else if format.show_plus_sign && int.sign() == Sign::Plus {
+			"+".to_string() + &format.format_bigint(&int)
 		} else {
 			format.format_bigint(&int)
 		};
@@ -213,7 +213,11 @@ impl StackRenderer {
 					Ok(entry) => entry,
 					Err(_) => continue,
 				};
-				let entry = Stack::value_for_integer_mode(&format.integer_mode, entry);
+				let entry = Stack::value_for_integer_mode(
+					&format.integer_mode,
+					format.integer_overflow,
+					entry,
+				);
 				entry.layout(format, base_font, renderer.metrics(), width)
 			};
 
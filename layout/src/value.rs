@@ -7,6 +7,7 @@ use crate::string::StringLayout;
 use crate::unit::CompositeUnitLayout;
 use crate::vector::VectorLayout;
 use num_bigint::ToBigInt;
+use rscalc_math::complex::ComplexNumber;
 use rscalc_math::format::{Format, FormatMode, MAX_SHORT_DISPLAY_BITS};
 use rscalc_math::number::Number;
 use rscalc_math::value::Value;
@@ -99,6 +100,35 @@ impl ValueLayout for Value {
 		metrics: &dyn FontMetrics,
 		max_width: i32,
 	) -> Layout {
+		// Show time quantities as a duration if enabled
+		if format.show_duration {
+			if let Some(seconds) = self.duration_seconds() {
+				if let Some(string) = format.format_duration(&seconds) {
+					if let Some(layout) = string.single_line_layout(
+						base_font,
+						TokenType::Float,
+						metrics,
+						max_width,
+						None,
+					) {
+						return layout;
+					}
+				}
+			}
+		}
+
+		// Show units reduced to base SI units if enabled. This only affects the display,
+		// the value keeps the units it was entered with.
+		if format.show_base_units {
+			if let Value::NumberWithUnit(_, _) = self {
+				if let Ok(value) = self.to_base_units() {
+					let mut format = format.clone();
+					format.show_base_units = false;
+					return value.layout(&format, base_font, metrics, max_width);
+				}
+			}
+		}
+
 		let mut max_width = max_width;
 
 		// Generate unit layout if there are units
@@ -278,6 +308,14 @@ impl ValueLayout for Value {
 			Value::Number(value) | Value::NumberWithUnit(value, _) => {
 				value.single_line_layout(format, int_font, frac_font, metrics, max_width)
 			}
+			Value::Complex(value) if format.complex_polar => polar_layout(
+				value,
+				format,
+				int_font,
+				metrics,
+				max_width,
+				format.precision,
+			),
 			Value::Complex(value) => {
 				// Complex number, try to render the full representation of both real and
 				// imaginary parts.
@@ -351,6 +389,12 @@ impl ValueLayout for Value {
 					.to_decimal()
 					.single_line_layout(format, "", "", font, metrics, max_width)
 			}
+			Value::Complex(value) if format.complex_polar => {
+				// Reduce the precision until the polar form fits
+				polar_layout(value, format, font, metrics, max_width, 1).unwrap_or(
+					Layout::StaticText("⟪Render error⟫", font, TokenType::Object),
+				)
+			}
 			Value::Complex(value) => {
 				// Render complex number as two lines, one with the decimal real part, and
 				// one with the decimal imaginary part.
@@ -417,6 +461,11 @@ impl ValueLayout for Value {
 					max_width,
 				)
 			}
+			Value::Complex(value) if format.complex_polar => {
+				polar_layout(value, format, small_font, metrics, max_width, 1).unwrap_or(
+					Layout::StaticText("⟪Render error⟫", small_font, TokenType::Object),
+				)
+			}
 			Value::Complex(value) => {
 				// Render complex number as two lines, one with the decimal real part, and
 				// one with the decimal imaginary part.
@@ -521,7 +570,11 @@ impl ValueLayout for Value {
 				// Real number in rational form
 				if format.show_alt_float && format.mode == FormatMode::Rational {
 					if let Ok(number) = self.real_number() {
-						let string = format.decimal_format().format_decimal(&number.to_decimal());
+						let string = if format.show_continued_fraction {
+							format.format_continued_fraction(number)?
+						} else {
+							format.decimal_format().format_decimal(&number.to_decimal())
+						};
 						string.single_line_layout(font, TokenType::Float, metrics, max_width, None)
 					} else {
 						None
@@ -532,6 +585,7 @@ impl ValueLayout for Value {
 			}
 			Value::Complex(value) => {
 				if format.show_alt_float
+					&& !format.complex_polar
 					&& format.mode == FormatMode::Rational
 					&& (value.real_part().is_rational() || value.imaginary_part().is_rational())
 				{
@@ -632,3 +686,29 @@ impl ValueLayout for Value {
 		(layout, AlternateLayoutType::None)
 	}
 }
+
+fn polar_layout(
+	value: &ComplexNumber,
+	format: &Format,
+	font: Font,
+	metrics: &dyn FontMetrics,
+	max_width: i32,
+	min_precision: usize,
+) -> Option<Layout> {
+	// Render the magnitude and angle of a complex number, using the lowest precision
+	// that is needed to make it fit.
+	let mut format = format.decimal_format();
+	loop {
+		let layout = value.format(&format).single_line_layout(
+			font,
+			TokenType::Complex,
+			metrics,
+			max_width,
+			None,
+		);
+		if layout.is_some() || format.precision <= min_precision {
+			return layout;
+		}
+		format.precision -= 1;
+	}
+}
@@ -1,9 +1,12 @@
 use crate::format::Format;
 use crate::number::{Number, ToNumber};
+use crate::unit::AngleUnit;
 use intel_dfp::Decimal;
+use num_bigint::Sign;
+use num_integer::Integer;
 
 #[cfg(not(feature = "std"))]
-use alloc::string::String;
+use alloc::string::{String, ToString};
 
 // Maximum integer size before it is converted into a floating point number.
 pub const MAX_COMPLEX_INTEGER_BITS: u64 = 1024;
@@ -12,6 +15,10 @@ pub const MAX_COMPLEX_INTEGER_BITS: u64 = 1024;
 // 128-bit float available in fractional form.
 pub const MAX_COMPLEX_DENOMINATOR_BITS: u64 = 128;
 
+// Maximum size of an integer power that is computed exactly. Larger powers would
+// exceed the integer size limit anyway.
+const MAX_EXACT_POWER_BITS: u64 = 32;
+
 #[derive(Clone)]
 pub struct ComplexNumber {
 	real: Number,
@@ -37,6 +44,14 @@ impl ComplexNumber {
 		}
 	}
 
+	/// Creates a complex number from a magnitude and an angle in radians
+	pub fn from_polar(magnitude: &Number, angle: &Number) -> Self {
+		ComplexNumber {
+			real: Self::check_int_bounds(magnitude * &angle.cos()),
+			imaginary: Self::check_int_bounds(magnitude * &angle.sin()),
+		}
+	}
+
 	pub fn i() -> Self {
 		ComplexNumber {
 			real: 0.into(),
@@ -83,6 +98,10 @@ impl ComplexNumber {
 		self.imaginary.is_zero()
 	}
 
+	fn is_zero(&self) -> bool {
+		self.real.is_zero() && self.imaginary.is_zero()
+	}
+
 	pub fn is_out_of_range(&self) -> bool {
 		self.real.is_infinite()
 			|| self.real.is_nan()
@@ -99,17 +118,39 @@ impl ComplexNumber {
 	}
 
 	pub fn format(&self, format: &Format) -> String {
+		if format.complex_polar {
+			return self.format_polar(format);
+		}
+
+		// The sign of the imaginary part is given by the operator between the parts
+		let mut imaginary_format = format.clone();
+		imaginary_format.show_plus_sign = false;
 		if self.imaginary.is_negative() {
 			format.format_number(&self.real).to_string()
-				+ " - " + format.format_number(&-&self.imaginary).to_str()
+				+ " - " + imaginary_format.format_number(&-&self.imaginary).to_str()
 				+ "ℹ"
 		} else {
 			format.format_number(&self.real).to_string()
-				+ " + " + format.format_number(&self.imaginary).to_str()
+				+ " + " + imaginary_format.format_number(&self.imaginary).to_str()
 				+ "ℹ"
 		}
 	}
 
+	fn format_polar(&self, format: &Format) -> String {
+		// Magnitude is never negative, so explicit plus signs would only add clutter
+		let mut format = format.clone();
+		format.show_plus_sign = false;
+		let angle = self.polar_angle();
+		let angle = angle.angle_from_radians(format.angle_mode);
+		let suffix = match format.angle_mode {
+			AngleUnit::Degrees => "°".to_string(),
+			unit => " ".to_string() + unit.to_str(),
+		};
+		format.format_number(&self.magnitude()).to_string()
+			+ "∡" + format.format_number(&angle).to_str()
+			+ &suffix
+	}
+
 	pub fn magnitude(&self) -> Number {
 		(&self.real * &self.real + &self.imaginary * &self.imaginary).sqrt()
 	}
@@ -175,9 +216,50 @@ impl ComplexNumber {
 	}
 
 	pub fn pow(&self, power: &ComplexNumber) -> Self {
+		if let Some(result) = self.exact_pow(power) {
+			return result;
+		}
 		(power * &self.ln()).exp()
 	}
 
+	fn exact_pow(&self, power: &ComplexNumber) -> Option<Self> {
+		// Integer powers of exact complex numbers are computed with repeated squaring so
+		// that Gaussian integers and rational parts stay exact.
+		if !self.real.is_exact() || !self.imaginary.is_exact() || !power.is_real() {
+			return None;
+		}
+		let power = match &power.real {
+			Number::Integer(power) => power,
+			_ => return None,
+		};
+		if power.bits() > MAX_EXACT_POWER_BITS || (self.is_zero() && power.sign() == Sign::Minus) {
+			return None;
+		}
+
+		let mut n = if power.sign() == Sign::Minus {
+			-power
+		} else {
+			power.clone()
+		};
+		let mut result = 1.to_complex();
+		let mut square = self.clone();
+		while n.sign() != Sign::NoSign {
+			if n.is_odd() {
+				result = &result * &square;
+			}
+			n >>= 1;
+			if n.sign() != Sign::NoSign {
+				square = &square * &square;
+			}
+		}
+
+		if power.sign() == Sign::Minus {
+			Some(1.to_complex() / result)
+		} else {
+			Some(result)
+		}
+	}
+
 	pub fn sin(&self) -> Self {
 		ComplexNumber {
 			real: &self.real.sin() * &self.imaginary.cosh(),
@@ -10,6 +10,21 @@ pub enum Constant {
 }
 
 impl Constant {
+	pub fn to_u8(&self) -> u8 {
+		match self {
+			Constant::Pi => 0,
+			Constant::SpeedOfLight => 1,
+		}
+	}
+
+	pub fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(Constant::Pi),
+			1 => Some(Constant::SpeedOfLight),
+			_ => None,
+		}
+	}
+
 	pub fn to_str(&self) -> &'static str {
 		match self {
 			Constant::Pi => "π",
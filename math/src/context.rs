@@ -1,27 +1,34 @@
 use crate::complex::ComplexNumber;
 use crate::constant::Constant;
 use crate::error::{Error, Result};
-use crate::format::{DecimalPointMode, Format, FormatMode, IntegerMode};
+use crate::format::{
+	DecimalPointMode, Format, FormatMode, IntegerMode, IntegerOverflowMode, MAX_GROUPING,
+	MAX_INTEGER_RADIX, MAX_PRECISION, MIN_INTEGER_RADIX, TRAILING_ZERO_DIGITS,
+};
 use crate::matrix::Matrix;
 use crate::number::{Number, MAX_INTEGER_BITS};
 use crate::stack::Stack;
-use crate::storage::store;
+use crate::stats;
+use crate::storage::{
+	deserialize_inline, serialize_inline, store, DeserializeInput, SerializeOutput,
+};
 use crate::time::Now;
-use crate::unit::{AngleUnit, Unit};
+use crate::undo::{
+	pop_redo_action, pop_undo_action, push_redo_action, push_undo_action,
+	push_undo_action_preserving_redo, UndoAction,
+};
+use crate::unit::{AngleUnit, CompositeUnit, Unit};
 use crate::value::{Value, ValueRef};
 use crate::vector::Vector;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-use num_bigint::ToBigInt;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use num_bigint::{BigInt, BigUint, Sign, ToBigInt, ToBigUint};
+use num_integer::Integer;
 
-#[cfg(feature = "std")]
-use std::borrow::Cow;
 #[cfg(feature = "std")]
 use std::collections::BTreeMap;
 #[cfg(feature = "std")]
 use std::convert::TryFrom;
 
-#[cfg(not(feature = "std"))]
-use alloc::borrow::Cow;
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap;
 #[cfg(not(feature = "std"))]
@@ -36,6 +43,10 @@ pub enum Location {
 	Variable(char),
 }
 
+const LOCATION_SERIALIZE_TYPE_INTEGER: u8 = 0;
+const LOCATION_SERIALIZE_TYPE_STACK_OFFSET: u8 = 1;
+const LOCATION_SERIALIZE_TYPE_VARIABLE: u8 = 2;
+
 pub struct Context {
 	stack: Stack,
 	format: Format,
@@ -45,6 +56,39 @@ pub struct Context {
 	memory: BTreeMap<Location, ValueRef>,
 }
 
+impl Location {
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		match self {
+			Location::Integer(idx) => {
+				output.write_u8(LOCATION_SERIALIZE_TYPE_INTEGER)?;
+				output.write_u32(*idx as u32)?;
+			}
+			Location::StackOffset(offset) => {
+				output.write_u8(LOCATION_SERIALIZE_TYPE_STACK_OFFSET)?;
+				output.write_u32(*offset as u32)?;
+			}
+			Location::Variable(ch) => {
+				output.write_u8(LOCATION_SERIALIZE_TYPE_VARIABLE)?;
+				output.write_u32(*ch as u32)?;
+			}
+		}
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		match input.read_u8()? {
+			LOCATION_SERIALIZE_TYPE_INTEGER => Ok(Location::Integer(input.read_u32()? as usize)),
+			LOCATION_SERIALIZE_TYPE_STACK_OFFSET => {
+				Ok(Location::StackOffset(input.read_u32()? as usize))
+			}
+			LOCATION_SERIALIZE_TYPE_VARIABLE => Ok(Location::Variable(
+				core::char::from_u32(input.read_u32()?).ok_or(Error::CorruptData)?,
+			)),
+			_ => Err(Error::CorruptData),
+		}
+	}
+}
+
 impl Context {
 	pub fn new() -> Self {
 		Context {
@@ -68,6 +112,50 @@ impl Context {
 		}
 	}
 
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		self.stack.serialize(output)?;
+		output.write_u32(self.memory.len() as u32)?;
+		for (location, value) in &self.memory {
+			location.serialize(output)?;
+			serialize_inline(&value.get()?, output)?;
+		}
+		self.format.serialize(output)?;
+		self.default_integer_format.serialize(output)?;
+		self.prev_decimal_integer_mode.serialize(output)?;
+		output.write_u16(Unit::Angle(self.angle_mode).to_u16())?;
+		Ok(())
+	}
+
+	pub fn deserialize_into(&mut self, input: &mut DeserializeInput) -> Result<()> {
+		// Read everything before applying any of it so that corrupt data leaves the
+		// current state intact.
+		let entries = Stack::deserialize_entries(input)?;
+		let count = input.read_u32()?;
+		let mut memory = BTreeMap::new();
+		for _ in 0..count {
+			let location = Location::deserialize(input)?;
+			let value = store(deserialize_inline::<Value>(input)?)?;
+			memory.insert(location, value);
+		}
+		let format = Format::deserialize(input)?;
+		let default_integer_format = IntegerMode::deserialize(input)?;
+		let prev_decimal_integer_mode = IntegerMode::deserialize(input)?;
+		let angle_mode = match Unit::from_u16(input.read_u16()?) {
+			Some(Unit::Angle(angle_mode)) => angle_mode,
+			_ => return Err(Error::CorruptData),
+		};
+
+		self.stack.restore_entries(entries);
+		self.memory = memory;
+		self.format = format;
+		self.format.angle_mode = angle_mode;
+		self.default_integer_format = default_integer_format;
+		self.prev_decimal_integer_mode = prev_decimal_integer_mode;
+		self.angle_mode = angle_mode;
+		self.stack.invalidate_caches();
+		Ok(())
+	}
+
 	pub fn stack(&self) -> &Stack {
 		&self.stack
 	}
@@ -95,6 +183,11 @@ impl Context {
 		self.stack.invalidate_caches();
 	}
 
+	pub fn toggle_si_prefixes(&mut self) {
+		self.format.si_prefixes = !self.format.si_prefixes;
+		self.stack.invalidate_caches();
+	}
+
 	pub fn toggle_alt_float(&mut self) {
 		self.format.show_alt_float = !self.format.show_alt_float;
 		self.stack.invalidate_caches();
@@ -105,6 +198,24 @@ impl Context {
 		self.stack.invalidate_caches();
 	}
 
+	pub fn set_precision(&mut self, precision: usize) -> Result<()> {
+		if precision < 1 || precision > MAX_PRECISION {
+			return Err(Error::ValueOutOfRange);
+		}
+		self.format.precision = precision;
+		self.stack.invalidate_caches();
+		Ok(())
+	}
+
+	pub fn set_grouping(&mut self, grouping: usize) -> Result<()> {
+		if grouping > MAX_GROUPING as usize {
+			return Err(Error::ValueOutOfRange);
+		}
+		self.format.grouping = grouping as u8;
+		self.stack.invalidate_caches();
+		Ok(())
+	}
+
 	pub fn set_decimal_point_mode(&mut self, mode: DecimalPointMode) {
 		self.format.decimal_point = mode;
 		self.stack.invalidate_caches();
@@ -126,6 +237,14 @@ impl Context {
 		self.stack.invalidate_caches();
 	}
 
+	pub fn toggle_integer_saturation(&mut self) {
+		self.format.integer_overflow = match self.format.integer_overflow {
+			IntegerOverflowMode::Wrap => IntegerOverflowMode::Saturate,
+			IntegerOverflowMode::Saturate => IntegerOverflowMode::Wrap,
+		};
+		self.stack.invalidate_caches();
+	}
+
 	pub fn set_integer_radix(&mut self, radix: u8) {
 		if radix == 10 {
 			if self.format.integer_radix != 10 {
@@ -142,6 +261,14 @@ impl Context {
 		self.stack.invalidate_caches();
 	}
 
+	pub fn set_custom_integer_radix(&mut self, radix: usize) -> Result<()> {
+		if radix < MIN_INTEGER_RADIX as usize || radix > MAX_INTEGER_RADIX as usize {
+			return Err(Error::ValueOutOfRange);
+		}
+		self.set_integer_radix(radix as u8);
+		Ok(())
+	}
+
 	pub fn toggle_integer_radix(&mut self) {
 		if self.format.integer_radix == 10 {
 			self.set_integer_radix(16);
@@ -172,6 +299,66 @@ impl Context {
 
 	pub fn set_angle_mode(&mut self, unit: AngleUnit) {
 		self.angle_mode = unit;
+		self.format.angle_mode = unit;
+		if self.format.complex_polar || self.format.symbolic_angles {
+			self.stack.invalidate_caches();
+		}
+	}
+
+	pub fn toggle_continued_fraction(&mut self) {
+		self.format.show_continued_fraction = !self.format.show_continued_fraction;
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_duration(&mut self) {
+		self.format.show_duration = !self.format.show_duration;
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_plus_sign(&mut self) {
+		self.format.show_plus_sign = !self.format.show_plus_sign;
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_symbolic_angles(&mut self) {
+		self.format.symbolic_angles = !self.format.symbolic_angles;
+		self.stack.invalidate_caches();
+	}
+
+	/// Cycles the trailing zero padding for the current display mode only, so that they
+	/// can be shown in scientific notation without also being shown in normal display.
+	pub fn toggle_trailing_zeros(&mut self) {
+		let setting = match self.format.mode {
+			FormatMode::Scientific | FormatMode::Engineering => &mut self.format.sci_trailing_zeros,
+			_ => &mut self.format.trailing_zeros,
+		};
+		*setting = match *setting {
+			None => Some(TRAILING_ZERO_DIGITS[0]),
+			Some(digits) => TRAILING_ZERO_DIGITS
+				.iter()
+				.cloned()
+				.find(|count| *count > digits),
+		};
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_significant_figures(&mut self) {
+		self.format.significant_figures = !self.format.significant_figures;
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_base_units_display(&mut self) {
+		self.format.show_base_units = !self.format.show_base_units;
+		self.stack.invalidate_caches();
+	}
+
+	pub fn toggle_exact_only(&mut self) {
+		self.format.exact_only = !self.format.exact_only;
+	}
+
+	pub fn toggle_complex_polar(&mut self) {
+		self.format.complex_polar = !self.format.complex_polar;
+		self.stack.invalidate_caches();
 	}
 
 	pub fn stack_len(&self) -> usize {
@@ -181,6 +368,7 @@ impl Context {
 	pub fn top(&self) -> Result<Value> {
 		Ok(Stack::value_for_integer_mode(
 			&self.format.integer_mode,
+			self.format.integer_overflow,
 			self.stack.top()?,
 		))
 	}
@@ -188,12 +376,37 @@ impl Context {
 	pub fn entry(&self, idx: usize) -> Result<Value> {
 		Ok(Stack::value_for_integer_mode(
 			&self.format.integer_mode,
+			self.format.integer_overflow,
 			self.stack.entry(idx)?,
 		))
 	}
 
+	fn check_exact(&self, count: usize, items: &[&Value]) -> Result<()> {
+		// In exact mode, operations on exact inputs must not produce floating point
+		// results. Inexact inputs are allowed to produce inexact results.
+		if !self.format.exact_only {
+			return Ok(());
+		}
+		for item in items {
+			if !item.is_exact()? {
+				for idx in 0..count {
+					if !self.entry(idx)?.is_exact()? {
+						return Ok(());
+					}
+				}
+				return Err(Error::InexactResult);
+			}
+		}
+		Ok(())
+	}
+
 	pub fn replace_entries(&mut self, count: usize, value: Value) -> Result<()> {
-		let value = Stack::value_for_integer_mode(&self.format.integer_mode, value);
+		let value = Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			self.format.integer_overflow,
+			value,
+		);
+		self.check_exact(count, &[&value])?;
 		self.stack.replace_entries(count, value)?;
 		Ok(())
 	}
@@ -202,22 +415,61 @@ impl Context {
 		self.stack.replace_top_with_multiple(items)
 	}
 
+	pub fn replace_entries_with_multiple(&mut self, count: usize, items: Vec<Value>) -> Result<()> {
+		let items = items
+			.into_iter()
+			.map(|item| {
+				Stack::value_for_integer_mode(
+					&self.format.integer_mode,
+					self.format.integer_overflow,
+					item,
+				)
+			})
+			.collect::<Vec<Value>>();
+		self.check_exact(count, &items.iter().collect::<Vec<&Value>>())?;
+		self.stack.replace_entries_with_multiple(count, items)
+	}
+
 	pub fn set_top(&mut self, value: Value) -> Result<()> {
-		let value = Stack::value_for_integer_mode(&self.format.integer_mode, value);
+		let value = Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			self.format.integer_overflow,
+			value,
+		);
+		self.check_exact(1, &[&value])?;
 		self.stack.set_top(value)
 	}
 
 	pub fn set_entry(&mut self, offset: usize, value: Value) -> Result<()> {
-		let value = Stack::value_for_integer_mode(&self.format.integer_mode, value);
+		let value = Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			self.format.integer_overflow,
+			value,
+		);
 		self.stack.set_entry(offset, value)?;
 		Ok(())
 	}
 
 	pub fn push(&mut self, value: Value) -> Result<()> {
-		let value = Stack::value_for_integer_mode(&self.format.integer_mode, value);
+		let value = Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			self.format.integer_overflow,
+			value,
+		);
 		self.stack.push(value)
 	}
 
+	/// Enters a new value, which replaces the top of the stack if stack lift has
+	/// been disabled.
+	pub fn input_value(&mut self, value: Value) -> Result<()> {
+		let value = Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			self.format.integer_overflow,
+			value,
+		);
+		self.stack.input_value(value)
+	}
+
 	pub fn push_constant(&mut self, constant: Constant) -> Result<()> {
 		self.push(constant.value())
 	}
@@ -225,6 +477,7 @@ impl Context {
 	pub fn pop(&mut self) -> Result<Value> {
 		Ok(Stack::value_for_integer_mode(
 			&self.format.integer_mode,
+			self.format.integer_overflow,
 			self.stack.pop()?,
 		))
 	}
@@ -233,10 +486,42 @@ impl Context {
 		self.stack.rotate_down();
 	}
 
+	pub fn rotate_up(&mut self) {
+		self.stack.rotate_up();
+	}
+
+	pub fn drop_n(&mut self) -> Result<()> {
+		// Drop the count itself along with the requested number of entries
+		let count = usize::try_from(&*self.top()?.to_int()?)?;
+		let total = count.checked_add(1).ok_or(Error::NotEnoughValues)?;
+		self.stack.pop_multiple(total)
+	}
+
+	pub fn pick(&mut self) -> Result<()> {
+		// Entry one is the value just below the index at the top of the stack
+		let idx = usize::try_from(&*self.top()?.to_int()?)?;
+		if idx == 0 {
+			return Err(Error::InvalidStackIndex);
+		}
+		self.set_top(self.entry(idx)?)
+	}
+
+	pub fn stack_depth(&mut self) -> Result<()> {
+		self.push(Value::Number(self.stack.len().into()))
+	}
+
 	pub fn swap(&mut self, a_idx: usize, b_idx: usize) -> Result<()> {
 		self.stack.swap(a_idx, b_idx)
 	}
 
+	/// Clears the top of the stack to zero without dropping it. Stack lift is disabled
+	/// so that the next number entered replaces the zero.
+	pub fn clear_x(&mut self) -> Result<()> {
+		self.set_top(Value::Number(0.into()))?;
+		self.stack.disable_stack_lift();
+		Ok(())
+	}
+
 	pub fn clear_stack(&mut self) {
 		self.stack.clear();
 	}
@@ -268,24 +553,143 @@ impl Context {
 		Ok(())
 	}
 
+	pub fn delete(&mut self, location: &Location) -> Result<()> {
+		match location {
+			Location::StackOffset(_) => Err(Error::InvalidEntry),
+			location => {
+				if self.memory.remove(location).is_some() {
+					Ok(())
+				} else {
+					Err(Error::ValueNotDefined)
+				}
+			}
+		}
+	}
+
+	pub fn memory_locations(&self) -> Vec<Location> {
+		self.memory.keys().cloned().collect()
+	}
+
+	pub fn clear_all_variables(&mut self) {
+		if self.stack.undo_enabled() {
+			let variables = self
+				.memory
+				.iter()
+				.map(|(location, value)| (location.clone(), value.clone()))
+				.collect();
+			push_undo_action(UndoAction::ClearVariables(variables));
+		}
+		self.memory.clear();
+	}
+
 	pub fn undo(&mut self) -> Result<()> {
-		self.stack.undo()
+		if !self.stack.undo_enabled() {
+			return Err(Error::UndoBufferEmpty);
+		}
+		let redo_action = self.undo_action(pop_undo_action()?)?;
+		push_redo_action(redo_action);
+		Ok(())
+	}
+
+	pub fn redo(&mut self) -> Result<()> {
+		if !self.stack.undo_enabled() {
+			return Err(Error::RedoBufferEmpty);
+		}
+		let undo_action = self.undo_action(pop_redo_action()?)?;
+		push_undo_action_preserving_redo(undo_action);
+		Ok(())
+	}
+
+	fn undo_action(&mut self, action: UndoAction) -> Result<UndoAction> {
+		match action {
+			UndoAction::ClearVariables(variables) => {
+				let mut locations = Vec::new();
+				for (location, value) in variables {
+					locations.push(location.clone());
+					self.memory.insert(location, value);
+				}
+				Ok(UndoAction::RestoreVariables(locations))
+			}
+			UndoAction::RestoreVariables(locations) => {
+				let mut variables = Vec::new();
+				for location in locations {
+					if let Some(value) = self.memory.remove(&location) {
+						variables.push((location, value));
+					}
+				}
+				Ok(UndoAction::ClearVariables(variables))
+			}
+			action => self.stack.undo_action(action),
+		}
+	}
+
+	// Returns the significant digits and least significant exponent of each decimal
+	// operand. Exact operands do not limit the precision of the result.
+	fn operand_precision(operands: &[&Value]) -> Vec<(usize, isize)> {
+		operands
+			.iter()
+			.filter_map(|value| value.real_number().ok()?.decimal_precision())
+			.collect()
+	}
+
+	// When tracking significant figures, sums and differences are rounded to the
+	// least precise decimal place of the operands
+	fn with_sum_precision(&self, result: Value, y: &Value, x: &Value) -> Value {
+		if !self.format.significant_figures {
+			return result;
+		}
+		match Self::operand_precision(&[y, x])
+			.iter()
+			.map(|(_, exponent)| *exponent)
+			.max()
+		{
+			Some(exponent) => result.with_decimal_exponent(exponent),
+			None => result,
+		}
+	}
+
+	// When tracking significant figures, products and quotients are rounded to the
+	// fewest significant digits of the operands
+	fn with_product_precision(&self, result: Value, y: &Value, x: &Value) -> Value {
+		if !self.format.significant_figures {
+			return result;
+		}
+		match Self::operand_precision(&[y, x])
+			.iter()
+			.map(|(digits, _)| *digits)
+			.min()
+		{
+			Some(digits) => result.with_significant_digits(digits),
+			None => result,
+		}
 	}
 
 	pub fn add(&mut self) -> Result<()> {
-		self.replace_entries(2, (self.entry(1)? + self.entry(0)?)?)
+		let y = self.entry(1)?;
+		let x = self.entry(0)?;
+		let result = self.with_sum_precision((&y + &x)?, &y, &x);
+		self.replace_entries(2, result)
 	}
 
 	pub fn sub(&mut self) -> Result<()> {
-		self.replace_entries(2, (self.entry(1)? - self.entry(0)?)?)
+		let y = self.entry(1)?;
+		let x = self.entry(0)?;
+		let result = self.with_sum_precision((&y - &x)?, &y, &x);
+		self.replace_entries(2, result)
 	}
 
 	pub fn mul(&mut self) -> Result<()> {
-		self.replace_entries(2, (self.entry(1)? * self.entry(0)?)?)
+		let y = self.entry(1)?;
+		let x = self.entry(0)?;
+		let result = self.with_product_precision((&y * &x)?, &y, &x);
+		self.replace_entries(2, result)
 	}
 
 	pub fn div(&mut self) -> Result<()> {
-		self.replace_entries(2, (self.entry(1)? / self.entry(0)?)?)
+		let y = self.entry(1)?;
+		let x = self.entry(0)?;
+		let result = self.with_product_precision((&y / &x)?, &y, &x);
+		self.replace_entries(2, result)
 	}
 
 	pub fn recip(&mut self) -> Result<()> {
@@ -296,8 +700,9 @@ impl Context {
 		self.replace_entries(2, (self.entry(1)?).pow(&self.entry(0)?)?)
 	}
 
+	// Unary functions are applied to each element when given a vector or matrix
 	pub fn sqrt(&mut self) -> Result<()> {
-		self.set_top(self.top()?.sqrt()?)
+		self.set_top(self.top()?.map_elements(&|value| value.sqrt())?)
 	}
 
 	pub fn square(&mut self) -> Result<()> {
@@ -306,73 +711,279 @@ impl Context {
 		self.set_top(square)
 	}
 
+	pub fn abs(&mut self) -> Result<()> {
+		self.set_top(self.top()?.abs()?)
+	}
+
+	pub fn signum(&mut self) -> Result<()> {
+		self.set_top(self.top()?.signum()?)
+	}
+
+	pub fn round_to_multiple(&mut self) -> Result<()> {
+		let value = self.entry(1)?.round_to_multiple(&self.entry(0)?)?;
+		self.replace_entries(2, value)
+	}
+
+	pub fn round_to_significant_digits(&mut self) -> Result<()> {
+		let digits = usize::try_from(&*self.entry(0)?.to_int()?)?;
+		let value = self.entry(1)?.round_to_significant_digits(digits)?;
+		self.replace_entries(2, value)
+	}
+
+	pub fn truncate_to_multiple(&mut self) -> Result<()> {
+		let value = self.entry(1)?.truncate_to_multiple(&self.entry(0)?)?;
+		self.replace_entries(2, value)
+	}
+
 	pub fn percent(&mut self) -> Result<()> {
 		let factor = (self.entry(0)? / Value::Number(100.into()))?;
 		self.set_top((self.entry(1)? * factor)?)
 	}
 
+	pub fn percent_change(&mut self) -> Result<()> {
+		let old = self.entry(1)?;
+		let change = ((self.entry(0)? - old.clone())? / old)?;
+		self.replace_entries(2, (change * Value::Number(100.into()))?)
+	}
+
+	pub fn percent_total(&mut self) -> Result<()> {
+		let fraction = (self.entry(0)? / self.entry(1)?)?;
+		self.replace_entries(2, (fraction * Value::Number(100.into()))?)
+	}
+
+	pub fn markup(&mut self) -> Result<()> {
+		// Price from a cost and a markup percentage relative to the cost
+		let cost = self.entry(1)?;
+		let factor = (Value::Number(1.into()) + (self.entry(0)? / Value::Number(100.into()))?)?;
+		self.replace_entries(2, (cost * factor)?)
+	}
+
+	pub fn margin(&mut self) -> Result<()> {
+		// Margin percentage from a cost and price, relative to the price
+		let cost = self.entry(1)?;
+		let price = self.entry(0)?;
+		let fraction = ((price.clone() - cost)? / price)?;
+		self.replace_entries(2, (fraction * Value::Number(100.into()))?)
+	}
+
+	pub fn discount(&mut self) -> Result<()> {
+		// Price after taking a percentage off of the original price
+		let price = self.entry(1)?;
+		let factor = (Value::Number(1.into()) - (self.entry(0)? / Value::Number(100.into()))?)?;
+		self.replace_entries(2, (price * factor)?)
+	}
+
+	pub fn compound_interest(&mut self) -> Result<()> {
+		// Future value of a principal after compounding a rate, given as a fraction such
+		// as 0.05 for 5%, over a number of periods
+		let principal = self.entry(2)?;
+		let growth = (Value::Number(1.into()) + self.entry(1)?)?;
+		let value = (principal * growth.pow(&self.entry(0)?)?)?;
+		self.replace_entries(3, value)
+	}
+
+	pub fn compound_annual_growth_rate(&mut self) -> Result<()> {
+		// Rate, as a fraction, that grows the beginning value into the ending value when
+		// compounded over a number of periods
+		let ratio = (self.entry(1)? / self.entry(2)?)?;
+		let exponent = (Value::Number(1.into()) / self.entry(0)?)?;
+		let rate = (ratio.pow(&exponent)? - Value::Number(1.into()))?;
+		self.replace_entries(3, rate)
+	}
+
 	pub fn log(&mut self) -> Result<()> {
-		self.set_top(self.top()?.log()?)
+		self.set_top(self.top()?.map_elements(&|value| value.log())?)
 	}
 
 	pub fn exp10(&mut self) -> Result<()> {
-		self.set_top(self.top()?.exp10()?)
+		self.set_top(self.top()?.map_elements(&|value| value.exp10())?)
 	}
 
 	pub fn ln(&mut self) -> Result<()> {
-		self.set_top(self.top()?.ln()?)
+		self.set_top(self.top()?.map_elements(&|value| value.ln())?)
 	}
 
 	pub fn exp(&mut self) -> Result<()> {
-		self.set_top(self.top()?.exp()?)
+		self.set_top(self.top()?.map_elements(&|value| value.exp())?)
 	}
 
 	pub fn sin(&mut self) -> Result<()> {
-		self.set_top(self.top()?.sin(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.sin(angle_mode))?)
 	}
 
 	pub fn cos(&mut self) -> Result<()> {
-		self.set_top(self.top()?.cos(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.cos(angle_mode))?)
 	}
 
 	pub fn tan(&mut self) -> Result<()> {
-		self.set_top(self.top()?.tan(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.tan(angle_mode))?)
 	}
 
 	pub fn asin(&mut self) -> Result<()> {
-		self.set_top(self.top()?.asin(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.asin(angle_mode))?)
 	}
 
 	pub fn acos(&mut self) -> Result<()> {
-		self.set_top(self.top()?.acos(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.acos(angle_mode))?)
 	}
 
 	pub fn atan(&mut self) -> Result<()> {
-		self.set_top(self.top()?.atan(self.angle_mode)?)
+		let angle_mode = self.angle_mode;
+		self.set_top(self.top()?.map_elements(&|value| value.atan(angle_mode))?)
+	}
+
+	pub fn atan2(&mut self) -> Result<()> {
+		let value = self.entry(1)?.atan2(&self.entry(0)?, self.angle_mode)?;
+		self.replace_entries(2, value)
+	}
+
+	pub fn hypot(&mut self) -> Result<()> {
+		let value = self.entry(1)?.hypot(&self.entry(0)?)?;
+		self.replace_entries(2, value)
 	}
 
 	pub fn sinh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.sinh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.sinh())?)
 	}
 
 	pub fn cosh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.cosh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.cosh())?)
 	}
 
 	pub fn tanh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.tanh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.tanh())?)
 	}
 
 	pub fn asinh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.asinh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.asinh())?)
 	}
 
 	pub fn acosh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.acosh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.acosh())?)
 	}
 
 	pub fn atanh(&mut self) -> Result<()> {
-		self.set_top(self.top()?.atanh()?)
+		self.set_top(self.top()?.map_elements(&|value| value.atanh())?)
+	}
+
+	pub fn gamma(&mut self) -> Result<()> {
+		self.set_top(self.top()?.map_elements(&|value| value.gamma())?)
+	}
+
+	pub fn ln_gamma(&mut self) -> Result<()> {
+		self.set_top(self.top()?.map_elements(&|value| value.ln_gamma())?)
+	}
+
+	pub fn erf(&mut self) -> Result<()> {
+		self.set_top(self.top()?.map_elements(&|value| value.erf())?)
+	}
+
+	pub fn erfc(&mut self) -> Result<()> {
+		self.set_top(self.top()?.map_elements(&|value| value.erfc())?)
+	}
+
+	pub fn int_floor(&mut self) -> Result<()> {
+		self.set_top(self.top()?.floor_to_int_value()?)
+	}
+
+	pub fn int_ceil(&mut self) -> Result<()> {
+		self.set_top(self.top()?.ceil_to_int_value()?)
+	}
+
+	pub fn int_round(&mut self) -> Result<()> {
+		self.set_top(self.top()?.round_to_int_value()?)
+	}
+
+	pub fn integer_part(&mut self) -> Result<()> {
+		self.set_top(self.top()?.integer_part_value()?)
+	}
+
+	pub fn fractional_part(&mut self) -> Result<()> {
+		self.set_top(self.top()?.fractional_part_value()?)
+	}
+
+	pub fn simplify(&mut self) -> Result<()> {
+		self.set_top(self.top()?.simplified_value()?)
+	}
+
+	pub fn float_to_bits(&mut self) -> Result<()> {
+		// Reinterpret the IEEE-754 double precision encoding of the value as an integer
+		let bits = self.top()?.real_number()?.to_f64().to_bits();
+		self.set_top(Value::Number(Number::Integer(bits.into())))
+	}
+
+	pub fn bits_to_float(&mut self) -> Result<()> {
+		// Integer must fit in the 64 bits of a double precision encoding
+		let bits = u64::try_from(&self.top()?.exact_int()?).map_err(|_| Error::ValueOutOfRange)?;
+		self.set_top(Value::Number(Number::Decimal(f64::from_bits(bits).into())))
+	}
+
+	pub fn clamp(&mut self) -> Result<()> {
+		// Value is given below the lower and upper bounds on the stack
+		let value = self.entry(2)?;
+		let lower = self.entry(1)?;
+		let upper = self.entry(0)?;
+		if lower.real_number()? > upper.real_number()? {
+			return Err(Error::ValueOutOfRange);
+		}
+		let result = if value.real_number()? < lower.real_number()? {
+			lower
+		} else if value.real_number()? > upper.real_number()? {
+			upper
+		} else {
+			value
+		};
+		self.replace_entries(3, result)
+	}
+
+	pub fn normal_cdf(&mut self) -> Result<()> {
+		// Mean and standard deviation are given below the value on the stack
+		let x = self.entry(0)?.real_number()?.clone();
+		let stddev = self.entry(1)?.real_number()?.clone();
+		let mean = self.entry(2)?.real_number()?.clone();
+		let result = stats::normal_cdf(&x, &mean, &stddev)?;
+		self.replace_entries(3, Value::Number(result))
+	}
+
+	pub fn normal_inv_cdf(&mut self) -> Result<()> {
+		// Mean and standard deviation are given below the probability on the stack
+		let p = self.entry(0)?.real_number()?.clone();
+		let stddev = self.entry(1)?.real_number()?.clone();
+		let mean = self.entry(2)?.real_number()?.clone();
+		let result = stats::normal_inv_cdf(&p, &mean, &stddev)?;
+		self.replace_entries(3, Value::Number(result))
+	}
+
+	pub fn div_mod(&mut self) -> Result<()> {
+		// Euclidean division, the remainder is never negative
+		let divisor = self.entry(0)?.exact_int()?;
+		let dividend = self.entry(1)?.exact_int()?;
+		if divisor == 0.to_bigint().unwrap() {
+			return Err(Error::NotARealNumber);
+		}
+		let (mut quotient, mut remainder) = dividend.div_rem(&divisor);
+		if remainder.sign() == Sign::Minus {
+			if divisor.sign() == Sign::Minus {
+				quotient += 1;
+				remainder -= &divisor;
+			} else {
+				quotient -= 1;
+				remainder += &divisor;
+			}
+		}
+		self.replace_entries_with_multiple(
+			2,
+			[
+				Value::Number(Number::Integer(quotient)),
+				Value::Number(Number::Integer(remainder)),
+			]
+			.to_vec(),
+		)
 	}
 
 	pub fn and(&mut self) -> Result<()> {
@@ -397,19 +1008,106 @@ impl Context {
 	}
 
 	pub fn not(&mut self) -> Result<()> {
-		let value = Number::Integer(!&*self.top()?.to_int()?);
-		self.set_top(Value::Number(value))
+		let value = self.value_from_bit_pattern(!&*self.top()?.to_int()?);
+		self.set_top(value)
 	}
 
-	pub fn shl(&mut self) -> Result<()> {
-		let x = self.entry(0)?;
-		let mut x = x.to_int()?;
+	fn value_from_bit_pattern(&self, bits: BigInt) -> Value {
+		// Results of bitwise operations always wrap to the integer size, even when
+		// arithmetic results are saturated.
+		Stack::value_for_integer_mode(
+			&self.format.integer_mode,
+			IntegerOverflowMode::Wrap,
+			Value::Number(Number::Integer(bits)),
+		)
+	}
+
+	fn integer_bit_pattern(&self, value: &Value) -> Result<BigUint> {
+		// Get the bits of the integer as stored in the current integer mode
+		let int = value.to_int()?;
+		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			let mask = 2.to_bigint().unwrap().pow(size as u32) - 1.to_bigint().unwrap();
+			Ok((&*int & &mask).to_biguint().unwrap())
+		} else {
+			int.to_biguint().ok_or(Error::ValueOutOfRange)
+		}
+	}
+
+	pub fn pop_count(&mut self) -> Result<()> {
+		let bits = self.integer_bit_pattern(&self.top()?)?;
+		self.set_top(bits.count_ones().into())
+	}
+
+	pub fn count_leading_zeros(&mut self) -> Result<()> {
+		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			let bits = self.integer_bit_pattern(&self.top()?)?;
+			self.set_top((size as u64 - bits.bits()).into())
+		} else {
+			Err(Error::RequiresSizedIntegerMode)
+		}
+	}
+
+	pub fn count_trailing_zeros(&mut self) -> Result<()> {
+		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			let bits = self.integer_bit_pattern(&self.top()?)?;
+			self.set_top(bits.trailing_zeros().unwrap_or(size as u64).into())
+		} else {
+			Err(Error::RequiresSizedIntegerMode)
+		}
+	}
+
+	pub fn byte_swap(&mut self) -> Result<()> {
+		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			if size % 8 != 0 {
+				return Err(Error::ValueOutOfRange);
+			}
+			let bits = self.integer_bit_pattern(&self.top()?)?;
+			let mut bytes = bits.to_bytes_le();
+			bytes.resize(size / 8, 0);
+			bytes.reverse();
+			let value = BigUint::from_bytes_le(&bytes).to_bigint().unwrap();
+			self.set_top(self.value_from_bit_pattern(value))
+		} else {
+			Err(Error::RequiresSizedIntegerMode)
+		}
+	}
+
+	pub fn bit_reverse(&mut self) -> Result<()> {
 		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
-			if size.is_power_of_two() {
-				x = Cow::Owned(&*x & &(size - 1).to_bigint().unwrap());
+			let bits = self.integer_bit_pattern(&self.top()?)?;
+			let mut value = 0.to_bigint().unwrap();
+			for i in 0..size as u64 {
+				value <<= 1;
+				if bits.bit(i) {
+					value |= 1.to_bigint().unwrap();
+				}
 			}
+			self.set_top(self.value_from_bit_pattern(value))
+		} else {
+			Err(Error::RequiresSizedIntegerMode)
 		}
-		let x = u32::try_from(&*x)?;
+	}
+
+	fn sized_integer_mask(size: usize) -> BigUint {
+		(1.to_biguint().unwrap() << size) - 1.to_biguint().unwrap()
+	}
+
+	fn sized_shift_count(&self, value: &Value, size: usize) -> Result<u32> {
+		// Shift counts wrap at the integer width, as they do in hardware
+		let count = value.to_int()?.mod_floor(&size.to_bigint().unwrap());
+		Ok(u32::try_from(&count)?)
+	}
+
+	pub fn shl(&mut self) -> Result<()> {
+		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			// Bits shifted past the integer width are discarded
+			let x = self.sized_shift_count(&self.entry(0)?, size)?;
+			let y = self.integer_bit_pattern(&self.entry(1)?)?;
+			let value = (y << x) & Self::sized_integer_mask(size);
+			return self.replace_entries(2, self.value_from_bit_pattern(value.into()));
+		}
+		let x = self.entry(0)?;
+		let x = u32::try_from(&*x.to_int()?)?;
 		let y = self.entry(1)?;
 		let y = y.to_int()?;
 		if (y.bits() + x as u64) > MAX_INTEGER_BITS {
@@ -420,14 +1118,11 @@ impl Context {
 	}
 
 	pub fn shr(&mut self) -> Result<()> {
-		let x = self.entry(0)?;
-		let mut x = x.to_int()?;
-		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
-			if size.is_power_of_two() {
-				x = Cow::Owned(&*x & (size - 1).to_bigint().unwrap());
-			}
-		}
-		let x = u32::try_from(&*x)?;
+		let x = if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
+			self.sized_shift_count(&self.entry(0)?, size)?
+		} else {
+			u32::try_from(&*self.entry(0)?.to_int()?)?
+		};
 		let y = self.entry(1)?;
 		let y = y.to_int()?;
 		let value = Value::Number(Number::Integer(&*y >> x));
@@ -436,19 +1131,10 @@ impl Context {
 
 	pub fn rotate_left(&mut self) -> Result<()> {
 		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
-			let x = self.entry(0)?;
-			let mut x = x.to_int()?;
-			if size.is_power_of_two() {
-				x = Cow::Owned(&*x & (size - 1).to_bigint().unwrap());
-			}
-			if let Ok(x) = u32::try_from(&*x) {
-				let y = self.entry(1)?;
-				let y = y.to_int()?;
-				let value = (&*y << x) | (&*y >> ((size as u32) - x));
-				self.replace_entries(2, Value::Number(Number::Integer(value)))
-			} else {
-				Err(Error::ValueOutOfRange)
-			}
+			let x = self.sized_shift_count(&self.entry(0)?, size)?;
+			let y = self.integer_bit_pattern(&self.entry(1)?)?;
+			let value = ((&y << x) | (&y >> ((size as u32) - x))) & Self::sized_integer_mask(size);
+			self.replace_entries(2, self.value_from_bit_pattern(value.into()))
 		} else {
 			Err(Error::RequiresSizedIntegerMode)
 		}
@@ -456,19 +1142,10 @@ impl Context {
 
 	pub fn rotate_right(&mut self) -> Result<()> {
 		if let IntegerMode::SizedInteger(size, _) = self.format.integer_mode {
-			let x = self.entry(0)?;
-			let mut x = x.to_int()?;
-			if size.is_power_of_two() {
-				x = Cow::Owned(&*x & (size - 1).to_bigint().unwrap());
-			}
-			if let Ok(x) = u32::try_from(&*x) {
-				let y = self.entry(1)?;
-				let y = y.to_int()?;
-				let value = (&*y >> x) | (&*y << ((size as u32) - x));
-				self.replace_entries(2, Value::Number(Number::Integer(value)))
-			} else {
-				Err(Error::ValueOutOfRange)
-			}
+			let x = self.sized_shift_count(&self.entry(0)?, size)?;
+			let y = self.integer_bit_pattern(&self.entry(1)?)?;
+			let value = ((&y >> x) | (&y << ((size as u32) - x))) & Self::sized_integer_mask(size);
+			self.replace_entries(2, self.value_from_bit_pattern(value.into()))
 		} else {
 			Err(Error::RequiresSizedIntegerMode)
 		}
@@ -510,6 +1187,74 @@ impl Context {
 		}
 	}
 
+	pub fn day_of_week(&mut self) -> Result<()> {
+		// Weekday is numbered starting with Sunday as zero
+		let date = self.top()?.date_part()?;
+		self.set_top(date.weekday().num_days_from_sunday().into())
+	}
+
+	pub fn day_of_year(&mut self) -> Result<()> {
+		let date = self.top()?.date_part()?;
+		self.set_top(date.ordinal().into())
+	}
+
+	pub fn iso_week(&mut self) -> Result<()> {
+		let date = self.top()?.date_part()?;
+		self.set_top(date.iso_week().week().into())
+	}
+
+	pub fn quarter(&mut self) -> Result<()> {
+		let date = self.top()?.date_part()?;
+		self.set_top((date.month0() / 3 + 1).into())
+	}
+
+	pub fn to_julian_day(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.to_julian_day()?))
+	}
+
+	pub fn from_julian_day(&mut self) -> Result<()> {
+		self.set_top(Value::from_julian_day(self.top()?.real_number()?)?)
+	}
+
+	pub fn to_unix_time(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.to_unix_time()?))
+	}
+
+	pub fn from_unix_time(&mut self) -> Result<()> {
+		self.set_top(Value::from_unix_time(self.top()?.real_number()?)?)
+	}
+
+	pub fn add_months(&mut self) -> Result<()> {
+		let months = i64::try_from(&*self.entry(0)?.to_int()?)?;
+		self.replace_entries(2, self.entry(1)?.add_months(months)?)
+	}
+
+	pub fn add_years(&mut self) -> Result<()> {
+		let years = i64::try_from(&*self.entry(0)?.to_int()?)?;
+		self.replace_entries(2, self.entry(1)?.add_years(years)?)
+	}
+
+	pub fn to_hms(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.real_number()?.to_hms()?))
+	}
+
+	pub fn from_hms(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.real_number()?.hms_to_hours()?))
+	}
+
+	pub fn to_dms(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.real_number()?.to_dms()?))
+	}
+
+	pub fn from_dms(&mut self) -> Result<()> {
+		self.set_top(Value::Number(self.top()?.real_number()?.dms_to_degrees()?))
+	}
+
+	pub fn continued_fraction(&mut self) -> Result<()> {
+		let vector = self.top()?.real_number()?.continued_fraction()?;
+		self.set_top(Value::Vector(vector))
+	}
+
 	pub fn clear_units(&mut self) -> Result<()> {
 		let value = if let Value::NumberWithUnit(num, _) = self.top()? {
 			Value::Number(num)
@@ -519,6 +1264,8 @@ impl Context {
 		self.set_top(value)
 	}
 
+	// Unit operations compute the complete result before replacing the top of the stack,
+	// so that a single undo restores both the original number and its units.
 	pub fn add_unit(&mut self, unit: Unit) -> Result<()> {
 		let value = self.top()?.add_unit(unit)?;
 		self.set_top(value)
@@ -560,6 +1307,21 @@ impl Context {
 		self.set_top(value)
 	}
 
+	pub fn to_base_units(&mut self) -> Result<()> {
+		let value = self.top()?.to_base_units()?;
+		self.set_top(value)
+	}
+
+	pub fn to_metric(&mut self) -> Result<()> {
+		let value = self.top()?.to_metric_units()?;
+		self.set_top(value)
+	}
+
+	pub fn to_imperial(&mut self) -> Result<()> {
+		let value = self.top()?.to_imperial_units()?;
+		self.set_top(value)
+	}
+
 	pub fn sum(&mut self) -> Result<()> {
 		if let Value::Vector(vector) = self.top()? {
 			self.set_top(vector.sum()?)
@@ -568,6 +1330,45 @@ impl Context {
 		}
 	}
 
+	pub fn sort_vector(&mut self) -> Result<()> {
+		if let Value::Vector(vector) = self.top()? {
+			self.set_top(Value::Vector(vector.sorted()?))
+		} else {
+			Err(Error::DataTypeMismatch)
+		}
+	}
+
+	pub fn reverse_vector(&mut self) -> Result<()> {
+		if let Value::Vector(vector) = self.top()? {
+			self.set_top(Value::Vector(vector.reversed()?))
+		} else {
+			Err(Error::DataTypeMismatch)
+		}
+	}
+
+	pub fn vector_get(&mut self) -> Result<()> {
+		// Elements are numbered starting at one
+		let idx = usize::try_from(&*self.entry(0)?.to_int()?)?;
+		if let Value::Vector(vector) = self.entry(1)? {
+			let idx = idx.checked_sub(1).ok_or(Error::IndexOutOfRange)?;
+			self.replace_entries(2, vector.get(idx)?)
+		} else {
+			Err(Error::DataTypeMismatch)
+		}
+	}
+
+	pub fn vector_put(&mut self) -> Result<()> {
+		let value = self.entry(0)?;
+		let idx = usize::try_from(&*self.entry(1)?.to_int()?)?;
+		if let Value::Vector(mut vector) = self.entry(2)? {
+			let idx = idx.checked_sub(1).ok_or(Error::IndexOutOfRange)?;
+			vector.set(idx, value)?;
+			self.replace_entries(3, Value::Vector(vector))
+		} else {
+			Err(Error::DataTypeMismatch)
+		}
+	}
+
 	pub fn mean(&mut self) -> Result<()> {
 		if let Value::Vector(vector) = self.top()? {
 			self.set_top(vector.mean()?)
@@ -787,6 +1588,205 @@ impl Context {
 		}
 	}
 
+	pub fn range(&mut self) -> Result<()> {
+		let start = self.entry(2)?.real_number()?.clone();
+		let stop = self.entry(1)?.real_number()?.clone();
+		let step = self.entry(0)?.real_number()?.clone();
+
+		// Step must move from the start toward the stop
+		let span = &stop - &start;
+		if step.is_zero() || (!span.is_zero() && span.is_negative() != step.is_negative()) {
+			return Err(Error::ValueOutOfRange);
+		}
+
+		// Compute each element from the start to avoid accumulating rounding errors
+		let count = usize::try_from(&(&span / &step).floor_to_int()?)? + 1;
+		let mut result = Vector::new()?;
+		for i in 0..count {
+			result.push(Value::Number(
+				&start + &(&Number::Integer(i.into()) * &step),
+			))?;
+		}
+		self.replace_entries(3, Value::Vector(result))
+	}
+
+	pub fn linspace(&mut self) -> Result<()> {
+		let start = self.entry(2)?.real_number()?.clone();
+		let stop = self.entry(1)?.real_number()?.clone();
+		let count = usize::try_from(&*self.entry(0)?.to_int()?)?;
+		if count == 0 {
+			return Err(Error::ValueOutOfRange);
+		}
+
+		let mut result = Vector::new()?;
+		if count == 1 {
+			result.push(Value::Number(start))?;
+		} else {
+			let step = &(&stop - &start) / &Number::Integer((count - 1).into());
+			for i in 0..count {
+				result.push(Value::Number(
+					&start + &(&Number::Integer(i.into()) * &step),
+				))?;
+			}
+		}
+		self.replace_entries(3, Value::Vector(result))
+	}
+
+	fn elements_row_major(value: &Value) -> Result<Vec<Value>> {
+		let mut result = Vec::new();
+		match value {
+			Value::Vector(vector) => {
+				for i in 0..vector.len() {
+					result.push(vector.get(i)?);
+				}
+			}
+			Value::Matrix(matrix) => {
+				for row in 0..matrix.rows() {
+					for col in 0..matrix.cols() {
+						result.push(matrix.get(row, col)?);
+					}
+				}
+			}
+			_ => return Err(Error::DataTypeMismatch),
+		}
+		Ok(result)
+	}
+
+	pub fn reshape(&mut self) -> Result<()> {
+		let rows = usize::try_from(&*self.entry(1)?.to_int()?)?;
+		let cols = usize::try_from(&*self.entry(0)?.to_int()?)?;
+		let elements = Self::elements_row_major(&self.entry(2)?)?;
+		if rows == 0 || cols == 0 || rows.checked_mul(cols) != Some(elements.len()) {
+			return Err(Error::DimensionMismatch);
+		}
+
+		if rows == 1 {
+			// Matrix of one row is always stored as a vector
+			let mut vector = Vector::new()?;
+			for element in elements {
+				vector.push(element)?;
+			}
+			self.replace_entries(3, Value::Vector(vector))
+		} else {
+			let mut result = Matrix::new(rows, cols)?;
+			for (i, element) in elements.into_iter().enumerate() {
+				result.set(i / cols, i % cols, element)?;
+			}
+			self.replace_entries(3, Value::Matrix(result))
+		}
+	}
+
+	pub fn flatten(&mut self) -> Result<()> {
+		let mut result = Vector::new()?;
+		for element in Self::elements_row_major(&self.top()?)? {
+			result.push(element)?;
+		}
+		self.set_top(Value::Vector(result))
+	}
+
+	fn matrix_operand(value: &Value) -> Result<Matrix> {
+		// Vectors are treated as a matrix with a single row
+		match value {
+			Value::Vector(vector) => {
+				let mut result = Matrix::new(1, vector.len())?;
+				for i in 0..vector.len() {
+					result.set(0, i, vector.get(i)?)?;
+				}
+				Ok(result)
+			}
+			Value::Matrix(matrix) => Ok(matrix.clone()),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	fn matrix_result(matrix: Matrix) -> Result<Value> {
+		if matrix.rows() == 1 {
+			// Matrix of one row is always stored as a vector
+			let mut vector = Vector::new()?;
+			for i in 0..matrix.cols() {
+				vector.push(matrix.get(0, i)?)?;
+			}
+			Ok(Value::Vector(vector))
+		} else {
+			Ok(Value::Matrix(matrix))
+		}
+	}
+
+	pub fn augment(&mut self) -> Result<()> {
+		let left = Self::matrix_operand(&self.entry(1)?)?;
+		let right = Self::matrix_operand(&self.entry(0)?)?;
+		self.replace_entries(2, Self::matrix_result(left.hconcat(&right)?)?)
+	}
+
+	pub fn stack_matrices(&mut self) -> Result<()> {
+		let top = Self::matrix_operand(&self.entry(1)?)?;
+		let bottom = Self::matrix_operand(&self.entry(0)?)?;
+		self.replace_entries(2, Self::matrix_result(top.vconcat(&bottom)?)?)
+	}
+
+	fn matrix_index(value: &Value) -> Result<usize> {
+		// Rows and columns are numbered starting at one
+		usize::try_from(&*value.to_int()?)?
+			.checked_sub(1)
+			.ok_or(Error::IndexOutOfRange)
+	}
+
+	pub fn submatrix(&mut self) -> Result<()> {
+		// Top left and bottom right elements of the block are given below the matrix
+		let matrix = Self::matrix_operand(&self.entry(4)?)?;
+		let first_row = Self::matrix_index(&self.entry(3)?)?;
+		let first_col = Self::matrix_index(&self.entry(2)?)?;
+		let last_row = Self::matrix_index(&self.entry(1)?)?;
+		let last_col = Self::matrix_index(&self.entry(0)?)?;
+		if last_row < first_row || last_col < first_col {
+			return Err(Error::IndexOutOfRange);
+		}
+		let result = matrix.submatrix(
+			first_row,
+			first_col,
+			last_row - first_row + 1,
+			last_col - first_col + 1,
+		)?;
+		self.replace_entries(5, Self::matrix_result(result)?)
+	}
+
+	pub fn delete_row(&mut self) -> Result<()> {
+		let matrix = Self::matrix_operand(&self.entry(1)?)?;
+		let row = Self::matrix_index(&self.entry(0)?)?;
+		self.replace_entries(2, Self::matrix_result(matrix.delete_row(row)?)?)
+	}
+
+	pub fn delete_column(&mut self) -> Result<()> {
+		let matrix = Self::matrix_operand(&self.entry(1)?)?;
+		let col = Self::matrix_index(&self.entry(0)?)?;
+		self.replace_entries(2, Self::matrix_result(matrix.delete_col(col)?)?)
+	}
+
+	pub fn lu_decomposition(&mut self) -> Result<()> {
+		// Replaces the matrix with L, U and the row permutation, numbered from one
+		let (lower, upper, permutation) = Self::matrix_operand(&self.top()?)?.lu()?;
+		let mut rows = Vector::new()?;
+		for row in permutation {
+			rows.push(Value::Number(((row + 1) as u64).into()))?;
+		}
+		self.replace_entries_with_multiple(
+			1,
+			[
+				Self::matrix_result(lower)?,
+				Self::matrix_result(upper)?,
+				Value::Vector(rows),
+			]
+			.to_vec(),
+		)
+	}
+
+	pub fn eigenvalues(&mut self) -> Result<()> {
+		match self.top()? {
+			Value::Matrix(matrix) => self.set_top(Value::Vector(matrix.eigenvalues()?)),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
 	pub fn identity_matrix(&mut self) -> Result<()> {
 		let size = usize::try_from(&*self.top()?.to_int()?)?;
 		if size == 0 {
@@ -796,11 +1796,36 @@ impl Context {
 			result.push(1.into())?;
 			self.set_top(Value::Vector(result))
 		} else {
-			let mut result = Matrix::new(size, size)?;
-			for i in 0..size {
-				result.set(i, i, 1.into())?;
-			}
-			self.set_top(Value::Matrix(result))
+			self.set_top(Value::Matrix(Matrix::identity(size)?))
+		}
+	}
+
+	pub fn matrix_pow(&mut self) -> Result<()> {
+		let power = self.entry(0)?.real_number()?.clone();
+		match self.entry(1)? {
+			Value::Matrix(matrix) => self.replace_entries(2, Value::Matrix(matrix.pow(&power)?)),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn trace(&mut self) -> Result<()> {
+		match self.top()? {
+			Value::Matrix(matrix) => self.set_top(matrix.trace()?),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn row_sums(&mut self) -> Result<()> {
+		match self.top()? {
+			Value::Matrix(matrix) => self.set_top(Value::Vector(matrix.row_sums()?)),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn col_sums(&mut self) -> Result<()> {
+		match self.top()? {
+			Value::Matrix(matrix) => self.set_top(Value::Vector(matrix.col_sums()?)),
+			_ => Err(Error::DataTypeMismatch),
 		}
 	}
 
@@ -863,6 +1888,86 @@ impl Context {
 		}
 	}
 
+	pub fn to_polar(&mut self) -> Result<()> {
+		// Polar form is represented as a two element vector containing the
+		// magnitude and the angle. The angle is tagged with the current angle
+		// unit so that it converts back correctly even if the mode changes.
+		let value = self.top()?.complex_number()?.into_owned();
+		let angle = value.polar_angle();
+		let mut vector = Vector::new()?;
+		vector.push(Value::Number(value.magnitude()))?;
+		vector.push(Value::NumberWithUnit(
+			angle.angle_from_radians(self.angle_mode).into_owned(),
+			CompositeUnit::single_unit(self.angle_mode.into()),
+		))?;
+		self.set_top(Value::Vector(vector))
+	}
+
+	pub fn real_part(&mut self) -> Result<()> {
+		let value = self.top()?.complex_number()?.into_owned();
+		self.set_top(Value::Number(value.take_real_part()))
+	}
+
+	pub fn imaginary_part(&mut self) -> Result<()> {
+		// Real numbers have an imaginary part of zero
+		let value = self.top()?.complex_number()?.into_owned();
+		self.set_top(Value::Number(value.imaginary_part().clone()))
+	}
+
+	pub fn round_complex(&mut self) -> Result<()> {
+		// Each part is rounded to the display precision on its own, so that a small
+		// imaginary part keeps its digits next to a large real part
+		let digits = self.format.precision;
+		let value = self.top()?.complex_number()?.into_owned();
+		self.set_top(Value::check_complex(ComplexNumber::from_parts(
+			value.real_part().round_to_significant_digits(digits)?,
+			value.imaginary_part().round_to_significant_digits(digits)?,
+		))?)
+	}
+
+	pub fn swap_complex_parts(&mut self) -> Result<()> {
+		// Real numbers are treated as having a zero imaginary part, so they become
+		// purely imaginary
+		let value = self.top()?.complex_number()?.into_owned();
+		self.set_top(Value::check_complex(ComplexNumber::from_parts(
+			value.imaginary_part().clone(),
+			value.real_part().clone(),
+		))?)
+	}
+
+	pub fn to_rectangular(&mut self) -> Result<()> {
+		// Convert a two element vector in polar form (see `to_polar`) into
+		// a complex number.
+		let vector = match self.top()? {
+			Value::Vector(vector) => vector,
+			_ => return Err(Error::DataTypeMismatch),
+		};
+		if vector.len() != 2 {
+			return Err(Error::DimensionMismatch);
+		}
+		let magnitude = vector.get(0)?;
+		let angle = match vector.get(1)? {
+			Value::NumberWithUnit(num, unit) => {
+				// The angle must have only an angle unit, such as the one tagged by
+				// `to_polar`. Any other unit cannot be interpreted as an angle.
+				let mut unit = unit.clone();
+				let value = unit.convert_single_unit(&num, AngleUnit::Radians.into())?;
+				if unit.units.len() != 1 {
+					return Err(Error::IncompatibleUnits);
+				}
+				value
+			}
+			angle => angle
+				.real_number()?
+				.angle_to_radians(self.angle_mode)
+				.into_owned(),
+		};
+		self.set_top(Value::check_complex(ComplexNumber::from_polar(
+			magnitude.real_number()?,
+			&angle,
+		))?)
+	}
+
 	pub fn add_to_vector(&mut self) -> Result<()> {
 		let top = self.entry(0)?;
 		if let Value::Vector(existing_vector) = top {
@@ -923,3 +2028,273 @@ impl Context {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::unit::DistanceUnit;
+
+	#[test]
+	fn polar_round_trip() {
+		let mut context = Context::new();
+		context
+			.push(Value::Complex(ComplexNumber::from_parts(
+				3.into(),
+				4.into(),
+			)))
+			.unwrap();
+		context.to_polar().unwrap();
+		context.to_rectangular().unwrap();
+		let value = context
+			.top()
+			.unwrap()
+			.complex_number()
+			.unwrap()
+			.into_owned();
+		assert!((value.real_part().to_f64() - 3.0).abs() < 1e-12);
+		assert!((value.imaginary_part().to_f64() - 4.0).abs() < 1e-12);
+	}
+
+	#[test]
+	fn rectangular_rejects_non_angle_unit() {
+		let mut context = Context::new();
+		let mut vector = Vector::new().unwrap();
+		vector.push(Value::Number(2.into())).unwrap();
+		vector
+			.push(Value::NumberWithUnit(
+				3.into(),
+				CompositeUnit::single_unit(Unit::Distance(DistanceUnit::Meters)),
+			))
+			.unwrap();
+		context.push(Value::Vector(vector)).unwrap();
+		assert_eq!(
+			context.to_rectangular().err(),
+			Some(Error::IncompatibleUnits)
+		);
+	}
+
+	#[test]
+	fn clear_x_then_input_replaces_top() {
+		let mut context = Context::new();
+		context.input_value(Value::Number(1.into())).unwrap();
+		context.input_value(Value::Number(2.into())).unwrap();
+		context.clear_x().unwrap();
+		assert_eq!(context.stack_len(), 2);
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 0.0);
+
+		context.input_value(Value::Number(7.into())).unwrap();
+		assert_eq!(context.stack_len(), 2);
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 7.0);
+		assert_eq!(context.entry(1).unwrap().to_f64().unwrap(), 1.0);
+
+		// Lift is enabled again after the replacement
+		context.input_value(Value::Number(8.into())).unwrap();
+		assert_eq!(context.stack_len(), 3);
+	}
+
+	#[test]
+	fn push_undo_redo() {
+		let _lock = crate::undo::TEST_LOCK.lock();
+		let mut context = Context::new_with_undo();
+		context.push(Value::Number(1.into())).unwrap();
+		context.push(Value::Number(2.into())).unwrap();
+
+		context.undo().unwrap();
+		assert_eq!(context.stack_len(), 1);
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 1.0);
+
+		context.redo().unwrap();
+		assert_eq!(context.stack_len(), 2);
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 2.0);
+		assert_eq!(context.redo().err(), Some(Error::RedoBufferEmpty));
+	}
+
+	#[test]
+	fn compound_interest_round_trip() {
+		let mut context = Context::new();
+		context.push(Value::Number(1000.into())).unwrap();
+		context
+			.push(Value::Number(Number::from_str("0.05").unwrap()))
+			.unwrap();
+		context.push(Value::Number(10.into())).unwrap();
+		context.compound_interest().unwrap();
+		let future_value = context.top().unwrap().to_f64().unwrap();
+		assert!((future_value - 1628.894626777442).abs() < 1e-9);
+
+		// Growing 1000 into the future value over the same periods recovers the rate
+		context.push(Value::Number(1000.into())).unwrap();
+		context.swap(0, 1).unwrap();
+		context.push(Value::Number(10.into())).unwrap();
+		context.compound_annual_growth_rate().unwrap();
+		assert!((context.top().unwrap().to_f64().unwrap() - 0.05).abs() < 1e-12);
+	}
+
+	#[test]
+	fn undo_unit_conversion() {
+		let _lock = crate::undo::TEST_LOCK.lock();
+		let mut context = Context::new_with_undo();
+		context.push(Value::Number(1500.into())).unwrap();
+		context
+			.add_unit(Unit::Distance(DistanceUnit::Meters))
+			.unwrap();
+		context
+			.convert_to_unit(Unit::Distance(DistanceUnit::Kilometers))
+			.unwrap();
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 1.5);
+
+		// A single undo restores both the number and its unit
+		context.undo().unwrap();
+		match context.top().unwrap() {
+			Value::NumberWithUnit(value, unit) => {
+				assert_eq!(value.to_f64(), 1500.0);
+				assert!(
+					unit.units.values().cloned().collect::<Vec<_>>()
+						== vec![(Unit::Distance(DistanceUnit::Meters), 1)]
+				);
+			}
+			_ => panic!("conversion undo lost the unit"),
+		}
+	}
+
+	#[test]
+	fn precision_limits_displayed_digits() {
+		let mut context = Context::new();
+		context.set_precision(6).unwrap();
+		let pi = Number::Decimal(intel_dfp::Decimal::pi());
+		assert_eq!(context.format().format_number(&pi).to_string(), "3.14159");
+		assert_eq!(context.set_precision(0).err(), Some(Error::ValueOutOfRange));
+		assert_eq!(
+			context.set_precision(MAX_PRECISION + 1).err(),
+			Some(Error::ValueOutOfRange)
+		);
+	}
+
+	#[test]
+	fn delete_variable() {
+		let mut context = Context::new();
+		context
+			.write(Location::Variable('A'), Value::Number(1.into()))
+			.unwrap();
+		context
+			.write(Location::Variable('B'), Value::Number(2.into()))
+			.unwrap();
+		assert!(
+			context.memory_locations() == vec![Location::Variable('A'), Location::Variable('B')]
+		);
+
+		context.delete(&Location::Variable('A')).unwrap();
+		assert!(context.memory_locations() == vec![Location::Variable('B')]);
+		assert_eq!(
+			context.delete(&Location::Variable('A')).err(),
+			Some(Error::ValueNotDefined)
+		);
+	}
+
+	#[test]
+	fn clear_all_variables() {
+		let _lock = crate::undo::TEST_LOCK.lock();
+		let mut context = Context::new_with_undo();
+		context
+			.write(Location::Variable('A'), Value::Number(1.into()))
+			.unwrap();
+		context
+			.write(Location::Integer(3), Value::Number(2.into()))
+			.unwrap();
+		context.clear_all_variables();
+		assert_eq!(
+			context.read(&Location::Variable('A')).err(),
+			Some(Error::ValueNotDefined)
+		);
+		assert_eq!(
+			context.read(&Location::Integer(3)).err(),
+			Some(Error::ValueNotDefined)
+		);
+
+		// Clearing can be undone
+		context.undo().unwrap();
+		assert_eq!(
+			context
+				.read(&Location::Variable('A'))
+				.unwrap()
+				.to_f64()
+				.unwrap(),
+			1.0
+		);
+	}
+
+	#[test]
+	fn exact_only_rejects_inexact_results() {
+		let mut context = Context::new();
+		context.toggle_exact_only();
+		context.push(Value::Number(2.into())).unwrap();
+		assert_eq!(context.sqrt().err(), Some(Error::InexactResult));
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 2.0);
+
+		context.push(Value::Number(4.into())).unwrap();
+		context.sqrt().unwrap();
+		assert!(context.top().unwrap().is_exact().unwrap());
+		assert_eq!(context.top().unwrap().to_f64().unwrap(), 2.0);
+
+		// Integers too large to keep exactly are not converted to decimals
+		context.push(Value::Number(100000.into())).unwrap();
+		assert_eq!(context.pow().err(), Some(Error::InexactResult));
+		assert_eq!(context.stack_len(), 3);
+	}
+
+	#[test]
+	fn sin_of_vector() {
+		let mut context = Context::new();
+		context.set_angle_mode(AngleUnit::Radians);
+		let mut vector = Vector::new().unwrap();
+		vector.push(Value::Number(0.into())).unwrap();
+		let half_pi = intel_dfp::Decimal::pi() / 2.into();
+		vector
+			.push(Value::Number(Number::Decimal(half_pi)))
+			.unwrap();
+		context.push(Value::Vector(vector)).unwrap();
+		context.sin().unwrap();
+
+		match context.top().unwrap() {
+			Value::Vector(result) => {
+				assert_eq!(result.len(), 2);
+				assert_eq!(result.get(0).unwrap().to_f64().unwrap(), 0.0);
+				assert!((result.get(1).unwrap().to_f64().unwrap() - 1.0).abs() < 1e-15);
+			}
+			_ => panic!("sin of a vector should be a vector"),
+		}
+	}
+
+	#[test]
+	fn significant_figures() {
+		let decimal = |text: &str| {
+			Value::Number(Number::Decimal(intel_dfp::Decimal::from_str(text).unwrap()))
+		};
+		let display = |context: &Context| {
+			let top = context.top().unwrap();
+			context
+				.format()
+				.format_number(top.real_number().unwrap())
+				.to_string()
+		};
+		let mut context = Context::new();
+		context.toggle_significant_figures();
+
+		// Products keep the fewest significant digits of the operands
+		context.push(decimal("2.0")).unwrap();
+		context.push(decimal("3.00")).unwrap();
+		context.mul().unwrap();
+		assert_eq!(display(&context), "6.0");
+
+		// Sums keep the least precise decimal place of the operands
+		context.push(decimal("1.234")).unwrap();
+		context.push(decimal("2.1")).unwrap();
+		context.add().unwrap();
+		assert_eq!(display(&context), "3.3");
+
+		// Exact operands do not limit the precision
+		context.push(decimal("1.50")).unwrap();
+		context.push(Value::Number(3.into())).unwrap();
+		context.mul().unwrap();
+		assert_eq!(display(&context), "4.50");
+	}
+}
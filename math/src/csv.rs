@@ -0,0 +1,190 @@
+use crate::error::{Error, Result};
+use crate::format::{DecimalPointMode, Format, FormatMode, FormatResult, MAX_PRECISION};
+use crate::matrix::Matrix;
+use crate::number::Number;
+use crate::stack::Stack;
+use crate::value::Value;
+
+/// Exports the contents of the stack as comma separated values. Each stack entry is
+/// placed on its own line, starting from the bottom of the stack. Vectors are written
+/// as a single line and matrices are written as one line per row. When the decimal
+/// point is a comma, fields are separated with semicolons instead.
+pub fn export_stack_csv(stack: &Stack, format: &Format) -> Result<String> {
+	let format = export_format(format);
+	let mut result = String::new();
+	for idx in (0..stack.len()).rev() {
+		match stack.entry(idx)? {
+			Value::Vector(vector) => {
+				let mut fields = Vec::new();
+				for i in 0..vector.len() {
+					fields.push(vector.get(i)?);
+				}
+				push_line(&mut result, &fields, &format);
+			}
+			Value::Matrix(matrix) => push_matrix_lines(&mut result, &matrix, &format)?,
+			value => push_line(&mut result, &[value], &format),
+		}
+	}
+	Ok(result)
+}
+
+/// Exports a matrix as comma separated values, with one line per row. When the decimal
+/// point is a comma, fields are separated with semicolons instead.
+pub fn export_matrix_csv(matrix: &Matrix, format: &Format) -> Result<String> {
+	let format = export_format(format);
+	let mut result = String::new();
+	push_matrix_lines(&mut result, matrix, &format)?;
+	Ok(result)
+}
+
+fn export_format(format: &Format) -> Format {
+	// Exported values must be readable by spreadsheets and by `Matrix::from_csv`, so
+	// they are written as full precision base 10 decimals regardless of the display
+	// settings. Only the decimal point follows the display format.
+	let mut result = Format::new();
+	result.mode = FormatMode::Normal;
+	result.decimal_point = format.decimal_point;
+	result.thousands = false;
+	result.precision = MAX_PRECISION;
+	result.limit_size = false;
+	result
+}
+
+fn delimiter(format: &Format) -> char {
+	match format.decimal_point {
+		DecimalPointMode::Period => ',',
+		DecimalPointMode::Comma => ';',
+	}
+}
+
+fn push_matrix_lines(result: &mut String, matrix: &Matrix, format: &Format) -> Result<()> {
+	for row in 0..matrix.rows() {
+		let mut fields = Vec::new();
+		for col in 0..matrix.cols() {
+			fields.push(matrix.get(row, col)?);
+		}
+		push_line(result, &fields, format);
+	}
+	Ok(())
+}
+
+fn push_line(result: &mut String, fields: &[Value], format: &Format) {
+	let delimiter = delimiter(format);
+	for (i, value) in fields.iter().enumerate() {
+		if i > 0 {
+			result.push(delimiter);
+		}
+		let text = match value.format(format) {
+			FormatResult::Integer(string)
+			| FormatResult::Float(string)
+			| FormatResult::Complex(string)
+			| FormatResult::Object(string) => string,
+		}
+		.replace('ᴇ', "E");
+
+		// Quote fields that would otherwise be split or misinterpreted
+		if text.contains(delimiter) || text.contains('"') {
+			result.push('"');
+			result.push_str(&text.replace('"', "\"\""));
+			result.push('"');
+		} else {
+			result.push_str(&text);
+		}
+	}
+	result.push('\n');
+}
+
+/// Splits a line of comma separated values into fields. When the decimal point is a
+/// comma, fields are expected to be separated with semicolons instead.
+pub(crate) fn split_fields(line: &str, decimal_point: DecimalPointMode) -> Vec<&str> {
+	let delimiter = match decimal_point {
+		DecimalPointMode::Period => ',',
+		DecimalPointMode::Comma => ';',
+	};
+	line.split(delimiter).map(|field| field.trim()).collect()
+}
+
+/// Parses a single field of a comma separated value file into a number.
+pub(crate) fn parse_number(field: &str, decimal_point: DecimalPointMode) -> Result<Number> {
+	let field = field.trim().trim_matches('"').trim();
+	if field.len() == 0 {
+		return Err(Error::CorruptData);
+	}
+
+	// Numbers must be plain integers or decimals, ratios are not accepted
+	let field = match decimal_point {
+		DecimalPointMode::Period => field.to_string(),
+		DecimalPointMode::Comma => field.replace(',', "."),
+	};
+	if !field.chars().all(|ch| {
+		ch.is_ascii_digit() || ch == '.' || ch == '-' || ch == '+' || ch == 'e' || ch == 'E'
+	}) {
+		return Err(Error::CorruptData);
+	}
+	Number::from_str(&field).map_err(|_| Error::CorruptData)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn matrix(values: &[&[Number]]) -> Matrix {
+		let mut result = Matrix::new(values.len(), values[0].len()).unwrap();
+		for (row, items) in values.iter().enumerate() {
+			for (col, value) in items.iter().enumerate() {
+				result.set(row, col, Value::Number(value.clone())).unwrap();
+			}
+		}
+		result
+	}
+
+	#[test]
+	fn export_matrix() {
+		let matrix = matrix(&[&[1.into(), 2.into()], &[3.into(), 4.into()]]);
+		assert_eq!(
+			export_matrix_csv(&matrix, &Format::new()).unwrap(),
+			"1,2\n3,4\n"
+		);
+	}
+
+	#[test]
+	fn export_with_comma_decimal_point() {
+		let matrix = matrix(&[
+			&[Number::from_str("1.5").unwrap(), 2.into()],
+			&[3.into(), Number::from_str("-0.25").unwrap()],
+		]);
+		let mut format = Format::new();
+		format.decimal_point = DecimalPointMode::Comma;
+		assert_eq!(
+			export_matrix_csv(&matrix, &format).unwrap(),
+			"1,5;2\n3;-0,25\n"
+		);
+	}
+
+	#[test]
+	fn export_ignores_display_format() {
+		let matrix = matrix(&[&[
+			Number::from_str("1e40").unwrap(),
+			Number::from_str("0.123456789012345").unwrap(),
+			Number::from_str("0.00001").unwrap(),
+			Number::from_str("3/4").unwrap(),
+		]]);
+		let mut format = Format::new();
+		format.mode = FormatMode::Scientific;
+		format.precision = 4;
+		format.integer_radix = 16;
+		format.show_plus_sign = true;
+		let text = export_matrix_csv(&matrix, &format).unwrap();
+		assert_eq!(text, "1E40,0.123456789012345,0.00001,0.75\n");
+
+		let restored = Matrix::from_csv(&text, DecimalPointMode::Period).unwrap();
+		for col in 0..matrix.cols() {
+			match (matrix.get(0, col).unwrap(), restored.get(0, col).unwrap()) {
+				(Value::Number(expected), Value::Number(value)) => {
+					assert!(value.to_decimal() == expected.to_decimal())
+				}
+				_ => panic!("expected numbers"),
+			}
+		}
+	}
+}
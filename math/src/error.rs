@@ -1,4 +1,5 @@
 use core::array::TryFromSliceError;
+use core::num::TryFromIntError;
 use num_bigint::TryFromBigIntError;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -21,9 +22,11 @@ pub enum Error {
 	CorruptData,
 	StackOverflow,
 	UndoBufferEmpty,
+	RedoBufferEmpty,
 	VectorTooLarge,
 	MatrixTooLarge,
 	DimensionMismatch,
+	InexactResult,
 }
 
 impl Error {
@@ -47,9 +50,11 @@ impl Error {
 			Error::CorruptData => "Corrupt data",
 			Error::StackOverflow => "Stack overflow",
 			Error::UndoBufferEmpty => "Undo buffer empty",
+			Error::RedoBufferEmpty => "Nothing to redo",
 			Error::VectorTooLarge => "Vector too large",
 			Error::MatrixTooLarge => "Matrix too large",
 			Error::DimensionMismatch => "Dimension mismatch",
+			Error::InexactResult => "Inexact result",
 		}
 	}
 }
@@ -60,6 +65,12 @@ impl<T> From<TryFromBigIntError<T>> for Error {
 	}
 }
 
+impl From<TryFromIntError> for Error {
+	fn from(_: TryFromIntError) -> Self {
+		Error::ValueOutOfRange
+	}
+}
+
 impl From<TryFromSliceError> for Error {
 	fn from(_: TryFromSliceError) -> Self {
 		Error::CorruptData
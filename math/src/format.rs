@@ -1,6 +1,10 @@
-use crate::number::Number;
+use crate::error::{Error, Result};
+use crate::number::{Number, ToNumber};
+use crate::storage::{DeserializeInput, SerializeOutput};
+use crate::unit::AngleUnit;
 use intel_dfp::Decimal;
-use num_bigint::{BigInt, BigUint, Sign, ToBigUint};
+use num_bigint::{BigInt, BigUint, Sign, ToBigInt, ToBigUint};
+use num_integer::Integer;
 
 #[cfg(feature = "std")]
 use std::convert::TryInto;
@@ -15,6 +19,49 @@ use core::convert::TryInto;
 // Number of integer bits to attempt to render in short form (i.e. stack display)
 pub const MAX_SHORT_DISPLAY_BITS: u64 = 128;
 
+// Maximum number of significant digits that can be displayed. This is the number of
+// digits available in the 128-bit float.
+pub const MAX_PRECISION: usize = 34;
+
+// Fraction digit counts that trailing zero padding cycles through.
+pub const TRAILING_ZERO_DIGITS: [usize; 3] = [2, 4, 6];
+
+// Range of radix values supported for integer display and entry.
+pub const MIN_INTEGER_RADIX: u8 = 2;
+pub const MAX_INTEGER_RADIX: u8 = 36;
+
+// Word sizes in bits that can be selected for sized integers.
+pub const SIZED_INTEGER_BITS: [usize; 5] = [8, 16, 32, 64, 128];
+
+// Maximum number of digits in a group when digit grouping is enabled.
+pub const MAX_GROUPING: u8 = 9;
+
+// SI prefixes for engineering display, starting at 10^-24 and going in steps of 10^3
+const SI_PREFIXES: [&'static str; 17] = [
+	"y", "z", "a", "f", "p", "n", "μ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+];
+const SI_PREFIX_MIN_EXPONENT: isize = -24;
+const SI_PREFIX_MAX_EXPONENT: isize = 24;
+
+// Largest angle, in twelfths of π, that is shown as a fraction of π. This is two
+// full turns in either direction.
+const MAX_PI_FRACTION_TWELFTHS: i32 = 48;
+
+// Languages that use a comma as the decimal point, and territories where those
+// languages use a period instead.
+const COMMA_DECIMAL_LANGUAGES: [&'static str; 43] = [
+	"af", "az", "be", "bg", "bs", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl",
+	"hr", "hu", "hy", "id", "is", "it", "ka", "kk", "lt", "lv", "mk", "nb", "nl", "nn", "no", "pl",
+	"pt", "ro", "ru", "sk", "sl", "sq", "sr", "sv", "tr", "uk", "vi",
+];
+const PERIOD_DECIMAL_TERRITORIES: [&'static str; 11] = [
+	"CH", "DO", "GT", "HN", "LI", "MX", "NI", "PA", "PR", "SV", "US",
+];
+
+const INTEGER_MODE_SERIALIZE_TYPE_FLOAT: u8 = 0;
+const INTEGER_MODE_SERIALIZE_TYPE_BIG_INTEGER: u8 = 1;
+const INTEGER_MODE_SERIALIZE_TYPE_SIZED_INTEGER: u8 = 2;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum FormatMode {
 	Normal,
@@ -36,6 +83,12 @@ pub enum IntegerMode {
 	SizedInteger(usize, bool),
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IntegerOverflowMode {
+	Wrap,
+	Saturate,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum AlternateFormatMode {
 	Smart,
@@ -43,7 +96,128 @@ pub enum AlternateFormatMode {
 	Left,
 }
 
+impl FormatMode {
+	pub fn to_u8(&self) -> u8 {
+		match self {
+			FormatMode::Normal => 0,
+			FormatMode::Rational => 1,
+			FormatMode::Scientific => 2,
+			FormatMode::Engineering => 3,
+		}
+	}
+
+	pub fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(FormatMode::Normal),
+			1 => Some(FormatMode::Rational),
+			2 => Some(FormatMode::Scientific),
+			3 => Some(FormatMode::Engineering),
+			_ => None,
+		}
+	}
+}
+
+impl DecimalPointMode {
+	fn to_u8(&self) -> u8 {
+		match self {
+			DecimalPointMode::Period => 0,
+			DecimalPointMode::Comma => 1,
+		}
+	}
+
+	fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(DecimalPointMode::Period),
+			1 => Some(DecimalPointMode::Comma),
+			_ => None,
+		}
+	}
+
+	/// Gets the decimal point used by a POSIX locale name, such as "de_DE.UTF-8".
+	pub fn from_locale(locale: &str) -> Self {
+		let name = locale
+			.split(|ch| ch == '.' || ch == '@')
+			.next()
+			.unwrap_or("");
+		let mut parts = name.split('_');
+		let language = parts.next().unwrap_or("");
+		let territory = parts.next().unwrap_or("");
+		if COMMA_DECIMAL_LANGUAGES.contains(&language)
+			&& !PERIOD_DECIMAL_TERRITORIES.contains(&territory)
+		{
+			DecimalPointMode::Comma
+		} else {
+			DecimalPointMode::Period
+		}
+	}
+}
+
+impl IntegerMode {
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		match self {
+			IntegerMode::Float => output.write_u8(INTEGER_MODE_SERIALIZE_TYPE_FLOAT)?,
+			IntegerMode::BigInteger => output.write_u8(INTEGER_MODE_SERIALIZE_TYPE_BIG_INTEGER)?,
+			IntegerMode::SizedInteger(size, signed) => {
+				output.write_u8(INTEGER_MODE_SERIALIZE_TYPE_SIZED_INTEGER)?;
+				output.write_u32(*size as u32)?;
+				output.write_u8(*signed as u8)?;
+			}
+		}
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		match input.read_u8()? {
+			INTEGER_MODE_SERIALIZE_TYPE_FLOAT => Ok(IntegerMode::Float),
+			INTEGER_MODE_SERIALIZE_TYPE_BIG_INTEGER => Ok(IntegerMode::BigInteger),
+			INTEGER_MODE_SERIALIZE_TYPE_SIZED_INTEGER => {
+				let size = input.read_u32()? as usize;
+				let signed = input.read_u8()? != 0;
+				if !SIZED_INTEGER_BITS.contains(&size) {
+					return Err(Error::CorruptData);
+				}
+				Ok(IntegerMode::SizedInteger(size, signed))
+			}
+			_ => Err(Error::CorruptData),
+		}
+	}
+}
+
+impl IntegerOverflowMode {
+	fn to_u8(&self) -> u8 {
+		match self {
+			IntegerOverflowMode::Wrap => 0,
+			IntegerOverflowMode::Saturate => 1,
+		}
+	}
+
+	fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(IntegerOverflowMode::Wrap),
+			1 => Some(IntegerOverflowMode::Saturate),
+			_ => None,
+		}
+	}
+}
+
 impl AlternateFormatMode {
+	fn to_u8(&self) -> u8 {
+		match self {
+			AlternateFormatMode::Smart => 0,
+			AlternateFormatMode::Bottom => 1,
+			AlternateFormatMode::Left => 2,
+		}
+	}
+
+	fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(AlternateFormatMode::Smart),
+			1 => Some(AlternateFormatMode::Bottom),
+			2 => Some(AlternateFormatMode::Left),
+			_ => None,
+		}
+	}
+
 	pub fn left_enabled(&self) -> bool {
 		self != &AlternateFormatMode::Bottom
 	}
@@ -57,10 +231,23 @@ impl AlternateFormatMode {
 pub struct Format {
 	pub mode: FormatMode,
 	pub integer_mode: IntegerMode,
+	pub integer_overflow: IntegerOverflowMode,
 	pub decimal_point: DecimalPointMode,
 	pub thousands: bool,
+	pub grouping: u8,
 	pub precision: usize,
-	pub trailing_zeros: bool,
+	// Largest number of integer digits shown before switching to scientific notation,
+	// or `None` to switch when the integer digits exceed the precision.
+	pub sci_high_threshold: Option<usize>,
+	// Largest number of leading zeros after the decimal point shown before switching
+	// to scientific notation. This is also limited to half of the precision.
+	pub sci_low_threshold: usize,
+	// Number of fraction digits that decimals are padded to with trailing zeros, or
+	// `None` to not pad. This is set separately for normal display and for scientific
+	// and engineering notation.
+	pub trailing_zeros: Option<usize>,
+	pub sci_trailing_zeros: Option<usize>,
+	pub si_prefixes: bool,
 	pub integer_radix: u8,
 	pub show_alt_hex: bool,
 	pub show_alt_float: bool,
@@ -68,6 +255,21 @@ pub struct Format {
 	pub limit_size: bool,
 	pub time_24_hour: bool,
 	pub stack_xyz: bool,
+	pub complex_polar: bool,
+	pub show_continued_fraction: bool,
+	pub show_duration: bool,
+	pub exact_only: bool,
+	pub show_plus_sign: bool,
+	// Show angles that are simple fractions of π symbolically when in radians mode
+	pub symbolic_angles: bool,
+	// Round the results of arithmetic to the precision of the operands, and show the
+	// trailing zeros of decimals that are significant
+	pub significant_figures: bool,
+	// Show units reduced to base SI units, without changing the stored units
+	pub show_base_units: bool,
+	// Angle unit for polar display of complex numbers, this follows the angle mode
+	// of the context and is not saved with the format.
+	pub angle_mode: AngleUnit,
 }
 
 pub enum FormatResult {
@@ -82,10 +284,16 @@ impl Format {
 		Format {
 			mode: FormatMode::Rational,
 			integer_mode: IntegerMode::Float,
+			integer_overflow: IntegerOverflowMode::Wrap,
 			decimal_point: DecimalPointMode::Period,
 			thousands: true,
+			grouping: 3,
 			precision: 12,
-			trailing_zeros: false,
+			sci_high_threshold: None,
+			sci_low_threshold: 4,
+			trailing_zeros: None,
+			sci_trailing_zeros: None,
+			si_prefixes: false,
 			integer_radix: 10,
 			show_alt_hex: true,
 			show_alt_float: true,
@@ -93,6 +301,15 @@ impl Format {
 			limit_size: true,
 			time_24_hour: false,
 			stack_xyz: false,
+			complex_polar: false,
+			show_continued_fraction: false,
+			show_duration: false,
+			exact_only: false,
+			show_plus_sign: false,
+			symbolic_angles: false,
+			significant_figures: false,
+			show_base_units: false,
+			angle_mode: AngleUnit::Degrees,
 		}
 	}
 
@@ -100,10 +317,16 @@ impl Format {
 		Format {
 			mode: FormatMode::Normal,
 			integer_mode: IntegerMode::BigInteger,
+			integer_overflow: self.integer_overflow,
 			decimal_point: self.decimal_point,
 			thousands: false,
+			grouping: self.grouping,
 			precision: 4,
-			trailing_zeros: true,
+			sci_high_threshold: None,
+			sci_low_threshold: 4,
+			trailing_zeros: None,
+			sci_trailing_zeros: None,
+			si_prefixes: false,
 			integer_radix: 10,
 			show_alt_hex: false,
 			show_alt_float: false,
@@ -111,6 +334,15 @@ impl Format {
 			limit_size: true,
 			time_24_hour: false,
 			stack_xyz: false,
+			complex_polar: false,
+			show_continued_fraction: false,
+			show_duration: false,
+			exact_only: self.exact_only,
+			show_plus_sign: false,
+			symbolic_angles: false,
+			significant_figures: false,
+			show_base_units: false,
+			angle_mode: self.angle_mode,
 		}
 	}
 
@@ -121,10 +353,16 @@ impl Format {
 				IntegerMode::Float => IntegerMode::BigInteger,
 				integer_mode => *integer_mode,
 			},
+			integer_overflow: self.integer_overflow,
 			decimal_point: self.decimal_point,
 			thousands: self.thousands,
+			grouping: self.grouping,
 			precision: self.precision,
+			sci_high_threshold: self.sci_high_threshold,
+			sci_low_threshold: self.sci_low_threshold,
 			trailing_zeros: self.trailing_zeros,
+			sci_trailing_zeros: self.sci_trailing_zeros,
+			si_prefixes: self.si_prefixes,
 			integer_radix: 16,
 			show_alt_hex: self.show_alt_hex,
 			show_alt_float: self.show_alt_float,
@@ -132,6 +370,15 @@ impl Format {
 			limit_size: self.limit_size,
 			time_24_hour: self.time_24_hour,
 			stack_xyz: self.stack_xyz,
+			complex_polar: self.complex_polar,
+			show_continued_fraction: self.show_continued_fraction,
+			show_duration: self.show_duration,
+			exact_only: self.exact_only,
+			show_plus_sign: self.show_plus_sign,
+			symbolic_angles: self.symbolic_angles,
+			significant_figures: self.significant_figures,
+			show_base_units: self.show_base_units,
+			angle_mode: self.angle_mode,
 		}
 	}
 
@@ -139,10 +386,16 @@ impl Format {
 		Format {
 			mode: self.mode,
 			integer_mode: self.integer_mode,
+			integer_overflow: self.integer_overflow,
 			decimal_point: self.decimal_point,
 			thousands: self.thousands,
+			grouping: self.grouping,
 			precision: self.precision,
+			sci_high_threshold: self.sci_high_threshold,
+			sci_low_threshold: self.sci_low_threshold,
 			trailing_zeros: self.trailing_zeros,
+			sci_trailing_zeros: self.sci_trailing_zeros,
+			si_prefixes: self.si_prefixes,
 			integer_radix: 10,
 			show_alt_hex: self.show_alt_hex,
 			show_alt_float: self.show_alt_float,
@@ -150,6 +403,15 @@ impl Format {
 			limit_size: self.limit_size,
 			time_24_hour: self.time_24_hour,
 			stack_xyz: self.stack_xyz,
+			complex_polar: self.complex_polar,
+			show_continued_fraction: self.show_continued_fraction,
+			show_duration: self.show_duration,
+			exact_only: self.exact_only,
+			show_plus_sign: self.show_plus_sign,
+			symbolic_angles: self.symbolic_angles,
+			significant_figures: self.significant_figures,
+			show_base_units: self.show_base_units,
+			angle_mode: self.angle_mode,
 		}
 	}
 
@@ -157,10 +419,16 @@ impl Format {
 		Format {
 			mode: self.mode,
 			integer_mode: self.integer_mode,
+			integer_overflow: self.integer_overflow,
 			decimal_point: self.decimal_point,
 			thousands: self.thousands,
+			grouping: self.grouping,
 			precision: core::cmp::min(self.precision, max_precision),
+			sci_high_threshold: self.sci_high_threshold,
+			sci_low_threshold: self.sci_low_threshold,
 			trailing_zeros: self.trailing_zeros,
+			sci_trailing_zeros: self.sci_trailing_zeros,
+			si_prefixes: self.si_prefixes,
 			integer_radix: self.integer_radix,
 			show_alt_hex: self.show_alt_hex,
 			show_alt_float: self.show_alt_float,
@@ -168,10 +436,162 @@ impl Format {
 			limit_size: self.limit_size,
 			time_24_hour: self.time_24_hour,
 			stack_xyz: self.stack_xyz,
+			complex_polar: self.complex_polar,
+			show_continued_fraction: self.show_continued_fraction,
+			show_duration: self.show_duration,
+			exact_only: self.exact_only,
+			show_plus_sign: self.show_plus_sign,
+			symbolic_angles: self.symbolic_angles,
+			significant_figures: self.significant_figures,
+			show_base_units: self.show_base_units,
+			angle_mode: self.angle_mode,
 		}
 	}
 
+	/// Sets the decimal point from the locale of the host, using the standard locale
+	/// environment variables in order of precedence. Digit grouping is unchanged, as
+	/// the supported locales all group by thousands.
+	#[cfg(feature = "std")]
+	pub fn apply_host_locale(&mut self) {
+		for name in &["LC_ALL", "LC_NUMERIC", "LANG"] {
+			if let Ok(locale) = std::env::var(name) {
+				if locale.len() != 0 {
+					self.decimal_point = DecimalPointMode::from_locale(&locale);
+					return;
+				}
+			}
+		}
+	}
+
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		output.write_u8(self.mode.to_u8())?;
+		self.integer_mode.serialize(output)?;
+		output.write_u8(self.integer_overflow.to_u8())?;
+		output.write_u8(self.decimal_point.to_u8())?;
+		output.write_u8(self.thousands as u8)?;
+		output.write_u8(self.grouping)?;
+		output.write_u8(self.precision as u8)?;
+		output.write_u8(self.sci_high_threshold.unwrap_or(0) as u8)?;
+		output.write_u8(self.sci_low_threshold as u8)?;
+		output.write_u8(self.trailing_zeros.unwrap_or(0) as u8)?;
+		output.write_u8(self.sci_trailing_zeros.unwrap_or(0) as u8)?;
+		output.write_u8(self.si_prefixes as u8)?;
+		output.write_u8(self.integer_radix)?;
+		output.write_u8(self.show_alt_hex as u8)?;
+		output.write_u8(self.show_alt_float as u8)?;
+		output.write_u8(self.alt_mode.to_u8())?;
+		output.write_u8(self.limit_size as u8)?;
+		output.write_u8(self.time_24_hour as u8)?;
+		output.write_u8(self.stack_xyz as u8)?;
+		output.write_u8(self.complex_polar as u8)?;
+		output.write_u8(self.show_continued_fraction as u8)?;
+		output.write_u8(self.show_duration as u8)?;
+		output.write_u8(self.exact_only as u8)?;
+		output.write_u8(self.show_plus_sign as u8)?;
+		output.write_u8(self.symbolic_angles as u8)?;
+		output.write_u8(self.significant_figures as u8)?;
+		output.write_u8(self.show_base_units as u8)?;
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		let mode = FormatMode::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?;
+		let integer_mode = IntegerMode::deserialize(input)?;
+		let integer_overflow =
+			IntegerOverflowMode::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?;
+		let decimal_point =
+			DecimalPointMode::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?;
+		let thousands = input.read_u8()? != 0;
+		let grouping = input.read_u8()?;
+		let precision = input.read_u8()? as usize;
+		let sci_high_threshold = match input.read_u8()? {
+			0 => None,
+			digits => Some(digits as usize),
+		};
+		let sci_low_threshold = input.read_u8()? as usize;
+		let trailing_zeros = match input.read_u8()? {
+			0 => None,
+			digits => Some(digits as usize),
+		};
+		let sci_trailing_zeros = match input.read_u8()? {
+			0 => None,
+			digits => Some(digits as usize),
+		};
+		let si_prefixes = input.read_u8()? != 0;
+		let integer_radix = input.read_u8()?;
+		let show_alt_hex = input.read_u8()? != 0;
+		let show_alt_float = input.read_u8()? != 0;
+		let alt_mode = AlternateFormatMode::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?;
+		let limit_size = input.read_u8()? != 0;
+		let time_24_hour = input.read_u8()? != 0;
+		let stack_xyz = input.read_u8()? != 0;
+		let complex_polar = input.read_u8()? != 0;
+		let show_continued_fraction = input.read_u8()? != 0;
+		let show_duration = input.read_u8()? != 0;
+		let exact_only = input.read_u8()? != 0;
+		let show_plus_sign = input.read_u8()? != 0;
+		let symbolic_angles = input.read_u8()? != 0;
+		let significant_figures = input.read_u8()? != 0;
+		let show_base_units = input.read_u8()? != 0;
+		if precision < 1
+			|| precision > MAX_PRECISION
+			|| sci_low_threshold > MAX_PRECISION
+			|| trailing_zeros.unwrap_or(0) > MAX_PRECISION
+			|| sci_trailing_zeros.unwrap_or(0) > MAX_PRECISION
+			|| integer_radix < MIN_INTEGER_RADIX
+			|| integer_radix > MAX_INTEGER_RADIX
+			|| grouping > MAX_GROUPING
+		{
+			return Err(Error::CorruptData);
+		}
+		Ok(Format {
+			mode,
+			integer_mode,
+			integer_overflow,
+			decimal_point,
+			thousands,
+			grouping,
+			precision,
+			sci_high_threshold,
+			sci_low_threshold,
+			trailing_zeros,
+			sci_trailing_zeros,
+			si_prefixes,
+			integer_radix,
+			show_alt_hex,
+			show_alt_float,
+			alt_mode,
+			limit_size,
+			time_24_hour,
+			stack_xyz,
+			complex_polar,
+			show_continued_fraction,
+			show_duration,
+			exact_only,
+			show_plus_sign,
+			symbolic_angles,
+			significant_figures,
+			show_base_units,
+			angle_mode: AngleUnit::Degrees,
+		})
+	}
+
 	pub fn format_number(&self, num: &Number) -> FormatResult {
+		let mut result = self.format_number_without_sign(num);
+
+		// Positive numbers can be shown with an explicit plus sign, but zero never has a sign
+		if self.show_plus_sign && !num.is_zero() && !num.is_negative() && !num.is_nan() {
+			match &mut result {
+				FormatResult::Integer(string)
+				| FormatResult::Float(string)
+				| FormatResult::Complex(string)
+				| FormatResult::Object(string) => string.insert(0, '+'),
+			}
+		}
+		result
+	}
+
+	fn format_number_without_sign(&self, num: &Number) -> FormatResult {
 		match num {
 			Number::Integer(int) => match self.mode {
 				FormatMode::Normal | FormatMode::Rational => {
@@ -192,19 +612,148 @@ impl Format {
 				}
 			},
 			Number::Rational(_, _) => FormatResult::Float(self.format_decimal(&num.to_decimal())),
-			Number::Decimal(value) => FormatResult::Float(self.format_decimal(value)),
+			Number::Decimal(value) => match self.format_pi_fraction(value) {
+				Some(string) => FormatResult::Float(string),
+				None => FormatResult::Float(self.format_decimal(value)),
+			},
+		}
+	}
+
+	/// Formats an angle in radians as a fraction of π, such as "π/6", if symbolic angles
+	/// are enabled and the angle is a multiple of π/12. This covers the results of the
+	/// inverse trigonometric functions for the common angles.
+	fn format_pi_fraction(&self, value: &Decimal) -> Option<String> {
+		if !self.symbolic_angles || self.angle_mode != AngleUnit::Radians || !value.is_finite() {
+			return None;
+		}
+
+		let twelve: Decimal = 12.into();
+		let pi = Decimal::pi();
+		let twelfths = (&(value * &twelve) / &pi).round();
+		if twelfths == Decimal::zero() || twelfths.abs() > Decimal::from(MAX_PI_FRACTION_TWELFTHS) {
+			return None;
+		}
+		let error = (value - &(&(&twelfths * &pi) / &twelve)).abs();
+		if error > Decimal::from(-30).exp10() {
+			return None;
+		}
+
+		let twelfths: i32 = Number::Decimal(twelfths)
+			.to_int()
+			.ok()?
+			.as_ref()
+			.try_into()
+			.ok()?;
+		let divisor = twelfths.gcd(&12);
+		let (numerator, denominator) = (twelfths / divisor, 12 / divisor);
+		let mut result = String::new();
+		if numerator < 0 {
+			result.push('-');
+		}
+		if numerator.abs() != 1 {
+			result += &numerator.abs().to_string();
+		}
+		result.push('π');
+		if denominator != 1 {
+			result.push('/');
+			result += &denominator.to_string();
+		}
+		Some(result)
+	}
+
+	/// Formats the continued fraction expansion of an exact number in the standard
+	/// notation, such as "[3; 7, 16]" for 355/113.
+	pub fn format_continued_fraction(&self, num: &Number) -> Option<String> {
+		let terms = num.continued_fraction_terms().ok()?;
+		let mut result = "[".to_string();
+		for (i, term) in terms.iter().enumerate() {
+			match i {
+				0 => (),
+				1 => result += "; ",
+				_ => result += ", ",
+			}
+			result += &self.decimal_format().format_bigint(term);
+		}
+		result += "]";
+		Some(result)
+	}
+
+	/// Formats a number of seconds as a duration in days, hours, minutes and seconds,
+	/// such as "1d 01:01:01" for 90061 seconds. Fractions of a second are shown to
+	/// nanosecond precision.
+	pub fn format_duration(&self, seconds: &Number) -> Option<String> {
+		let nanos = (seconds * &1_000_000_000.to_number()).round_to_int().ok()?;
+		let (sign_str, nanos) = if nanos.sign() == Sign::Minus {
+			("-", -nanos)
+		} else {
+			("", nanos)
+		};
+		let (secs, nanos) = nanos.div_rem(&1_000_000_000.to_bigint().unwrap());
+		let (mins, secs) = secs.div_rem(&60.to_bigint().unwrap());
+		let (hours, mins) = mins.div_rem(&60.to_bigint().unwrap());
+		let (days, hours) = hours.div_rem(&24.to_bigint().unwrap());
+
+		let mut result = sign_str.to_string();
+		if days.sign() != Sign::NoSign {
+			result += &self.decimal_format().format_bigint(&days);
+			result += "d ";
+		}
+		result += &Self::zero_padded(&hours, 2);
+		result.push(':');
+		result += &Self::zero_padded(&mins, 2);
+		result.push(':');
+		result += &Self::zero_padded(&secs, 2);
+		if nanos.sign() != Sign::NoSign {
+			result.push(match self.decimal_point {
+				DecimalPointMode::Period => '.',
+				DecimalPointMode::Comma => ',',
+			});
+			result += Self::zero_padded(&nanos, 9).trim_end_matches('0');
+		}
+		Some(result)
+	}
+
+	fn zero_padded(value: &BigInt, digits: usize) -> String {
+		let mut result = value.to_string();
+		while result.len() < digits {
+			result.insert(0, '0');
+		}
+		result
+	}
+
+	/// Gets the number of fraction digits that decimals shown in the given display mode
+	/// are padded to with trailing zeros, if any.
+	pub fn trailing_zero_digits(&self, mode: FormatMode) -> Option<usize> {
+		match mode {
+			FormatMode::Scientific | FormatMode::Engineering => self.sci_trailing_zeros,
+			_ => self.trailing_zeros,
 		}
 	}
 
+	pub fn decimal_group_boundary(&self, digits: usize) -> bool {
+		// Checks if a separator belongs before the digit at the given position,
+		// counting from the least significant digit of the integer part
+		self.thousands && self.grouping != 0 && digits > 0 && digits % self.grouping as usize == 0
+	}
+
 	pub fn format_bigint(&self, int: &BigInt) -> String {
-		assert!(self.integer_radix > 1 && self.integer_radix <= 36);
+		assert!(self.integer_radix >= MIN_INTEGER_RADIX && self.integer_radix <= MAX_INTEGER_RADIX);
 
 		// String will be constructed in reverse to simplify implementation
 		let mut result = Vec::new();
 
 		// Format the magnitude of the number ignoring sign, the sign will be
-		// added later.
-		let mut val = int.magnitude().clone();
+		// added later. Negative sized integers in a non-decimal radix are shown
+		// using their two's complement representation instead.
+		let mut negative = int.sign() == Sign::Minus;
+		let mut val = match self.integer_mode {
+			IntegerMode::SizedInteger(size, _) if negative && self.integer_radix != 10 => {
+				negative = false;
+				let modulus = BigInt::from(1) << size;
+				(int % &modulus + &modulus).magnitude().clone()
+			}
+			_ => int.magnitude().clone(),
+		};
 
 		// Get big integers for the needed constants
 		let radix: BigUint = self.integer_radix.into();
@@ -213,7 +762,7 @@ impl Format {
 		let mut non_decimal = false;
 		while val != 0.to_biguint().unwrap() {
 			// Check for thousands separator
-			if digits % 3 == 0 && digits > 0 && self.integer_radix == 10 && self.thousands {
+			if self.integer_radix == 10 && self.decimal_group_boundary(digits) {
 				match self.decimal_point {
 					DecimalPointMode::Period => result.push(','),
 					DecimalPointMode::Comma => result.push('.'),
@@ -252,7 +801,7 @@ impl Format {
 		}
 
 		// Add in sign
-		if int.sign() == Sign::Minus {
+		if negative {
 			result.push('-');
 		}
 
@@ -326,14 +875,17 @@ impl Format {
 
 		// Count the number of trailing zeros in the fraction part of the number. This
 		// will be used to avoid displaying unnecessary parts of the fraction component
-		// (unless trailing zeros are enabled).
+		// (unless trailing zeros are enabled). When tracking significant figures, the
+		// trailing zeros stored in the decimal are significant and are kept.
 		let mut trailing_zeros = 0;
-		let digit_bytes = fraction_digits.as_bytes();
-		for i in 0..fraction_digits.len() {
-			if digit_bytes[(fraction_digits.len() - 1) - i] != '0' as u32 as u8 {
-				break;
+		if !self.significant_figures {
+			let digit_bytes = fraction_digits.as_bytes();
+			for i in 0..fraction_digits.len() {
+				if digit_bytes[(fraction_digits.len() - 1) - i] != '0' as u32 as u8 {
+					break;
+				}
+				trailing_zeros += 1;
 			}
-			trailing_zeros += 1;
 		}
 
 		// Get the nonzero fraction digits from the string
@@ -347,7 +899,7 @@ impl Format {
 			let mut digits = 0;
 			let digit_bytes = digit_str.as_bytes();
 			for i in 0..integer_part_digits {
-				if digits > 0 && digits % 3 == 0 && self.thousands {
+				if self.decimal_group_boundary(digits) {
 					match self.decimal_point {
 						DecimalPointMode::Period => integer_digits.push(',' as u32 as u8),
 						DecimalPointMode::Comma => integer_digits.push('.' as u32 as u8),
@@ -371,7 +923,7 @@ impl Format {
 		};
 
 		// Construct fraction part of string
-		let fraction_str = if integer_part_digits < 0 && fraction_digits.len() > 0 {
+		let mut fraction_str = if integer_part_digits < 0 && fraction_digits.len() > 0 {
 			// There are leading zeros in the fraction, prepend them
 			let mut digits = Vec::new();
 			digits.resize((-integer_part_digits) as usize, '0' as u32 as u8);
@@ -386,10 +938,31 @@ impl Format {
 			display_exponent = 0;
 		}
 
+		// Pad the fraction with zeros up to the digit count for this mode, without going
+		// past the precision. The precision counts the integer digits, or the fraction
+		// digits after a zero.
+		if let Some(digits) = self.trailing_zero_digits(mode) {
+			let max_digits = if integer_part_digits > 0 {
+				self.precision.saturating_sub(integer_part_digits as usize)
+			} else {
+				self.precision
+			};
+			while fraction_str.len() < digits.min(max_digits) {
+				fraction_str.push('0');
+			}
+		}
+
 		// Construct final string
 		let sign_str = if sign { "-" } else { "" };
 
-		let exponent_str = if display_exponent != 0 {
+		let exponent_str = if mode == FormatMode::Engineering
+			&& self.si_prefixes
+			&& display_exponent >= SI_PREFIX_MIN_EXPONENT
+			&& display_exponent <= SI_PREFIX_MAX_EXPONENT
+		{
+			// Exponent is within the range of SI prefixes, use the prefix symbol instead
+			SI_PREFIXES[((display_exponent - SI_PREFIX_MIN_EXPONENT) / 3) as usize].to_string()
+		} else if display_exponent != 0 {
 			"ᴇ".to_string()
 				+ &self
 					.exponent_format()
@@ -400,7 +973,7 @@ impl Format {
 			"".to_string()
 		};
 
-		if fraction_digits.len() > 0 {
+		if fraction_str.len() > 0 {
 			let decimal = match self.decimal_point {
 				DecimalPointMode::Period => ".",
 				DecimalPointMode::Comma => ",",
@@ -437,26 +1010,33 @@ impl Format {
 		// Check to see if the number is too large or too small to display as a normal
 		// decimal number (or if the mode is not decimal), and determine the display
 		// mode according to this and the formatter settings.
-		let mut mode =
-			if self.mode == FormatMode::Scientific || self.mode == FormatMode::Engineering {
-				self.mode
-			} else if integer_part_digits > self.precision as isize
-				|| integer_part_digits < -4
-				|| integer_part_digits < -(self.precision as isize / 2)
-			{
-				FormatMode::Scientific
-			} else {
-				FormatMode::Normal
-			};
+		let mut mode = if self.mode == FormatMode::Scientific
+			|| self.mode == FormatMode::Engineering
+		{
+			self.mode
+		} else if integer_part_digits > self.sci_high_threshold.unwrap_or(self.precision) as isize
+			|| integer_part_digits < -(self.sci_low_threshold as isize)
+			|| integer_part_digits < -(self.precision as isize / 2)
+		{
+			FormatMode::Scientific
+		} else {
+			FormatMode::Normal
+		};
 
 		// Check for rounding
 		if digit_str.len() > self.precision {
 			// More digits than desired precision, round at desired precision.
 			let mut round_exponent =
 				(exponent + digit_str.len() as isize) - self.precision as isize;
-			if round_exponent > 0 && mode == FormatMode::Normal {
+			if round_exponent > 0
+				&& mode == FormatMode::Normal
+				&& digit_str[self.precision..]
+					.bytes()
+					.any(|digit| digit != b'0')
+			{
 				// If rounding was in the middle of the integer portion, always display using
 				// scientific notation, as we must not display digits after the rounding point.
+				// Trailing zeros are exact, so they can still be displayed normally.
 				mode = FormatMode::Scientific;
 			}
 
@@ -504,3 +1084,106 @@ impl FormatResult {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::SerializeBytes;
+
+	#[test]
+	fn decimal_point_from_locale() {
+		assert_eq!(
+			DecimalPointMode::from_locale("de_DE.UTF-8"),
+			DecimalPointMode::Comma
+		);
+		assert_eq!(
+			DecimalPointMode::from_locale("en_US.UTF-8"),
+			DecimalPointMode::Period
+		);
+		assert_eq!(DecimalPointMode::from_locale("C"), DecimalPointMode::Period);
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn apply_comma_locale_from_environment() {
+		let previous = std::env::var_os("LC_ALL");
+		std::env::set_var("LC_ALL", "fr_FR.UTF-8");
+		let mut format = Format::new();
+		format.apply_host_locale();
+		match previous {
+			Some(value) => std::env::set_var("LC_ALL", value),
+			None => std::env::remove_var("LC_ALL"),
+		}
+		assert_eq!(format.decimal_point, DecimalPointMode::Comma);
+	}
+
+	#[test]
+	fn trailing_zeros_per_mode() {
+		let value = Number::from_str("1.5").unwrap();
+		let mut format = Format::new();
+		format.mode = FormatMode::Normal;
+		format.sci_trailing_zeros = Some(2);
+		assert_eq!(format.format_number(&value).to_string(), "1.5");
+
+		format.mode = FormatMode::Scientific;
+		assert_eq!(format.format_number(&value).to_string(), "1.50ᴇ0");
+
+		format.mode = FormatMode::Normal;
+		format.trailing_zeros = Some(2);
+		assert_eq!(format.format_number(&value).to_string(), "1.50");
+		assert_eq!(
+			format
+				.format_number(&Number::from_str("1.2345").unwrap())
+				.to_string(),
+			"1.2345"
+		);
+	}
+
+	#[test]
+	fn integer_mode_rejects_unknown_word_size() {
+		let mut output = SerializeBytes::new();
+		IntegerMode::SizedInteger(32, false)
+			.serialize(&mut output)
+			.unwrap();
+		IntegerMode::SizedInteger(12, true)
+			.serialize(&mut output)
+			.unwrap();
+		let data = output.into_bytes();
+		let mut input = DeserializeInput::new(&data);
+		assert_eq!(
+			IntegerMode::deserialize(&mut input).unwrap(),
+			IntegerMode::SizedInteger(32, false)
+		);
+		assert_eq!(
+			IntegerMode::deserialize(&mut input).err(),
+			Some(Error::CorruptData)
+		);
+	}
+
+	#[test]
+	fn symbolic_angles() {
+		let mut format = Format::new();
+		format.symbolic_angles = true;
+		format.angle_mode = AngleUnit::Radians;
+		let half = &Number::from(1) / &Number::from(2);
+		let format_angle = |angle: Number| format.format_number(&angle).to_string();
+
+		assert_eq!(format_angle(half.asin()), "π/6");
+		assert_eq!(format_angle(Number::from(1).atan()), "π/4");
+		assert_eq!(format_angle(Number::from(0).acos()), "π/2");
+		assert_eq!(format_angle((-&half).acos()), "2π/3");
+		assert_eq!(format_angle(Number::from(-1).asin()), "-π/2");
+		assert_eq!(format_angle(Number::from(-1).acos()), "π");
+
+		// Angles that are not a multiple of π/12 are shown as decimals
+		let angle = format_angle(Number::from(2).atan());
+		assert!(!angle.contains('π'));
+		assert!(angle.starts_with("1.107"));
+
+		format.symbolic_angles = false;
+		assert!(format
+			.format_number(&half.asin())
+			.to_string()
+			.starts_with("0.523"));
+	}
+}
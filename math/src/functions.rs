@@ -1,7 +1,8 @@
 use crate::constant::Constant;
 use crate::context::Context;
-use crate::error::Result;
-use crate::format::{DecimalPointMode, FormatMode, IntegerMode};
+use crate::error::{Error, Result};
+use crate::format::{DecimalPointMode, FormatMode, IntegerMode, IntegerOverflowMode};
+use crate::storage::{DeserializeInput, SerializeOutput};
 use crate::unit::AngleUnit;
 use crate::unit::Unit;
 
@@ -14,12 +15,22 @@ pub enum StackFunction {
 	RationalFormat,
 	ScientificFormat,
 	EngineeringFormat,
+	SIPrefixes,
 	AlternateHex,
 	AlternateFloat,
+	ComplexPolar,
+	AlternateContinuedFraction,
+	DurationFormat,
+	PlusSign,
+	TrailingZeros,
+	SignificantFigures,
+	BaseUnitsDisplay,
+	SymbolicAngles,
 	ThousandsSeparatorOff,
 	ThousandsSeparatorOn,
 	DecimalPointPeriod,
 	DecimalPointComma,
+	SaturateIntegers,
 	Float,
 	BigInteger,
 	Signed8Bit,
@@ -40,6 +51,11 @@ pub enum StackFunction {
 	ShiftRight,
 	RotateLeft,
 	RotateRight,
+	PopCount,
+	CountLeadingZeros,
+	CountTrailingZeros,
+	ByteSwap,
+	BitReverse,
 	Hex,
 	Octal,
 	Decimal,
@@ -48,10 +64,29 @@ pub enum StackFunction {
 	Now,
 	Date,
 	Time,
+	DayOfWeek,
+	DayOfYear,
+	ToJulianDay,
+	FromJulianDay,
+	ToUnixTime,
+	FromUnixTime,
+	ISOWeek,
+	Quarter,
+	AddMonths,
+	AddYears,
+	ToHMS,
+	FromHMS,
+	ContinuedFraction,
+	ToDMS,
+	FromDMS,
 	Degrees,
 	Radians,
 	Gradians,
+	ExactOnly,
 	ClearUnits,
+	ToBaseUnits,
+	ToMetric,
+	ToImperial,
 	AddUnit(Unit),
 	AddUnitSquared(Unit),
 	AddUnitCubed(Unit),
@@ -69,12 +104,20 @@ pub enum StackFunction {
 	Asin,
 	Acos,
 	Atan,
+	Atan2,
+	Hypot,
 	Sinh,
 	Cosh,
 	Tanh,
 	Asinh,
 	Acosh,
 	Atanh,
+	Gamma,
+	LnGamma,
+	Erf,
+	Erfc,
+	NormalCDF,
+	NormalInvCDF,
 	Sum,
 	Mean,
 	DotProduct,
@@ -86,9 +129,445 @@ pub enum StackFunction {
 	ColsToMatrix,
 	IdentityMatrix,
 	Transpose,
+	MatrixPow,
+	Trace,
+	RowSums,
+	ColSums,
+	Range,
+	Linspace,
+	SortVector,
+	ReverseVector,
+	VectorGet,
+	VectorPut,
+	Reshape,
+	Flatten,
+	Simplify,
+	FloatToBits,
+	BitsToFloat,
+	Augment,
+	StackMatrices,
+	Submatrix,
+	DeleteRow,
+	DeleteColumn,
+	LUDecomposition,
+	Eigenvalues,
+	ToPolar,
+	RealPart,
+	ImaginaryPart,
+	RoundComplex,
+	SwapComplexParts,
+	ToRectangular,
+	Sign,
+	Abs,
+	RoundToMultiple,
+	RoundToSignificantDigits,
+	TruncateToMultiple,
+	IntgFloor,
+	IntgCeil,
+	IntgRound,
+	IntegerPart,
+	FractionalPart,
+	Clamp,
+	DivMod,
+	Percent,
+	PercentChange,
+	PercentTotal,
+	Markup,
+	Margin,
+	Discount,
+	CompoundInterest,
+	CompoundAnnualGrowthRate,
+	RollUp,
+	DropN,
+	Pick,
+	StackDepth,
+	ClearX,
 }
 
 impl StackFunction {
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		// Identifiers must remain stable, new functions should be given new identifiers
+		let id: u16 = match self {
+			StackFunction::NormalFormat => 0,
+			StackFunction::RationalFormat => 1,
+			StackFunction::ScientificFormat => 2,
+			StackFunction::EngineeringFormat => 3,
+			StackFunction::SIPrefixes => 4,
+			StackFunction::AlternateHex => 5,
+			StackFunction::AlternateFloat => 6,
+			StackFunction::ThousandsSeparatorOff => 7,
+			StackFunction::ThousandsSeparatorOn => 8,
+			StackFunction::DecimalPointPeriod => 9,
+			StackFunction::DecimalPointComma => 10,
+			StackFunction::Float => 11,
+			StackFunction::BigInteger => 12,
+			StackFunction::Signed8Bit => 13,
+			StackFunction::Signed16Bit => 14,
+			StackFunction::Signed32Bit => 15,
+			StackFunction::Signed64Bit => 16,
+			StackFunction::Signed128Bit => 17,
+			StackFunction::Unsigned8Bit => 18,
+			StackFunction::Unsigned16Bit => 19,
+			StackFunction::Unsigned32Bit => 20,
+			StackFunction::Unsigned64Bit => 21,
+			StackFunction::Unsigned128Bit => 22,
+			StackFunction::And => 23,
+			StackFunction::Or => 24,
+			StackFunction::Xor => 25,
+			StackFunction::Not => 26,
+			StackFunction::ShiftLeft => 27,
+			StackFunction::ShiftRight => 28,
+			StackFunction::RotateLeft => 29,
+			StackFunction::RotateRight => 30,
+			StackFunction::PopCount => 31,
+			StackFunction::CountLeadingZeros => 32,
+			StackFunction::CountTrailingZeros => 33,
+			StackFunction::ByteSwap => 34,
+			StackFunction::BitReverse => 35,
+			StackFunction::Hex => 36,
+			StackFunction::Octal => 37,
+			StackFunction::Decimal => 38,
+			StackFunction::BaseToggle => 39,
+			StackFunction::Constant(_) => 40,
+			StackFunction::Now => 41,
+			StackFunction::Date => 42,
+			StackFunction::Time => 43,
+			StackFunction::DayOfWeek => 44,
+			StackFunction::DayOfYear => 45,
+			StackFunction::AddMonths => 46,
+			StackFunction::AddYears => 47,
+			StackFunction::ToHMS => 48,
+			StackFunction::FromHMS => 49,
+			StackFunction::Degrees => 50,
+			StackFunction::Radians => 51,
+			StackFunction::Gradians => 52,
+			StackFunction::ClearUnits => 53,
+			StackFunction::AddUnit(_) => 54,
+			StackFunction::AddUnitSquared(_) => 55,
+			StackFunction::AddUnitCubed(_) => 56,
+			StackFunction::AddInvUnit(_) => 57,
+			StackFunction::AddInvUnitSquared(_) => 58,
+			StackFunction::AddInvUnitCubed(_) => 59,
+			StackFunction::ConvertToUnit(_) => 60,
+			StackFunction::Log => 61,
+			StackFunction::Exp10 => 62,
+			StackFunction::Ln => 63,
+			StackFunction::Exp => 64,
+			StackFunction::Sin => 65,
+			StackFunction::Cos => 66,
+			StackFunction::Tan => 67,
+			StackFunction::Asin => 68,
+			StackFunction::Acos => 69,
+			StackFunction::Atan => 70,
+			StackFunction::Atan2 => 71,
+			StackFunction::Hypot => 72,
+			StackFunction::Sinh => 73,
+			StackFunction::Cosh => 74,
+			StackFunction::Tanh => 75,
+			StackFunction::Asinh => 76,
+			StackFunction::Acosh => 77,
+			StackFunction::Atanh => 78,
+			StackFunction::Sum => 79,
+			StackFunction::Mean => 80,
+			StackFunction::DotProduct => 81,
+			StackFunction::CrossProduct => 82,
+			StackFunction::Magnitude => 83,
+			StackFunction::Normalize => 84,
+			StackFunction::ToMatrix => 85,
+			StackFunction::RowsToMatrix => 86,
+			StackFunction::ColsToMatrix => 87,
+			StackFunction::IdentityMatrix => 88,
+			StackFunction::Transpose => 89,
+			StackFunction::ToPolar => 90,
+			StackFunction::ToRectangular => 91,
+			StackFunction::Sign => 92,
+			StackFunction::Abs => 93,
+			StackFunction::RoundToMultiple => 94,
+			StackFunction::TruncateToMultiple => 95,
+			StackFunction::DivMod => 96,
+			StackFunction::Percent => 97,
+			StackFunction::PercentChange => 98,
+			StackFunction::PercentTotal => 99,
+			StackFunction::RollUp => 100,
+			StackFunction::DropN => 101,
+			StackFunction::Pick => 102,
+			StackFunction::Gamma => 103,
+			StackFunction::LnGamma => 104,
+			StackFunction::Erf => 105,
+			StackFunction::Erfc => 106,
+			StackFunction::ToBaseUnits => 107,
+			StackFunction::ToDMS => 108,
+			StackFunction::FromDMS => 109,
+			StackFunction::NormalCDF => 110,
+			StackFunction::NormalInvCDF => 111,
+			StackFunction::IntgFloor => 112,
+			StackFunction::IntgCeil => 113,
+			StackFunction::IntgRound => 114,
+			StackFunction::ComplexPolar => 115,
+			StackFunction::ExactOnly => 116,
+			StackFunction::AlternateContinuedFraction => 117,
+			StackFunction::ContinuedFraction => 118,
+			StackFunction::SaturateIntegers => 119,
+			StackFunction::ToJulianDay => 120,
+			StackFunction::FromJulianDay => 121,
+			StackFunction::ToUnixTime => 122,
+			StackFunction::FromUnixTime => 123,
+			StackFunction::ISOWeek => 124,
+			StackFunction::Quarter => 125,
+			StackFunction::DurationFormat => 126,
+			StackFunction::Markup => 127,
+			StackFunction::Margin => 128,
+			StackFunction::Discount => 129,
+			StackFunction::StackDepth => 130,
+			StackFunction::MatrixPow => 131,
+			StackFunction::Trace => 132,
+			StackFunction::RowSums => 133,
+			StackFunction::ColSums => 134,
+			StackFunction::Clamp => 135,
+			StackFunction::IntegerPart => 136,
+			StackFunction::FractionalPart => 137,
+			StackFunction::PlusSign => 138,
+			StackFunction::ToMetric => 139,
+			StackFunction::ToImperial => 140,
+			StackFunction::RoundToSignificantDigits => 141,
+			StackFunction::SymbolicAngles => 142,
+			StackFunction::CompoundInterest => 143,
+			StackFunction::CompoundAnnualGrowthRate => 144,
+			StackFunction::Range => 145,
+			StackFunction::Linspace => 146,
+			StackFunction::SortVector => 147,
+			StackFunction::ReverseVector => 148,
+			StackFunction::VectorGet => 149,
+			StackFunction::VectorPut => 150,
+			StackFunction::Reshape => 151,
+			StackFunction::Flatten => 152,
+			StackFunction::Simplify => 153,
+			StackFunction::FloatToBits => 154,
+			StackFunction::BitsToFloat => 155,
+			StackFunction::Augment => 156,
+			StackFunction::StackMatrices => 157,
+			StackFunction::Submatrix => 158,
+			StackFunction::DeleteRow => 159,
+			StackFunction::DeleteColumn => 160,
+			StackFunction::LUDecomposition => 161,
+			StackFunction::Eigenvalues => 162,
+			StackFunction::SignificantFigures => 163,
+			StackFunction::BaseUnitsDisplay => 164,
+			StackFunction::ClearX => 165,
+			StackFunction::SwapComplexParts => 166,
+			StackFunction::RealPart => 167,
+			StackFunction::ImaginaryPart => 168,
+			StackFunction::RoundComplex => 169,
+			StackFunction::TrailingZeros => 170,
+		};
+		output.write_u16(id)?;
+		match self {
+			StackFunction::Constant(constant) => output.write_u8(constant.to_u8())?,
+			StackFunction::AddUnit(unit)
+			| StackFunction::AddUnitSquared(unit)
+			| StackFunction::AddUnitCubed(unit)
+			| StackFunction::AddInvUnit(unit)
+			| StackFunction::AddInvUnitSquared(unit)
+			| StackFunction::AddInvUnitCubed(unit)
+			| StackFunction::ConvertToUnit(unit) => output.write_u16(unit.to_u16())?,
+			_ => (),
+		}
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		Ok(match input.read_u16()? {
+			0 => StackFunction::NormalFormat,
+			1 => StackFunction::RationalFormat,
+			2 => StackFunction::ScientificFormat,
+			3 => StackFunction::EngineeringFormat,
+			4 => StackFunction::SIPrefixes,
+			5 => StackFunction::AlternateHex,
+			6 => StackFunction::AlternateFloat,
+			7 => StackFunction::ThousandsSeparatorOff,
+			8 => StackFunction::ThousandsSeparatorOn,
+			9 => StackFunction::DecimalPointPeriod,
+			10 => StackFunction::DecimalPointComma,
+			11 => StackFunction::Float,
+			12 => StackFunction::BigInteger,
+			13 => StackFunction::Signed8Bit,
+			14 => StackFunction::Signed16Bit,
+			15 => StackFunction::Signed32Bit,
+			16 => StackFunction::Signed64Bit,
+			17 => StackFunction::Signed128Bit,
+			18 => StackFunction::Unsigned8Bit,
+			19 => StackFunction::Unsigned16Bit,
+			20 => StackFunction::Unsigned32Bit,
+			21 => StackFunction::Unsigned64Bit,
+			22 => StackFunction::Unsigned128Bit,
+			23 => StackFunction::And,
+			24 => StackFunction::Or,
+			25 => StackFunction::Xor,
+			26 => StackFunction::Not,
+			27 => StackFunction::ShiftLeft,
+			28 => StackFunction::ShiftRight,
+			29 => StackFunction::RotateLeft,
+			30 => StackFunction::RotateRight,
+			31 => StackFunction::PopCount,
+			32 => StackFunction::CountLeadingZeros,
+			33 => StackFunction::CountTrailingZeros,
+			34 => StackFunction::ByteSwap,
+			35 => StackFunction::BitReverse,
+			36 => StackFunction::Hex,
+			37 => StackFunction::Octal,
+			38 => StackFunction::Decimal,
+			39 => StackFunction::BaseToggle,
+			40 => StackFunction::Constant(
+				Constant::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?,
+			),
+			41 => StackFunction::Now,
+			42 => StackFunction::Date,
+			43 => StackFunction::Time,
+			44 => StackFunction::DayOfWeek,
+			45 => StackFunction::DayOfYear,
+			46 => StackFunction::AddMonths,
+			47 => StackFunction::AddYears,
+			48 => StackFunction::ToHMS,
+			49 => StackFunction::FromHMS,
+			50 => StackFunction::Degrees,
+			51 => StackFunction::Radians,
+			52 => StackFunction::Gradians,
+			53 => StackFunction::ClearUnits,
+			54 => {
+				StackFunction::AddUnit(Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?)
+			}
+			55 => StackFunction::AddUnitSquared(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			56 => StackFunction::AddUnitCubed(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			57 => StackFunction::AddInvUnit(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			58 => StackFunction::AddInvUnitSquared(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			59 => StackFunction::AddInvUnitCubed(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			60 => StackFunction::ConvertToUnit(
+				Unit::from_u16(input.read_u16()?).ok_or(Error::CorruptData)?,
+			),
+			61 => StackFunction::Log,
+			62 => StackFunction::Exp10,
+			63 => StackFunction::Ln,
+			64 => StackFunction::Exp,
+			65 => StackFunction::Sin,
+			66 => StackFunction::Cos,
+			67 => StackFunction::Tan,
+			68 => StackFunction::Asin,
+			69 => StackFunction::Acos,
+			70 => StackFunction::Atan,
+			71 => StackFunction::Atan2,
+			72 => StackFunction::Hypot,
+			73 => StackFunction::Sinh,
+			74 => StackFunction::Cosh,
+			75 => StackFunction::Tanh,
+			76 => StackFunction::Asinh,
+			77 => StackFunction::Acosh,
+			78 => StackFunction::Atanh,
+			79 => StackFunction::Sum,
+			80 => StackFunction::Mean,
+			81 => StackFunction::DotProduct,
+			82 => StackFunction::CrossProduct,
+			83 => StackFunction::Magnitude,
+			84 => StackFunction::Normalize,
+			85 => StackFunction::ToMatrix,
+			86 => StackFunction::RowsToMatrix,
+			87 => StackFunction::ColsToMatrix,
+			88 => StackFunction::IdentityMatrix,
+			89 => StackFunction::Transpose,
+			90 => StackFunction::ToPolar,
+			91 => StackFunction::ToRectangular,
+			92 => StackFunction::Sign,
+			93 => StackFunction::Abs,
+			94 => StackFunction::RoundToMultiple,
+			95 => StackFunction::TruncateToMultiple,
+			96 => StackFunction::DivMod,
+			97 => StackFunction::Percent,
+			98 => StackFunction::PercentChange,
+			99 => StackFunction::PercentTotal,
+			100 => StackFunction::RollUp,
+			101 => StackFunction::DropN,
+			102 => StackFunction::Pick,
+			103 => StackFunction::Gamma,
+			104 => StackFunction::LnGamma,
+			105 => StackFunction::Erf,
+			106 => StackFunction::Erfc,
+			107 => StackFunction::ToBaseUnits,
+			108 => StackFunction::ToDMS,
+			109 => StackFunction::FromDMS,
+			110 => StackFunction::NormalCDF,
+			111 => StackFunction::NormalInvCDF,
+			112 => StackFunction::IntgFloor,
+			113 => StackFunction::IntgCeil,
+			114 => StackFunction::IntgRound,
+			115 => StackFunction::ComplexPolar,
+			116 => StackFunction::ExactOnly,
+			117 => StackFunction::AlternateContinuedFraction,
+			118 => StackFunction::ContinuedFraction,
+			119 => StackFunction::SaturateIntegers,
+			120 => StackFunction::ToJulianDay,
+			121 => StackFunction::FromJulianDay,
+			122 => StackFunction::ToUnixTime,
+			123 => StackFunction::FromUnixTime,
+			124 => StackFunction::ISOWeek,
+			125 => StackFunction::Quarter,
+			126 => StackFunction::DurationFormat,
+			127 => StackFunction::Markup,
+			128 => StackFunction::Margin,
+			129 => StackFunction::Discount,
+			130 => StackFunction::StackDepth,
+			131 => StackFunction::MatrixPow,
+			132 => StackFunction::Trace,
+			133 => StackFunction::RowSums,
+			134 => StackFunction::ColSums,
+			135 => StackFunction::Clamp,
+			136 => StackFunction::IntegerPart,
+			137 => StackFunction::FractionalPart,
+			138 => StackFunction::PlusSign,
+			139 => StackFunction::ToMetric,
+			140 => StackFunction::ToImperial,
+			141 => StackFunction::RoundToSignificantDigits,
+			142 => StackFunction::SymbolicAngles,
+			143 => StackFunction::CompoundInterest,
+			144 => StackFunction::CompoundAnnualGrowthRate,
+			145 => StackFunction::Range,
+			146 => StackFunction::Linspace,
+			147 => StackFunction::SortVector,
+			148 => StackFunction::ReverseVector,
+			149 => StackFunction::VectorGet,
+			150 => StackFunction::VectorPut,
+			151 => StackFunction::Reshape,
+			152 => StackFunction::Flatten,
+			153 => StackFunction::Simplify,
+			154 => StackFunction::FloatToBits,
+			155 => StackFunction::BitsToFloat,
+			156 => StackFunction::Augment,
+			157 => StackFunction::StackMatrices,
+			158 => StackFunction::Submatrix,
+			159 => StackFunction::DeleteRow,
+			160 => StackFunction::DeleteColumn,
+			161 => StackFunction::LUDecomposition,
+			162 => StackFunction::Eigenvalues,
+			163 => StackFunction::SignificantFigures,
+			164 => StackFunction::BaseUnitsDisplay,
+			165 => StackFunction::ClearX,
+			166 => StackFunction::SwapComplexParts,
+			167 => StackFunction::RealPart,
+			168 => StackFunction::ImaginaryPart,
+			169 => StackFunction::RoundComplex,
+			170 => StackFunction::TrailingZeros,
+			_ => return Err(Error::CorruptData),
+		})
+	}
+
 	pub fn to_string(&self, context: &Context) -> String {
 		match self {
 			StackFunction::NormalFormat => {
@@ -119,6 +598,13 @@ impl StackFunction {
 					"Eng".to_string()
 				}
 			}
+			StackFunction::SIPrefixes => {
+				if context.format().si_prefixes {
+					"▪SI".to_string()
+				} else {
+					"SI".to_string()
+				}
+			}
 			StackFunction::AlternateHex => {
 				if context.format().show_alt_hex {
 					"▪↓Hex".to_string()
@@ -133,6 +619,61 @@ impl StackFunction {
 					"↓Flt".to_string()
 				}
 			}
+			StackFunction::ComplexPolar => {
+				if context.format().complex_polar {
+					"▪r∡θ".to_string()
+				} else {
+					"r∡θ".to_string()
+				}
+			}
+			StackFunction::AlternateContinuedFraction => {
+				if context.format().show_continued_fraction {
+					"▪↓CF".to_string()
+				} else {
+					"↓CF".to_string()
+				}
+			}
+			StackFunction::DurationFormat => {
+				if context.format().show_duration {
+					"▪D:H:M".to_string()
+				} else {
+					"D:H:M".to_string()
+				}
+			}
+			StackFunction::PlusSign => {
+				if context.format().show_plus_sign {
+					"▪+Sign".to_string()
+				} else {
+					"+Sign".to_string()
+				}
+			}
+			StackFunction::TrailingZeros => {
+				match context.format().trailing_zero_digits(context.format().mode) {
+					Some(digits) => "▪Pad".to_string() + &digits.to_string(),
+					None => "Pad".to_string(),
+				}
+			}
+			StackFunction::SignificantFigures => {
+				if context.format().significant_figures {
+					"▪SigFig".to_string()
+				} else {
+					"SigFig".to_string()
+				}
+			}
+			StackFunction::BaseUnitsDisplay => {
+				if context.format().show_base_units {
+					"▪BaseSI".to_string()
+				} else {
+					"BaseSI".to_string()
+				}
+			}
+			StackFunction::SymbolicAngles => {
+				if context.format().symbolic_angles {
+					"▪π/n".to_string()
+				} else {
+					"π/n".to_string()
+				}
+			}
 			StackFunction::ThousandsSeparatorOff => {
 				if context.format().thousands {
 					"1000".to_string()
@@ -161,6 +702,13 @@ impl StackFunction {
 					"0,5".to_string()
 				}
 			}
+			StackFunction::SaturateIntegers => {
+				if context.format().integer_overflow == IntegerOverflowMode::Saturate {
+					"▪Sat".to_string()
+				} else {
+					"Sat".to_string()
+				}
+			}
 			StackFunction::Float => {
 				if context.format().integer_mode == IntegerMode::Float {
 					"▪float".to_string()
@@ -253,6 +801,11 @@ impl StackFunction {
 			StackFunction::ShiftRight => ">>".to_string(),
 			StackFunction::RotateLeft => "rol".to_string(),
 			StackFunction::RotateRight => "ror".to_string(),
+			StackFunction::PopCount => "popcnt".to_string(),
+			StackFunction::CountLeadingZeros => "clz".to_string(),
+			StackFunction::CountTrailingZeros => "ctz".to_string(),
+			StackFunction::ByteSwap => "bswap".to_string(),
+			StackFunction::BitReverse => "brev".to_string(),
 			StackFunction::Hex => {
 				if context.format().integer_radix == 16 {
 					"▪Hex".to_string()
@@ -279,6 +832,21 @@ impl StackFunction {
 			StackFunction::Now => "Now".to_string(),
 			StackFunction::Date => "Date".to_string(),
 			StackFunction::Time => "Time".to_string(),
+			StackFunction::DayOfWeek => "DoW".to_string(),
+			StackFunction::DayOfYear => "DoY".to_string(),
+			StackFunction::ToJulianDay => "▸JD".to_string(),
+			StackFunction::FromJulianDay => "JD▸".to_string(),
+			StackFunction::ToUnixTime => "▸Unix".to_string(),
+			StackFunction::FromUnixTime => "Unix▸".to_string(),
+			StackFunction::ISOWeek => "Week".to_string(),
+			StackFunction::Quarter => "Qtr".to_string(),
+			StackFunction::AddMonths => "+Mon".to_string(),
+			StackFunction::AddYears => "+Yr".to_string(),
+			StackFunction::ToHMS => "▸HMS".to_string(),
+			StackFunction::FromHMS => "HMS▸".to_string(),
+			StackFunction::ContinuedFraction => "▸CF".to_string(),
+			StackFunction::ToDMS => "▸DMS".to_string(),
+			StackFunction::FromDMS => "DMS▸".to_string(),
 			StackFunction::Degrees => {
 				if context.angle_mode() == &AngleUnit::Degrees {
 					"▪Deg".to_string()
@@ -300,7 +868,17 @@ impl StackFunction {
 					"Grad".to_string()
 				}
 			}
+			StackFunction::ExactOnly => {
+				if context.format().exact_only {
+					"▪Exact".to_string()
+				} else {
+					"Exact".to_string()
+				}
+			}
 			StackFunction::ClearUnits => "←Unit".to_string(),
+			StackFunction::ToBaseUnits => "→SI".to_string(),
+			StackFunction::ToMetric => "→Metric".to_string(),
+			StackFunction::ToImperial => "→Imperial".to_string(),
 			StackFunction::AddUnit(unit) => unit.to_str().to_string(),
 			StackFunction::AddUnitSquared(unit) => unit.to_str().to_string() + "²",
 			StackFunction::AddUnitCubed(unit) => unit.to_str().to_string() + "³",
@@ -318,12 +896,20 @@ impl StackFunction {
 			StackFunction::Asin => "asin".to_string(),
 			StackFunction::Acos => "acos".to_string(),
 			StackFunction::Atan => "atan".to_string(),
+			StackFunction::Atan2 => "atan2".to_string(),
+			StackFunction::Hypot => "hypot".to_string(),
 			StackFunction::Sinh => "sinh".to_string(),
 			StackFunction::Cosh => "cosh".to_string(),
 			StackFunction::Tanh => "tanh".to_string(),
 			StackFunction::Asinh => "asinh".to_string(),
 			StackFunction::Acosh => "acosh".to_string(),
 			StackFunction::Atanh => "atanh".to_string(),
+			StackFunction::Gamma => "Γ".to_string(),
+			StackFunction::LnGamma => "lnΓ".to_string(),
+			StackFunction::Erf => "erf".to_string(),
+			StackFunction::Erfc => "erfc".to_string(),
+			StackFunction::NormalCDF => "Φ".to_string(),
+			StackFunction::NormalInvCDF => "invΦ".to_string(),
 			StackFunction::Sum => "sum".to_string(),
 			StackFunction::Mean => "mean".to_string(),
 			StackFunction::DotProduct => "dot".to_string(),
@@ -335,6 +921,113 @@ impl StackFunction {
 			StackFunction::ColsToMatrix => "C▸Mat".to_string(),
 			StackFunction::IdentityMatrix => "ident".to_string(),
 			StackFunction::Transpose => "transp".to_string(),
+			StackFunction::MatrixPow => "mpow".to_string(),
+			StackFunction::Trace => "trace".to_string(),
+			StackFunction::RowSums => "Σrow".to_string(),
+			StackFunction::ColSums => "Σcol".to_string(),
+			StackFunction::Range => "Range".to_string(),
+			StackFunction::Linspace => "LinSp".to_string(),
+			StackFunction::SortVector => "Sort".to_string(),
+			StackFunction::ReverseVector => "Rev".to_string(),
+			StackFunction::VectorGet => "Get".to_string(),
+			StackFunction::VectorPut => "Put".to_string(),
+			StackFunction::Reshape => "Reshp".to_string(),
+			StackFunction::Flatten => "Flat".to_string(),
+			StackFunction::Simplify => "Simp".to_string(),
+			StackFunction::FloatToBits => "→Bits".to_string(),
+			StackFunction::BitsToFloat => "Bits→".to_string(),
+			StackFunction::Augment => "Augm".to_string(),
+			StackFunction::StackMatrices => "Stack".to_string(),
+			StackFunction::Submatrix => "SubM".to_string(),
+			StackFunction::DeleteRow => "DelRow".to_string(),
+			StackFunction::DeleteColumn => "DelCol".to_string(),
+			StackFunction::LUDecomposition => "LU".to_string(),
+			StackFunction::Eigenvalues => "Eigen".to_string(),
+			StackFunction::ToPolar => "▸Pol".to_string(),
+			StackFunction::RealPart => "Re".to_string(),
+			StackFunction::ImaginaryPart => "Im".to_string(),
+			StackFunction::RoundComplex => "RndCx".to_string(),
+			StackFunction::SwapComplexParts => "Re≷Im".to_string(),
+			StackFunction::ToRectangular => "▸Rect".to_string(),
+			StackFunction::Sign => "sign".to_string(),
+			StackFunction::Abs => "abs".to_string(),
+			StackFunction::RoundToMultiple => "Rnd×".to_string(),
+			StackFunction::RoundToSignificantDigits => "RndSig".to_string(),
+			StackFunction::TruncateToMultiple => "Trn×".to_string(),
+			StackFunction::IntgFloor => "Floor".to_string(),
+			StackFunction::IntgCeil => "Ceil".to_string(),
+			StackFunction::IntgRound => "Round".to_string(),
+			StackFunction::IntegerPart => "IP".to_string(),
+			StackFunction::FractionalPart => "FP".to_string(),
+			StackFunction::Clamp => "Clamp".to_string(),
+			StackFunction::DivMod => "divmod".to_string(),
+			StackFunction::Percent => "%".to_string(),
+			StackFunction::PercentChange => "Δ%".to_string(),
+			StackFunction::PercentTotal => "%T".to_string(),
+			StackFunction::Markup => "Mkup".to_string(),
+			StackFunction::Margin => "Mrgn".to_string(),
+			StackFunction::Discount => "Disc".to_string(),
+			StackFunction::CompoundInterest => "CmpInt".to_string(),
+			StackFunction::CompoundAnnualGrowthRate => "CAGR".to_string(),
+			StackFunction::RollUp => "R↑".to_string(),
+			StackFunction::DropN => "DropN".to_string(),
+			StackFunction::Pick => "Pick".to_string(),
+			StackFunction::StackDepth => "Depth".to_string(),
+			StackFunction::ClearX => "CLx".to_string(),
+		}
+	}
+
+	/// Returns true if the function computes a new value, as opposed to changing a mode,
+	/// pushing a constant, or rearranging the stack.
+	pub fn computes_value(&self) -> bool {
+		match self {
+			StackFunction::NormalFormat
+			| StackFunction::RationalFormat
+			| StackFunction::ScientificFormat
+			| StackFunction::EngineeringFormat
+			| StackFunction::SIPrefixes
+			| StackFunction::AlternateHex
+			| StackFunction::AlternateFloat
+			| StackFunction::ComplexPolar
+			| StackFunction::AlternateContinuedFraction
+			| StackFunction::DurationFormat
+			| StackFunction::PlusSign
+			| StackFunction::TrailingZeros
+			| StackFunction::SignificantFigures
+			| StackFunction::BaseUnitsDisplay
+			| StackFunction::SymbolicAngles
+			| StackFunction::ThousandsSeparatorOff
+			| StackFunction::ThousandsSeparatorOn
+			| StackFunction::DecimalPointPeriod
+			| StackFunction::DecimalPointComma
+			| StackFunction::SaturateIntegers
+			| StackFunction::Float
+			| StackFunction::BigInteger
+			| StackFunction::Signed8Bit
+			| StackFunction::Signed16Bit
+			| StackFunction::Signed32Bit
+			| StackFunction::Signed64Bit
+			| StackFunction::Signed128Bit
+			| StackFunction::Unsigned8Bit
+			| StackFunction::Unsigned16Bit
+			| StackFunction::Unsigned32Bit
+			| StackFunction::Unsigned64Bit
+			| StackFunction::Unsigned128Bit
+			| StackFunction::Hex
+			| StackFunction::Octal
+			| StackFunction::Decimal
+			| StackFunction::BaseToggle
+			| StackFunction::Constant(_)
+			| StackFunction::Degrees
+			| StackFunction::Radians
+			| StackFunction::Gradians
+			| StackFunction::ExactOnly
+			| StackFunction::RollUp
+			| StackFunction::DropN
+			| StackFunction::Pick
+			| StackFunction::StackDepth
+			| StackFunction::ClearX => false,
+			_ => true,
 		}
 	}
 
@@ -356,6 +1049,10 @@ impl StackFunction {
 				context.set_format_mode(FormatMode::Engineering);
 				Ok(())
 			}
+			StackFunction::SIPrefixes => {
+				context.toggle_si_prefixes();
+				Ok(())
+			}
 			StackFunction::AlternateHex => {
 				context.toggle_alt_hex();
 				Ok(())
@@ -364,6 +1061,38 @@ impl StackFunction {
 				context.toggle_alt_float();
 				Ok(())
 			}
+			StackFunction::ComplexPolar => {
+				context.toggle_complex_polar();
+				Ok(())
+			}
+			StackFunction::AlternateContinuedFraction => {
+				context.toggle_continued_fraction();
+				Ok(())
+			}
+			StackFunction::DurationFormat => {
+				context.toggle_duration();
+				Ok(())
+			}
+			StackFunction::PlusSign => {
+				context.toggle_plus_sign();
+				Ok(())
+			}
+			StackFunction::TrailingZeros => {
+				context.toggle_trailing_zeros();
+				Ok(())
+			}
+			StackFunction::SignificantFigures => {
+				context.toggle_significant_figures();
+				Ok(())
+			}
+			StackFunction::BaseUnitsDisplay => {
+				context.toggle_base_units_display();
+				Ok(())
+			}
+			StackFunction::SymbolicAngles => {
+				context.toggle_symbolic_angles();
+				Ok(())
+			}
 			StackFunction::ThousandsSeparatorOff => {
 				context.set_thousands_separator(false);
 				Ok(())
@@ -380,6 +1109,10 @@ impl StackFunction {
 				context.set_decimal_point_mode(DecimalPointMode::Comma);
 				Ok(())
 			}
+			StackFunction::SaturateIntegers => {
+				context.toggle_integer_saturation();
+				Ok(())
+			}
 			StackFunction::Float => context.set_float_mode(),
 			StackFunction::BigInteger => {
 				context.set_integer_mode(IntegerMode::BigInteger);
@@ -433,6 +1166,11 @@ impl StackFunction {
 			StackFunction::ShiftRight => context.shr(),
 			StackFunction::RotateLeft => context.rotate_left(),
 			StackFunction::RotateRight => context.rotate_right(),
+			StackFunction::PopCount => context.pop_count(),
+			StackFunction::CountLeadingZeros => context.count_leading_zeros(),
+			StackFunction::CountTrailingZeros => context.count_trailing_zeros(),
+			StackFunction::ByteSwap => context.byte_swap(),
+			StackFunction::BitReverse => context.bit_reverse(),
 			StackFunction::Hex => {
 				context.set_integer_radix(16);
 				Ok(())
@@ -453,6 +1191,21 @@ impl StackFunction {
 			StackFunction::Now => context.now(),
 			StackFunction::Date => context.date(),
 			StackFunction::Time => context.time(),
+			StackFunction::DayOfWeek => context.day_of_week(),
+			StackFunction::DayOfYear => context.day_of_year(),
+			StackFunction::ToJulianDay => context.to_julian_day(),
+			StackFunction::FromJulianDay => context.from_julian_day(),
+			StackFunction::ToUnixTime => context.to_unix_time(),
+			StackFunction::FromUnixTime => context.from_unix_time(),
+			StackFunction::ISOWeek => context.iso_week(),
+			StackFunction::Quarter => context.quarter(),
+			StackFunction::AddMonths => context.add_months(),
+			StackFunction::AddYears => context.add_years(),
+			StackFunction::ToHMS => context.to_hms(),
+			StackFunction::FromHMS => context.from_hms(),
+			StackFunction::ContinuedFraction => context.continued_fraction(),
+			StackFunction::ToDMS => context.to_dms(),
+			StackFunction::FromDMS => context.from_dms(),
 			StackFunction::Degrees => {
 				context.set_angle_mode(AngleUnit::Degrees);
 				Ok(())
@@ -465,7 +1218,14 @@ impl StackFunction {
 				context.set_angle_mode(AngleUnit::Gradians);
 				Ok(())
 			}
+			StackFunction::ExactOnly => {
+				context.toggle_exact_only();
+				Ok(())
+			}
 			StackFunction::ClearUnits => context.clear_units(),
+			StackFunction::ToBaseUnits => context.to_base_units(),
+			StackFunction::ToMetric => context.to_metric(),
+			StackFunction::ToImperial => context.to_imperial(),
 			StackFunction::AddUnit(unit) => context.add_unit(*unit),
 			StackFunction::AddUnitSquared(unit) => context.add_unit_squared(*unit),
 			StackFunction::AddUnitCubed(unit) => context.add_unit_cubed(*unit),
@@ -483,12 +1243,20 @@ impl StackFunction {
 			StackFunction::Asin => context.asin(),
 			StackFunction::Acos => context.acos(),
 			StackFunction::Atan => context.atan(),
+			StackFunction::Atan2 => context.atan2(),
+			StackFunction::Hypot => context.hypot(),
 			StackFunction::Sinh => context.sinh(),
 			StackFunction::Cosh => context.cosh(),
 			StackFunction::Tanh => context.tanh(),
 			StackFunction::Asinh => context.asinh(),
 			StackFunction::Acosh => context.acosh(),
 			StackFunction::Atanh => context.atanh(),
+			StackFunction::Gamma => context.gamma(),
+			StackFunction::LnGamma => context.ln_gamma(),
+			StackFunction::Erf => context.erf(),
+			StackFunction::Erfc => context.erfc(),
+			StackFunction::NormalCDF => context.normal_cdf(),
+			StackFunction::NormalInvCDF => context.normal_inv_cdf(),
 			StackFunction::Sum => context.sum(),
 			StackFunction::Mean => context.mean(),
 			StackFunction::DotProduct => context.dot_product(),
@@ -500,6 +1268,62 @@ impl StackFunction {
 			StackFunction::ColsToMatrix => context.cols_to_matrix(),
 			StackFunction::IdentityMatrix => context.identity_matrix(),
 			StackFunction::Transpose => context.transpose(),
+			StackFunction::MatrixPow => context.matrix_pow(),
+			StackFunction::Trace => context.trace(),
+			StackFunction::RowSums => context.row_sums(),
+			StackFunction::ColSums => context.col_sums(),
+			StackFunction::Range => context.range(),
+			StackFunction::Linspace => context.linspace(),
+			StackFunction::SortVector => context.sort_vector(),
+			StackFunction::ReverseVector => context.reverse_vector(),
+			StackFunction::VectorGet => context.vector_get(),
+			StackFunction::VectorPut => context.vector_put(),
+			StackFunction::Reshape => context.reshape(),
+			StackFunction::Flatten => context.flatten(),
+			StackFunction::Simplify => context.simplify(),
+			StackFunction::FloatToBits => context.float_to_bits(),
+			StackFunction::BitsToFloat => context.bits_to_float(),
+			StackFunction::Augment => context.augment(),
+			StackFunction::StackMatrices => context.stack_matrices(),
+			StackFunction::Submatrix => context.submatrix(),
+			StackFunction::DeleteRow => context.delete_row(),
+			StackFunction::DeleteColumn => context.delete_column(),
+			StackFunction::LUDecomposition => context.lu_decomposition(),
+			StackFunction::Eigenvalues => context.eigenvalues(),
+			StackFunction::ToPolar => context.to_polar(),
+			StackFunction::RealPart => context.real_part(),
+			StackFunction::ImaginaryPart => context.imaginary_part(),
+			StackFunction::RoundComplex => context.round_complex(),
+			StackFunction::SwapComplexParts => context.swap_complex_parts(),
+			StackFunction::ToRectangular => context.to_rectangular(),
+			StackFunction::Sign => context.signum(),
+			StackFunction::Abs => context.abs(),
+			StackFunction::RoundToMultiple => context.round_to_multiple(),
+			StackFunction::RoundToSignificantDigits => context.round_to_significant_digits(),
+			StackFunction::TruncateToMultiple => context.truncate_to_multiple(),
+			StackFunction::IntgFloor => context.int_floor(),
+			StackFunction::IntgCeil => context.int_ceil(),
+			StackFunction::IntgRound => context.int_round(),
+			StackFunction::IntegerPart => context.integer_part(),
+			StackFunction::FractionalPart => context.fractional_part(),
+			StackFunction::Clamp => context.clamp(),
+			StackFunction::DivMod => context.div_mod(),
+			StackFunction::Percent => context.percent(),
+			StackFunction::PercentChange => context.percent_change(),
+			StackFunction::PercentTotal => context.percent_total(),
+			StackFunction::Markup => context.markup(),
+			StackFunction::Margin => context.margin(),
+			StackFunction::Discount => context.discount(),
+			StackFunction::CompoundInterest => context.compound_interest(),
+			StackFunction::CompoundAnnualGrowthRate => context.compound_annual_growth_rate(),
+			StackFunction::RollUp => {
+				context.rotate_up();
+				Ok(())
+			}
+			StackFunction::DropN => context.drop_n(),
+			StackFunction::Pick => context.pick(),
+			StackFunction::StackDepth => context.stack_depth(),
+			StackFunction::ClearX => context.clear_x(),
 		}
 	}
 }
@@ -9,12 +9,17 @@ extern crate lazy_static;
 pub mod complex;
 pub mod constant;
 pub mod context;
+#[cfg(feature = "std")]
+pub mod csv;
 pub mod error;
 pub mod format;
 pub mod functions;
 pub mod matrix;
 pub mod number;
+pub mod random;
+pub mod solver;
 pub mod stack;
+pub mod stats;
 pub mod storage;
 pub mod time;
 pub mod unit;
@@ -1,12 +1,29 @@
+use crate::complex::ComplexNumber;
+#[cfg(feature = "std")]
+use crate::csv::{parse_number, split_fields};
 use crate::error::{Error, Result};
+#[cfg(feature = "std")]
+use crate::format::DecimalPointMode;
+use crate::number::Number;
 use crate::storage::{
 	store, DeserializeInput, SerializeOutput, StorageObject, StorageRef, StorageRefArray,
 	StorageRefSerializer,
 };
 use crate::value::{Value, ValueRef};
+use crate::vector::Vector;
+use intel_dfp::Decimal;
+use num_bigint::Sign;
+use num_integer::Integer;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 const MAX_CAPACITY: usize = 1024;
 
+// Rounding errors in the roots of the characteristic cubic smaller than this many digits
+// below the size of the terms they were computed from are treated as exactly zero
+const CUBIC_TOLERANCE_DIGITS: i32 = 30;
+
 #[derive(Clone)]
 pub struct Matrix {
 	rows: usize,
@@ -30,6 +47,37 @@ impl Matrix {
 		})
 	}
 
+	/// Parses a matrix from comma separated values, with one row per line. When the
+	/// decimal point is a comma, fields are expected to be separated with semicolons.
+	#[cfg(feature = "std")]
+	pub fn from_csv(text: &str, decimal_point: DecimalPointMode) -> Result<Self> {
+		let mut rows: Vec<Vec<Number>> = Vec::new();
+		for line in text.lines() {
+			if line.trim().len() == 0 {
+				continue;
+			}
+			let mut row = Vec::new();
+			for field in split_fields(line, decimal_point) {
+				row.push(parse_number(field, decimal_point)?);
+			}
+			if rows.len() > 0 && row.len() != rows[0].len() {
+				return Err(Error::CorruptData);
+			}
+			rows.push(row);
+		}
+		if rows.len() == 0 {
+			return Err(Error::CorruptData);
+		}
+
+		let mut result = Matrix::new(rows.len(), rows[0].len())?;
+		for (row_idx, row) in rows.into_iter().enumerate() {
+			for (col_idx, value) in row.into_iter().enumerate() {
+				result.set(row_idx, col_idx, Value::Number(value))?;
+			}
+		}
+		Ok(result)
+	}
+
 	fn from_rows_cols_and_array(
 		rows: usize,
 		cols: usize,
@@ -76,6 +124,378 @@ impl Matrix {
 		self.array.set((row * self.cols) + col, store(value)?)
 	}
 
+	pub fn identity(size: usize) -> Result<Self> {
+		let mut result = Matrix::new(size, size)?;
+		for i in 0..size {
+			result.set(i, i, Value::Number(1.into()))?;
+		}
+		Ok(result)
+	}
+
+	pub fn multiply(&self, other: &Matrix) -> Result<Matrix> {
+		if self.cols != other.rows {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Matrix::new(self.rows, other.cols)?;
+		for row in 0..self.rows {
+			for col in 0..other.cols {
+				let mut sum = Value::Number(0.into());
+				for i in 0..self.cols {
+					sum = (sum + (self.get(row, i)? * other.get(i, col)?)?)?;
+				}
+				result.set(row, col, sum)?;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Sums the diagonal elements of a square matrix.
+	pub fn trace(&self) -> Result<Value> {
+		if self.rows != self.cols {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut sum = Value::Number(0.into());
+		for i in 0..self.rows {
+			sum = (sum + self.get(i, i)?)?;
+		}
+		Ok(sum)
+	}
+
+	/// Sums each row of the matrix, giving one element per row.
+	pub fn row_sums(&self) -> Result<Vector> {
+		let mut result = Vector::new()?;
+		for row in 0..self.rows {
+			let mut sum = Value::Number(0.into());
+			for col in 0..self.cols {
+				sum = (sum + self.get(row, col)?)?;
+			}
+			result.push(sum)?;
+		}
+		Ok(result)
+	}
+
+	/// Sums each column of the matrix, giving one element per column.
+	pub fn col_sums(&self) -> Result<Vector> {
+		let mut result = Vector::new()?;
+		for col in 0..self.cols {
+			let mut sum = Value::Number(0.into());
+			for row in 0..self.rows {
+				sum = (sum + self.get(row, col)?)?;
+			}
+			result.push(sum)?;
+		}
+		Ok(result)
+	}
+
+	/// Joins the columns of another matrix with the same number of rows onto the right
+	/// side of this matrix.
+	pub fn hconcat(&self, other: &Matrix) -> Result<Matrix> {
+		if self.rows != other.rows {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Matrix::new(self.rows, self.cols + other.cols)?;
+		for row in 0..self.rows {
+			for col in 0..self.cols {
+				result.set(row, col, self.get(row, col)?)?;
+			}
+			for col in 0..other.cols {
+				result.set(row, self.cols + col, other.get(row, col)?)?;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Joins the rows of another matrix with the same number of columns onto the bottom
+	/// of this matrix.
+	pub fn vconcat(&self, other: &Matrix) -> Result<Matrix> {
+		if self.cols != other.cols {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Matrix::new(self.rows + other.rows, self.cols)?;
+		for col in 0..self.cols {
+			for row in 0..self.rows {
+				result.set(row, col, self.get(row, col)?)?;
+			}
+			for row in 0..other.rows {
+				result.set(self.rows + row, col, other.get(row, col)?)?;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Extracts the block of the matrix with the given number of rows and columns,
+	/// starting at the given top left element.
+	pub fn submatrix(&self, row: usize, col: usize, rows: usize, cols: usize) -> Result<Matrix> {
+		if rows == 0 || cols == 0 {
+			return Err(Error::DimensionMismatch);
+		}
+		match (row.checked_add(rows), col.checked_add(cols)) {
+			(Some(end_row), Some(end_col)) if end_row <= self.rows && end_col <= self.cols => (),
+			_ => return Err(Error::IndexOutOfRange),
+		}
+		let mut result = Matrix::new(rows, cols)?;
+		for i in 0..rows {
+			for j in 0..cols {
+				result.set(i, j, self.get(row + i, col + j)?)?;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Removes a row from the matrix. The last remaining row cannot be removed.
+	pub fn delete_row(&self, row: usize) -> Result<Matrix> {
+		if row >= self.rows {
+			return Err(Error::IndexOutOfRange);
+		}
+		if self.rows == 1 {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Matrix::new(self.rows - 1, self.cols)?;
+		for i in 0..result.rows {
+			let source_row = if i < row { i } else { i + 1 };
+			for col in 0..self.cols {
+				result.set(i, col, self.get(source_row, col)?)?;
+			}
+		}
+		Ok(result)
+	}
+
+	/// Removes a column from the matrix. The last remaining column cannot be removed.
+	pub fn delete_col(&self, col: usize) -> Result<Matrix> {
+		if col >= self.cols {
+			return Err(Error::IndexOutOfRange);
+		}
+		if self.cols == 1 {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Matrix::new(self.rows, self.cols - 1)?;
+		for i in 0..result.cols {
+			let source_col = if i < col { i } else { i + 1 };
+			for row in 0..self.rows {
+				result.set(row, i, self.get(row, source_col)?)?;
+			}
+		}
+		Ok(result)
+	}
+
+	fn swap_rows(&mut self, a: usize, b: usize, cols: usize) -> Result<()> {
+		for col in 0..cols {
+			let value = self.get(a, col)?;
+			self.set(a, col, self.get(b, col)?)?;
+			self.set(b, col, value)?;
+		}
+		Ok(())
+	}
+
+	/// Computes the LU decomposition of a square matrix with partial pivoting. Returns
+	/// the lower and upper triangular matrices and the row permutation, such that row
+	/// `i` of L·U is row `permutation[i]` of this matrix. Exact values stay exact.
+	pub fn lu(&self) -> Result<(Matrix, Matrix, Vec<usize>)> {
+		if self.rows != self.cols {
+			return Err(Error::DimensionMismatch);
+		}
+		let size = self.rows;
+		let mut lower = Matrix::identity(size)?;
+		let mut upper = self.clone();
+		let mut permutation: Vec<usize> = (0..size).collect();
+
+		for k in 0..size {
+			// Use the row with the largest magnitude in this column as the pivot
+			let mut pivot_row = k;
+			let mut pivot_magnitude = upper.get(k, k)?.abs()?.real_number()?.clone();
+			for row in k + 1..size {
+				let magnitude = upper.get(row, k)?.abs()?.real_number()?.clone();
+				if magnitude > pivot_magnitude {
+					pivot_row = row;
+					pivot_magnitude = magnitude;
+				}
+			}
+			if pivot_magnitude.is_zero() {
+				// Column is already eliminated, the matrix is singular
+				continue;
+			}
+			if pivot_row != k {
+				upper.swap_rows(k, pivot_row, size)?;
+				lower.swap_rows(k, pivot_row, k)?;
+				permutation.swap(k, pivot_row);
+			}
+
+			// Eliminate the column below the pivot, keeping the factors in L
+			let pivot = upper.get(k, k)?;
+			for row in k + 1..size {
+				let factor = (upper.get(row, k)? / pivot.clone())?;
+				upper.set(row, k, Value::Number(0.into()))?;
+				for col in k + 1..size {
+					let value = (upper.get(row, col)? - (factor.clone() * upper.get(k, col)?)?)?;
+					upper.set(row, col, value)?;
+				}
+				lower.set(row, k, factor)?;
+			}
+		}
+		Ok((lower, upper, permutation))
+	}
+
+	fn real_element(&self, row: usize, col: usize) -> Result<Number> {
+		Ok(self.get(row, col)?.real_number()?.clone())
+	}
+
+	/// Computes the eigenvalues of a real 2×2 or 3×3 matrix from the roots of its
+	/// characteristic polynomial. Complex eigenvalues are returned as complex values.
+	pub fn eigenvalues(&self) -> Result<Vector> {
+		if self.rows != self.cols {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut result = Vector::new()?;
+		match self.rows {
+			2 => {
+				for value in self.eigenvalues_2x2()?.iter() {
+					result.push(Value::check_complex(value.clone())?)?;
+				}
+			}
+			3 => {
+				for value in self.eigenvalues_3x3()?.iter() {
+					result.push(Value::check_complex(value.clone())?)?;
+				}
+			}
+			_ => return Err(Error::DimensionMismatch),
+		}
+		Ok(result)
+	}
+
+	fn eigenvalues_2x2(&self) -> Result<[ComplexNumber; 2]> {
+		// Roots of x² - tr·x + det, kept exact when the discriminant is a perfect square
+		let a = self.real_element(0, 0)?;
+		let b = self.real_element(0, 1)?;
+		let c = self.real_element(1, 0)?;
+		let d = self.real_element(1, 1)?;
+		let half_trace = &(&a + &d) / &2.into();
+		let det = &(&a * &d) - &(&b * &c);
+		let discriminant = &(&half_trace * &half_trace) - &det;
+		if discriminant.is_negative() {
+			let root = (-&discriminant).sqrt();
+			Ok([
+				ComplexNumber::from_parts(half_trace.clone(), root.clone()),
+				ComplexNumber::from_parts(half_trace, -root),
+			])
+		} else {
+			let root = discriminant.sqrt();
+			Ok([
+				ComplexNumber::from_real(&half_trace + &root),
+				ComplexNumber::from_real(&half_trace - &root),
+			])
+		}
+	}
+
+	fn eigenvalues_3x3(&self) -> Result<[ComplexNumber; 3]> {
+		let mut m = Vec::new();
+		for row in 0..3 {
+			let mut values = Vec::new();
+			for col in 0..3 {
+				values.push(self.real_element(row, col)?.to_decimal().into_owned());
+			}
+			m.push(values);
+		}
+
+		// Coefficients of the characteristic polynomial x³ - c2·x² + c1·x - c0
+		let minor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+			&(&m[r0][c0] * &m[r1][c1]) - &(&m[r0][c1] * &m[r1][c0])
+		};
+		let c2 = &(&m[0][0] + &m[1][1]) + &m[2][2];
+		let c1 = &(&minor(0, 1, 0, 1) + &minor(0, 2, 0, 2)) + &minor(1, 2, 1, 2);
+		let c0 = &(&(&m[0][0] * &minor(1, 2, 1, 2)) - &(&m[0][1] * &minor(1, 2, 0, 2)))
+			+ &(&m[0][2] * &minor(1, 2, 0, 1));
+
+		// Substitute x = t + c2/3 to get the depressed cubic t³ + p·t + q
+		let two: Decimal = 2.into();
+		let three: Decimal = 3.into();
+		let shift = &c2 / &three;
+		let p = &c1 - &(&(&c2 * &c2) / &three);
+		let q = &(&(&(&c2 * &c1) / &three) - &c0)
+			- &(&(&two * &(&(&c2 * &c2) * &c2)) / &Decimal::from(27));
+		let q_term = &(&q * &q) / &Decimal::from(4);
+		let p_term = &(&(&p * &p) * &p) / &Decimal::from(27);
+		let discriminant = &q_term + &p_term;
+
+		// Remove rounding errors that would otherwise turn repeated roots into a complex
+		// pair, or leave a tiny real part on a purely imaginary root
+		let tolerance = Decimal::from(-CUBIC_TOLERANCE_DIGITS).exp10();
+		let snap = |value: Decimal, scale: &Decimal| {
+			if value.abs() <= &scale.abs() * &tolerance {
+				Decimal::zero()
+			} else {
+				value
+			}
+		};
+
+		if discriminant > &(&q_term.abs() + &p_term.abs()) * &tolerance {
+			// One real root and a complex conjugate pair (Cardano's formula)
+			let root = discriminant.sqrt();
+			let half_q = &q / &two;
+			let u = (&(-&half_q) + &root).cbrt();
+			let v = (&(-&half_q) - &root).cbrt();
+			let sum = &u + &v;
+			let magnitude = &(&u.abs() + &v.abs()) + &shift.abs();
+			let real = snap(&sum + &shift, &magnitude);
+			let pair_real = snap(&shift - &(&sum / &two), &magnitude);
+			let pair_imag = snap(&(&u - &v) * &(&three.sqrt() / &two), &magnitude);
+			Ok([
+				ComplexNumber::from_real(Number::Decimal(real)),
+				ComplexNumber::from_parts(
+					Number::Decimal(pair_real.clone()),
+					Number::Decimal(pair_imag.clone()),
+				),
+				ComplexNumber::from_parts(Number::Decimal(pair_real), Number::Decimal(-pair_imag)),
+			])
+		} else if p == Decimal::zero() {
+			// Triple root
+			let value = ComplexNumber::from_real(Number::Decimal(shift));
+			Ok([value.clone(), value.clone(), value])
+		} else {
+			// Three real roots (trigonometric method)
+			let scale = &two * &(&(-&p) / &three).sqrt();
+			let mut cos_arg = &(&three * &q) / &(&p * &scale);
+			let one: Decimal = 1.into();
+			if cos_arg > one {
+				cos_arg = one;
+			} else if cos_arg < -&one {
+				cos_arg = -one;
+			}
+			let angle = &cos_arg.acos() / &three;
+			let step = &(&two * &Decimal::pi()) / &three;
+			let magnitude = &scale.abs() + &shift.abs();
+			let root = |k: i32| {
+				let angle = &angle - &(&step * &Decimal::from(k));
+				let value = snap(&(&scale * &angle.cos()) + &shift, &magnitude);
+				ComplexNumber::from_real(Number::Decimal(value))
+			};
+			Ok([root(0), root(1), root(2)])
+		}
+	}
+
+	/// Raises a square matrix to a non-negative integer power using repeated squaring.
+	pub fn pow(&self, n: &Number) -> Result<Matrix> {
+		if self.rows != self.cols {
+			return Err(Error::DimensionMismatch);
+		}
+		let mut n = Value::Number(n.clone()).exact_int()?;
+		if n.sign() == Sign::Minus {
+			return Err(Error::ValueOutOfRange);
+		}
+
+		let mut result = Matrix::identity(self.rows)?;
+		let mut square = self.clone();
+		while n.sign() != Sign::NoSign {
+			if n.is_odd() {
+				result = result.multiply(&square)?;
+			}
+			n >>= 1;
+			if n.sign() != Sign::NoSign {
+				square = square.multiply(&square)?;
+			}
+		}
+		Ok(result)
+	}
+
 	/// Deep copies all values in the matrix onto the non-reclaimable heap. This is used
 	/// when pulling values out of reclaimable memory.
 	pub fn deep_copy_values(&mut self) -> Result<()> {
@@ -114,3 +534,110 @@ impl StorageObject for Matrix {
 		Ok(Matrix::from_rows_cols_and_array(rows, cols, array)?)
 	}
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_csv() {
+		let matrix = Matrix::from_csv("1,2\n3,4\n", DecimalPointMode::Period).unwrap();
+		assert_eq!(matrix.rows(), 2);
+		assert_eq!(matrix.cols(), 2);
+		assert_eq!(matrix.get(1, 0).unwrap().to_f64().unwrap(), 3.0);
+
+		let matrix = Matrix::from_csv("1,5;2\n3;4\n", DecimalPointMode::Comma).unwrap();
+		assert_eq!(matrix.get(0, 0).unwrap().to_f64().unwrap(), 1.5);
+	}
+
+	#[test]
+	fn parse_csv_rejects_ragged_rows() {
+		assert_eq!(
+			Matrix::from_csv("1,2\n3\n", DecimalPointMode::Period).err(),
+			Some(Error::CorruptData)
+		);
+		assert_eq!(
+			Matrix::from_csv("1,2\n3,4,5\n", DecimalPointMode::Period).err(),
+			Some(Error::CorruptData)
+		);
+	}
+
+	#[test]
+	fn matrix_power() {
+		let matrix = Matrix::from_csv("1,2\n3,4\n", DecimalPointMode::Period).unwrap();
+		let squared = matrix.pow(&2.into()).unwrap();
+		let product = matrix.multiply(&matrix).unwrap();
+		let identity = matrix.pow(&0.into()).unwrap();
+		for row in 0..2 {
+			for col in 0..2 {
+				assert_eq!(
+					squared.get(row, col).unwrap().to_f64().unwrap(),
+					product.get(row, col).unwrap().to_f64().unwrap()
+				);
+				let expected = if row == col { 1.0 } else { 0.0 };
+				assert_eq!(identity.get(row, col).unwrap().to_f64().unwrap(), expected);
+			}
+		}
+		assert_eq!(squared.get(1, 1).unwrap().to_f64().unwrap(), 22.0);
+	}
+
+	#[test]
+	fn lu_decomposition_with_pivoting() {
+		let matrix = Matrix::from_csv("1,2,3\n4,5,6\n7,8,10\n", DecimalPointMode::Period).unwrap();
+		let (lower, upper, permutation) = matrix.lu().unwrap();
+		assert_eq!(permutation[0], 2);
+
+		let product = lower.multiply(&upper).unwrap();
+		for row in 0..3 {
+			for col in 0..3 {
+				if col > row {
+					assert!(lower.real_element(row, col).unwrap().is_zero());
+				}
+				if col < row {
+					assert!(upper.real_element(row, col).unwrap().is_zero());
+				}
+				let value = product.get(row, col).unwrap();
+				assert!(value.is_exact().unwrap());
+				assert_eq!(
+					value.to_f64().unwrap(),
+					matrix.get(permutation[row], col).unwrap().to_f64().unwrap()
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn real_eigenvalues() {
+		let matrix = Matrix::from_csv("2,1\n1,2\n", DecimalPointMode::Period).unwrap();
+		let eigenvalues = matrix.eigenvalues().unwrap();
+		assert_eq!(eigenvalues.len(), 2);
+		let mut values = [
+			eigenvalues.get(0).unwrap().to_f64().unwrap(),
+			eigenvalues.get(1).unwrap().to_f64().unwrap(),
+		];
+		values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		assert!((values[0] - 1.0).abs() < 1e-12);
+		assert!((values[1] - 3.0).abs() < 1e-12);
+	}
+
+	#[test]
+	fn complex_eigenvalues() {
+		// Rotation by 90 degrees has the eigenvalues ±i
+		let matrix = Matrix::from_csv("0,-1\n1,0\n", DecimalPointMode::Period).unwrap();
+		let eigenvalues = matrix.eigenvalues().unwrap();
+		assert_eq!(eigenvalues.len(), 2);
+		let mut imaginary_sum = 0.0;
+		for i in 0..2 {
+			match eigenvalues.get(i).unwrap() {
+				Value::Complex(value) => {
+					assert!(value.real_part().to_f64().abs() < 1e-12);
+					let imaginary = value.imaginary_part().to_f64();
+					assert!((imaginary.abs() - 1.0).abs() < 1e-12);
+					imaginary_sum += imaginary;
+				}
+				_ => panic!("expected a complex eigenvalue"),
+			}
+		}
+		assert!(imaginary_sum.abs() < 1e-12);
+	}
+}
@@ -2,6 +2,9 @@ use crate::error::{Error, Result};
 use crate::format::Format;
 use crate::storage::{DeserializeInput, SerializeOutput, StorageObject, StorageRefSerializer};
 use crate::unit::{AngleUnit, UnitConversion};
+use crate::value::Value;
+use crate::vector::Vector;
+use core::cmp::Ordering;
 use intel_dfp::Decimal;
 use num_bigint::{BigInt, BigUint, Sign, ToBigInt, ToBigUint};
 use num_integer::Integer;
@@ -9,7 +12,7 @@ use num_integer::Integer;
 #[cfg(feature = "std")]
 use std::borrow::Cow;
 #[cfg(feature = "std")]
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
 #[cfg(not(feature = "std"))]
 use alloc::borrow::Cow;
@@ -18,11 +21,14 @@ use alloc::string::String;
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 #[cfg(not(feature = "std"))]
-use core::convert::TryInto;
+use core::convert::{TryFrom, TryInto};
 
 // Maximum integer size before it is converted into a floating point number.
 pub const MAX_INTEGER_BITS: u64 = 8192;
 
+// Largest integer for which the gamma function is computed exactly
+const MAX_EXACT_GAMMA: u32 = 1000;
+
 // Maximum integer exponent (10^x). This should match the above value in magnitude.
 pub const MAX_INTEGER_EXPONENT: isize = 2466;
 
@@ -82,6 +88,41 @@ impl Number {
 		}
 	}
 
+	/// Parses a number from a string. Integers and ratios such as "3/4" are parsed
+	/// exactly, and anything else is parsed as a decimal without going through a
+	/// binary floating point value.
+	pub fn from_str(string: &str) -> Result<Number> {
+		let string = string.trim();
+		if let Some(idx) = string.find('/') {
+			let (num, denom) = (&string[..idx], &string[idx + 1..]);
+			let num = BigInt::parse_bytes(num.trim().as_bytes(), 10).ok_or(Error::InvalidEntry)?;
+			let denom =
+				BigUint::parse_bytes(denom.trim().as_bytes(), 10).ok_or(Error::InvalidEntry)?;
+			if denom == 0.to_biguint().unwrap() {
+				return Err(Error::InvalidEntry);
+			}
+			return Ok(Number::Rational(num, denom).simplify());
+		}
+
+		let digits = string
+			.strip_prefix('-')
+			.or(string.strip_prefix('+'))
+			.unwrap_or(string);
+		if digits.len() > 0 && digits.chars().all(|ch| ch.is_ascii_digit()) {
+			let int = BigInt::parse_bytes(string.as_bytes(), 10).ok_or(Error::InvalidEntry)?;
+			return Ok(Self::check_int_bounds(Number::Integer(int)));
+		}
+
+		match Decimal::from_str(string) {
+			Ok(value) => Ok(Number::Decimal(value)),
+			Err(_) => Err(Error::InvalidEntry),
+		}
+	}
+
+	pub fn to_f64(&self) -> f64 {
+		self.to_decimal().to_f64()
+	}
+
 	pub fn to_int<'a>(&'a self) -> Result<Cow<'a, BigInt>> {
 		match self {
 			Number::Integer(int) => Ok(Cow::Borrowed(int)),
@@ -89,6 +130,11 @@ impl Number {
 			Number::Decimal(num) => {
 				let num = num.trunc();
 
+				// Convert directly when the value fits in a machine integer
+				if let Ok(int) = i64::try_from(&num) {
+					return Ok(Cow::Owned(int.into()));
+				}
+
 				let raw_str = num.to_string();
 
 				// Split string on the 'E' to decode parts of number. For non inf/NaN there
@@ -162,6 +208,13 @@ impl Number {
 		}
 	}
 
+	pub fn is_exact(&self) -> bool {
+		match self {
+			Number::Decimal(_) => false,
+			_ => true,
+		}
+	}
+
 	pub fn is_infinite(&self) -> bool {
 		match self {
 			Number::Decimal(value) => value.is_infinite(),
@@ -176,6 +229,33 @@ impl Number {
 		}
 	}
 
+	pub fn signum(&self) -> Number {
+		// Result is always an exact integer, unless the input is not a number
+		if self.is_nan() {
+			return self.clone();
+		}
+		if self.is_zero() {
+			Number::Integer(0.into())
+		} else if self.is_negative() {
+			Number::Integer((-1).into())
+		} else {
+			Number::Integer(1.into())
+		}
+	}
+
+	pub fn abs(&self) -> Number {
+		match self {
+			Number::Decimal(value) => Number::Decimal(value.abs()),
+			_ => {
+				if self.is_negative() {
+					-self
+				} else {
+					self.clone()
+				}
+			}
+		}
+	}
+
 	pub fn sqrt(&self) -> Number {
 		match &self {
 			Number::Integer(value) => {
@@ -222,7 +302,33 @@ impl Number {
 				}
 				Number::Decimal(right) => Number::Decimal(self.to_decimal().pow(right)),
 			},
-			Number::Rational(_, _) => Number::Decimal(self.to_decimal().pow(&power.to_decimal())),
+			Number::Rational(num, denom) => match power {
+				Number::Integer(right) => {
+					// Raise the numerator and denominator separately, swapping them
+					// for negative powers
+					let (num, denom) = if right.sign() == Sign::Minus {
+						let mut swapped_num = denom.to_bigint().unwrap();
+						if num.sign() == Sign::Minus {
+							swapped_num = -swapped_num;
+						}
+						(swapped_num, num.magnitude().clone())
+					} else {
+						(num.clone(), denom.clone())
+					};
+					if let Ok(int_power) = u32::try_from(right.magnitude()) {
+						if num.bits() * int_power as u64 > MAX_NUMERATOR_BITS
+							|| denom.bits() * int_power as u64 > MAX_DENOMINATOR_BITS
+						{
+							Number::Decimal(self.to_decimal().pow(&power.to_decimal()))
+						} else {
+							Number::Rational(num.pow(int_power), denom.pow(int_power)).simplify()
+						}
+					} else {
+						Number::Decimal(self.to_decimal().pow(&power.to_decimal()))
+					}
+				}
+				_ => Number::Decimal(self.to_decimal().pow(&power.to_decimal())),
+			},
 			Number::Decimal(left) => Number::Decimal(left.pow(&power.to_decimal())),
 		}
 	}
@@ -251,6 +357,15 @@ impl Number {
 		Number::Decimal(self.to_decimal().atan())
 	}
 
+	pub fn atan2(&self, x: &Number) -> Number {
+		// Self is the y coordinate, result is in the range -pi to pi
+		Number::Decimal(Decimal::atan2(&self.to_decimal(), &x.to_decimal()))
+	}
+
+	pub fn hypot(&self, other: &Number) -> Number {
+		Number::Decimal(Decimal::hypot(&self.to_decimal(), &other.to_decimal()))
+	}
+
 	pub fn sinh(&self) -> Number {
 		Number::Decimal(self.to_decimal().sinh())
 	}
@@ -275,6 +390,34 @@ impl Number {
 		Number::Decimal(self.to_decimal().atanh())
 	}
 
+	pub fn gamma(&self) -> Number {
+		// Gamma of a small positive integer is a factorial, compute it exactly
+		if let Number::Integer(int) = self {
+			if int > &0.to_bigint().unwrap() && int <= &MAX_EXACT_GAMMA.to_bigint().unwrap() {
+				let mut result = 1.to_bigint().unwrap();
+				let mut i = 2.to_bigint().unwrap();
+				while &i < int {
+					result *= &i;
+					i += 1;
+				}
+				return Self::check_int_bounds(Number::Integer(result));
+			}
+		}
+		Number::Decimal(self.to_decimal().tgamma())
+	}
+
+	pub fn ln_gamma(&self) -> Number {
+		Number::Decimal(self.to_decimal().lgamma())
+	}
+
+	pub fn erf(&self) -> Number {
+		Number::Decimal(self.to_decimal().erf())
+	}
+
+	pub fn erfc(&self) -> Number {
+		Number::Decimal(self.to_decimal().erfc())
+	}
+
 	pub fn angle_to_radians<'a>(&'a self, angle_mode: AngleUnit) -> Cow<'a, Number> {
 		match angle_mode {
 			AngleUnit::Radians => Cow::Borrowed(self),
@@ -289,6 +432,27 @@ impl Number {
 		}
 	}
 
+	/// Converts an angle to radians for use in a trigonometric function. Angles in
+	/// degrees or gradians are first reduced to a single turn, as very large angles would
+	/// otherwise lose all of their significant digits in the conversion. Angles in radians
+	/// are reduced exactly by the decimal library itself.
+	pub fn reduced_angle_to_radians<'a>(&'a self, angle_mode: AngleUnit) -> Cow<'a, Number> {
+		let full_turn: i32 = match angle_mode {
+			AngleUnit::Degrees => 360,
+			AngleUnit::Radians => return Cow::Borrowed(self),
+			AngleUnit::Gradians => 400,
+		};
+		let reduced = match self {
+			Number::Integer(int) => Number::Integer(int % full_turn.to_bigint().unwrap()),
+			Number::Rational(num, denom) => {
+				let turn = denom.to_bigint().unwrap() * full_turn.to_bigint().unwrap();
+				Number::Rational(num % turn, denom.clone()).simplify()
+			}
+			Number::Decimal(num) => Number::Decimal(num % &Decimal::from(full_turn)),
+		};
+		Cow::Owned(angle_mode.to_unit(&reduced, &AngleUnit::Radians))
+	}
+
 	pub fn log(&self) -> Number {
 		Number::Decimal(self.to_decimal().log10())
 	}
@@ -305,9 +469,309 @@ impl Number {
 		Number::Decimal(self.to_decimal().exp())
 	}
 
+	pub fn to_hms(&self) -> Result<Number> {
+		// Converts a number of hours into sexagesimal H.MMSS form
+		self.to_sexagesimal()
+	}
+
+	pub fn hms_to_hours(&self) -> Result<Number> {
+		// Converts a number in sexagesimal H.MMSS form into a number of hours
+		self.from_sexagesimal()
+	}
+
+	pub fn to_dms(&self) -> Result<Number> {
+		// Converts a number of degrees into sexagesimal D.MMSS form
+		self.to_sexagesimal()
+	}
+
+	pub fn dms_to_degrees(&self) -> Result<Number> {
+		// Converts a number in sexagesimal D.MMSS form into a number of degrees
+		self.from_sexagesimal()
+	}
+
+	fn to_sexagesimal(&self) -> Result<Number> {
+		if self.is_negative() {
+			return Ok(-(-self).to_sexagesimal()?);
+		}
+
+		// Work in total seconds so that rounding of the seconds can carry into
+		// the minutes and whole units.
+		let total_secs = Self::round_hms_seconds(self * &3600.to_number());
+		let whole_secs = total_secs.to_int()?.into_owned();
+		let frac_secs = &total_secs - &Number::Integer(whole_secs.clone());
+
+		let whole = &whole_secs / 3600.to_bigint().unwrap();
+		let minutes = (&whole_secs / 60.to_bigint().unwrap()) % 60.to_bigint().unwrap();
+		let secs = &Number::Integer(&whole_secs % 60.to_bigint().unwrap()) + &frac_secs;
+
+		Ok(
+			&(&Number::Integer(whole) + &(&Number::Integer(minutes) / &100.to_number()))
+				+ &(&secs / &10000.to_number()),
+		)
+	}
+
+	fn from_sexagesimal(&self) -> Result<Number> {
+		if self.is_negative() {
+			return Ok(-(-self).from_sexagesimal()?);
+		}
+
+		let whole = Number::Integer(self.to_int()?.into_owned());
+		let min_secs = &(self - &whole) * &100.to_number();
+		let minutes = Number::Integer(min_secs.to_int()?.into_owned());
+		let secs = &(&min_secs - &minutes) * &100.to_number();
+
+		Ok(&(&whole + &(&minutes / &60.to_number())) + &(&secs / &3600.to_number()))
+	}
+
+	pub fn floor_to_int(&self) -> Result<BigInt> {
+		match self {
+			Number::Integer(int) => Ok(int.clone()),
+			Number::Rational(num, denom) => Ok(num.div_floor(&denom.to_bigint().unwrap())),
+			Number::Decimal(num) => Ok(Number::Decimal(num.floor()).to_int()?.into_owned()),
+		}
+	}
+
+	pub fn ceil_to_int(&self) -> Result<BigInt> {
+		match self {
+			Number::Integer(int) => Ok(int.clone()),
+			Number::Rational(num, denom) => Ok(-(-num).div_floor(&denom.to_bigint().unwrap())),
+			Number::Decimal(num) => Ok(Number::Decimal(num.ceil()).to_int()?.into_owned()),
+		}
+	}
+
+	pub fn round_to_int(&self) -> Result<BigInt> {
+		// Halfway cases are rounded away from zero
+		match self {
+			Number::Integer(int) => Ok(int.clone()),
+			Number::Rational(num, denom) => {
+				let denom = denom.to_bigint().unwrap();
+				let twice = num * 2.to_bigint().unwrap();
+				let int = if num.sign() == Sign::Minus {
+					-((-twice + &denom) / (&denom * 2.to_bigint().unwrap()))
+				} else {
+					(twice + &denom) / (&denom * 2.to_bigint().unwrap())
+				};
+				Ok(int)
+			}
+			Number::Decimal(num) => Ok(Number::Decimal(num.round()).to_int()?.into_owned()),
+		}
+	}
+
+	/// Gets the integer part of the number, truncated toward zero.
+	pub fn integer_part(&self) -> Number {
+		match self {
+			Number::Integer(_) => self.clone(),
+			Number::Rational(num, denom) => Number::Integer(num / denom.to_bigint().unwrap()),
+			Number::Decimal(num) => Number::Decimal(num.trunc()),
+		}
+	}
+
+	/// Gets the fractional part of the number, which has the same sign as the number.
+	pub fn fractional_part(&self) -> Number {
+		match self {
+			Number::Integer(_) => Number::Integer(0.into()),
+			Number::Rational(num, denom) => {
+				Number::Rational(num % denom.to_bigint().unwrap(), denom.clone()).simplify()
+			}
+			Number::Decimal(num) => Number::Decimal(num.fract()),
+		}
+	}
+
+	/// Reduces a fraction to lowest terms. Decimals are converted to the exact fraction
+	/// that they represent if it fits within the limits of a rational number.
+	pub fn simplified(&self) -> Number {
+		match self {
+			Number::Integer(_) => self.clone(),
+			Number::Rational(_, _) => self.clone().simplify(),
+			Number::Decimal(value) => {
+				if value.is_nan() || value.is_infinite() {
+					return self.clone();
+				}
+
+				// Decimal strings are an integer followed by a power of ten exponent
+				let raw_str = value.to_string();
+				let parts: Vec<&str> = raw_str.split('E').collect();
+				if parts.len() != 2 {
+					return self.clone();
+				}
+				let digit_str = parts[0].strip_prefix('+').unwrap_or(parts[0]);
+				let digits = match BigInt::parse_bytes(digit_str.as_bytes(), 10) {
+					Some(digits) => digits,
+					None => return self.clone(),
+				};
+				let exponent: isize = match parts[1].parse() {
+					Ok(exponent) => exponent,
+					Err(_) => return self.clone(),
+				};
+				if exponent.abs() + digit_str.len() as isize > MAX_INTEGER_EXPONENT {
+					return self.clone();
+				}
+
+				let scale = 10.to_biguint().unwrap().pow(exponent.abs() as u32);
+				let result = if exponent < 0 {
+					Number::Rational(digits, scale).simplify()
+				} else {
+					Self::check_int_bounds(Number::Integer(digits * scale.to_bigint().unwrap()))
+				};
+				if result.is_exact() {
+					result
+				} else {
+					self.clone()
+				}
+			}
+		}
+	}
+
+	/// Returns the number of significant digits in a decimal and the exponent of its
+	/// least significant digit. Trailing zeros that were entered or computed are
+	/// significant. Exact numbers have unlimited precision and return `None`.
+	pub fn decimal_precision(&self) -> Option<(usize, isize)> {
+		match self {
+			Number::Decimal(value) => {
+				if value.is_nan() || value.is_infinite() {
+					return None;
+				}
+				let raw_str = value.to_string();
+				let parts: Vec<&str> = raw_str.split('E').collect();
+				if parts.len() != 2 {
+					return None;
+				}
+				let exponent: isize = parts[1].parse().ok()?;
+				Some((parts[0].len() - 1, exponent))
+			}
+			_ => None,
+		}
+	}
+
+	/// Rounds a decimal so that its least significant digit has the given exponent.
+	/// Exact numbers, and decimals that cannot hold that many digits, are unchanged.
+	pub fn with_decimal_exponent(&self, exponent: isize) -> Number {
+		match self {
+			Number::Decimal(value) => {
+				let exponent: Decimal = (exponent as i32).into();
+				let result = value.quantize(&exponent.exp10());
+				if result.is_nan() && !value.is_nan() {
+					self.clone()
+				} else {
+					Number::Decimal(result)
+				}
+			}
+			_ => self.clone(),
+		}
+	}
+
+	/// Rounds a decimal to the given number of significant digits, keeping trailing
+	/// zeros so that the precision is shown when displayed.
+	pub fn with_significant_digits(&self, digits: usize) -> Number {
+		match self.decimal_precision() {
+			Some((current_digits, exponent)) => {
+				self.with_decimal_exponent(exponent + current_digits as isize - digits as isize)
+			}
+			None => self.clone(),
+		}
+	}
+
+	/// Expands an exact number into the terms of its simple continued fraction. Floating
+	/// point numbers do not have an exact expansion and are rejected.
+	pub fn continued_fraction_terms(&self) -> Result<Vec<BigInt>> {
+		match self {
+			Number::Integer(int) => Ok([int.clone()].to_vec()),
+			Number::Rational(num, denom) => {
+				// Euclidean algorithm, each quotient is the next term of the expansion
+				let mut terms = Vec::new();
+				let mut num = num.clone();
+				let mut denom = denom.to_bigint().unwrap();
+				while denom.sign() != Sign::NoSign {
+					let (term, remainder) = num.div_mod_floor(&denom);
+					terms.push(term);
+					num = denom;
+					denom = remainder;
+				}
+				Ok(terms)
+			}
+			Number::Decimal(_) => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn continued_fraction(&self) -> Result<Vector> {
+		let mut result = Vector::new()?;
+		for term in self.continued_fraction_terms()? {
+			result.push(Value::Number(Number::Integer(term)))?;
+		}
+		Ok(result)
+	}
+
+	pub fn round_to_multiple(&self, step: &Number) -> Result<Number> {
+		// Round half away from zero to the nearest multiple of the step
+		if step.is_zero() {
+			return Err(Error::ValueOutOfRange);
+		}
+		let quotient = self / step;
+		let half = &1.to_number() / &2.to_number();
+		let adjusted = if quotient.is_negative() {
+			&quotient - &half
+		} else {
+			&quotient + &half
+		};
+		Ok(&Number::Integer(adjusted.to_int()?.into_owned()) * step)
+	}
+
+	pub fn truncate_to_multiple(&self, step: &Number) -> Result<Number> {
+		// Round toward zero to a multiple of the step
+		if step.is_zero() {
+			return Err(Error::ValueOutOfRange);
+		}
+		let quotient = self / step;
+		Ok(&Number::Integer(quotient.to_int()?.into_owned()) * step)
+	}
+
+	/// Rounds to the given number of significant digits. Halfway cases are rounded away
+	/// from zero. Exact numbers stay exact.
+	pub fn round_to_significant_digits(&self, digits: usize) -> Result<Number> {
+		if digits == 0 {
+			return Err(Error::ValueOutOfRange);
+		}
+		if self.is_zero() || self.is_infinite() || self.is_nan() {
+			return Ok(self.clone());
+		}
+
+		// Round to a multiple of the power of ten of the last digit that is kept
+		let exponent = i32::try_from(&self.abs().log().floor_to_int()?)?;
+		let shift = exponent - i32::try_from(digits)? + 1;
+		let step = if shift >= 0 {
+			Number::Integer(10.to_bigint().unwrap().pow(shift as u32))
+		} else {
+			Number::Rational(1.into(), 10.to_biguint().unwrap().pow((-shift) as u32))
+		};
+		let result = self.round_to_multiple(&step)?;
+		if self.is_exact() {
+			Ok(result)
+		} else {
+			Ok(Number::Decimal(result.to_decimal().into_owned()))
+		}
+	}
+
+	fn round_hms_seconds(secs: Number) -> Number {
+		// Floating point results can be slightly off from the intended number of
+		// seconds, round them so that 59.999... seconds becomes a whole minute.
+		match secs {
+			Number::Decimal(value) => {
+				let scale: Decimal = 1_000_000_000_000_000_000u64.into();
+				let half = Decimal::from(1) / Decimal::from(2);
+				Number::Decimal((&(&value * &scale) + &half).trunc() / scale)
+			}
+			_ => secs,
+		}
+	}
+
 	fn simplify(self) -> Self {
 		match self {
 			Number::Rational(num, denom) => {
+				if denom == 0.to_biguint().unwrap() {
+					// Never build a rational with a zero denominator, use float to get
+					// the right inf/NaN instead
+					return Number::Decimal(Self::bigint_to_decimal(&num) / Decimal::zero());
+				}
 				let num_abs = if num.sign() == Sign::Minus {
 					(-&num).to_biguint().unwrap()
 				} else {
@@ -443,13 +907,13 @@ impl Number {
 	}
 
 	fn num_div(&self, rhs: &Number) -> Number {
+		if rhs.is_zero() {
+			// Divide by zero, use float to get the right inf/NaN
+			return Number::Decimal(&*self.to_decimal() / &*rhs.to_decimal());
+		}
 		match &self {
 			Number::Integer(left) => match rhs {
 				Number::Integer(right) => {
-					if right == &0.to_bigint().unwrap() {
-						// Divide by zero, use float to get the right inf/NaN
-						return Number::Decimal(&*self.to_decimal() / &*rhs.to_decimal());
-					}
 					if right.sign() == Sign::Minus {
 						Number::Rational(-left.to_bigint().unwrap(), (-right).to_biguint().unwrap())
 							.simplify()
@@ -727,6 +1191,29 @@ impl ToNumber for BigUint {
 	}
 }
 
+impl PartialEq for Number {
+	fn eq(&self, other: &Number) -> bool {
+		self.partial_cmp(other) == Some(Ordering::Equal)
+	}
+}
+
+impl PartialOrd for Number {
+	fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+		// Compare using the sign of the difference, which is exact for integers and
+		// rational numbers. NaN is not ordered with respect to anything.
+		let diff = self - other;
+		if diff.is_nan() {
+			None
+		} else if diff.is_zero() {
+			Some(Ordering::Equal)
+		} else if diff.is_negative() {
+			Some(Ordering::Less)
+		} else {
+			Some(Ordering::Greater)
+		}
+	}
+}
+
 impl core::ops::Add for Number {
 	type Output = Self;
 
@@ -964,8 +1451,12 @@ impl StorageObject for Number {
 					digits.push(input.read_u32()?);
 				}
 
-				// Build denominator from parts
+				// Build denominator from parts. A zero denominator can't be produced by
+				// any operation, so the data must be corrupt.
 				let denominator = BigUint::from_slice(&digits);
+				if denominator == 0.to_biguint().unwrap() {
+					return Err(Error::CorruptData);
+				}
 
 				// Return rational from numerator and denominator
 				Ok(Number::Rational(numerator, denominator))
@@ -980,3 +1471,77 @@ impl StorageObject for Number {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::{deserialize_inline, SerializeBytes};
+
+	#[test]
+	fn parse_decimal_exactly() {
+		match Number::from_str("0.1").unwrap() {
+			Number::Decimal(value) => assert!(value == Decimal::from_str("0.1").unwrap()),
+			_ => panic!("expected a decimal"),
+		}
+		let tenth = Number::from_str("0.1").unwrap();
+		assert!(&tenth * &Number::from(10) == Number::from(1));
+	}
+
+	#[test]
+	fn parse_rational() {
+		match Number::from_str("22/7").unwrap() {
+			Number::Rational(num, denom) => {
+				assert_eq!(num, 22.to_bigint().unwrap());
+				assert_eq!(denom, 7.to_biguint().unwrap());
+			}
+			_ => panic!("expected a rational"),
+		}
+		assert!(Number::from_str("1/0").is_err());
+	}
+
+	#[test]
+	fn reject_zero_denominator() {
+		let mut output = SerializeBytes::new();
+		output.write_u8(NUM_SERIALIZE_TYPE_RATIONAL).unwrap();
+		output.write_u8(NUM_SERIALIZE_SIGN_POSITIVE).unwrap();
+		output.write_u32(1).unwrap();
+		output.write_u32(1).unwrap();
+		output.write_u32(0).unwrap();
+		let data = output.into_bytes();
+		let mut input = DeserializeInput::new(&data);
+		assert_eq!(
+			deserialize_inline::<Number>(&mut input).err(),
+			Some(Error::CorruptData)
+		);
+
+		// Building a rational with a zero denominator gives infinity instead
+		let value = Number::Rational(1.into(), 0.to_biguint().unwrap()).simplify();
+		assert!(value.to_decimal().is_infinite());
+	}
+
+	#[test]
+	fn gamma_and_error_function() {
+		// Gamma of an integer is exact, and agrees with the decimal computation
+		assert!(5.to_number().gamma() == 24.to_number());
+		let gamma = Number::Decimal(5.into()).gamma().to_f64();
+		assert!((gamma - 24.0).abs() < 1e-12);
+		assert_eq!(0.to_number().erf().to_f64(), 0.0);
+		assert!((1.to_number().erf().to_f64() - 0.8427007929497149).abs() < 1e-12);
+	}
+
+	#[test]
+	fn exact_rational_powers() {
+		let two_thirds = Number::from_str("2/3").unwrap();
+		let cube = two_thirds.pow(&3.into());
+		assert!(matches!(cube, Number::Rational(_, _)));
+		assert!(cube == Number::from_str("8/27").unwrap());
+		let three_quarters = Number::from_str("3/4").unwrap();
+		match three_quarters.pow(&(-2).into()) {
+			Number::Rational(num, denom) => {
+				assert_eq!(num, 16.to_bigint().unwrap());
+				assert_eq!(denom, 9.to_biguint().unwrap());
+			}
+			_ => panic!("expected a rational"),
+		}
+	}
+}
@@ -0,0 +1,93 @@
+use crate::error::{Error, Result};
+use crate::number::Number;
+use intel_dfp::Decimal;
+use num_bigint::{BigInt, BigUint, Sign};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+const PCG_INCREMENT: u64 = 1442695040888963407;
+
+// Random decimals are generated with this many digits after the decimal point. This
+// is the largest power of ten that fits in a 64-bit integer.
+const DECIMAL_DIGITS: i32 = 19;
+const DECIMAL_SCALE: u64 = 10_000_000_000_000_000_000;
+
+/// Pseudorandom number generator using the PCG32 algorithm. There is no operating
+/// system to provide entropy on the device, so the generator is seeded explicitly.
+/// A given seed always produces the same sequence of values.
+#[derive(Clone)]
+pub struct Random {
+	state: u64,
+}
+
+impl Random {
+	pub fn new(seed: u64) -> Self {
+		let mut result = Random { state: 0 };
+		result.seed(seed);
+		result
+	}
+
+	pub fn seed(&mut self, seed: u64) {
+		// Standard PCG initialization sequence
+		self.state = 0;
+		self.next_u32();
+		self.state = self.state.wrapping_add(seed);
+		self.next_u32();
+	}
+
+	pub fn next_u32(&mut self) -> u32 {
+		let old = self.state;
+		self.state = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(PCG_INCREMENT);
+		let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+		let rot = (old >> 59) as u32;
+		xorshifted.rotate_right(rot)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		((self.next_u32() as u64) << 32) | self.next_u32() as u64
+	}
+
+	/// Generates a uniformly distributed decimal in the range [0, 1).
+	pub fn next_decimal(&mut self) -> Number {
+		// Reject values outside of the decimal scale so that every digit is uniform
+		loop {
+			let value = self.next_u64();
+			if value < DECIMAL_SCALE {
+				let value: Decimal = value.into();
+				return Number::Decimal(&value / &Decimal::from(DECIMAL_DIGITS).exp10());
+			}
+		}
+	}
+
+	/// Generates a uniformly distributed integer in the range [0, limit).
+	pub fn next_below(&mut self, limit: &BigInt) -> Result<BigInt> {
+		if limit.sign() != Sign::Plus {
+			return Err(Error::ValueOutOfRange);
+		}
+
+		// Generate random values with the same number of bits as the limit, and reject
+		// any that are out of range. This takes fewer than two tries on average.
+		let bits = limit.bits() as usize;
+		let words = (bits + 31) / 32;
+		let top_mask = match bits % 32 {
+			0 => u32::MAX,
+			top_bits => (1 << top_bits) - 1,
+		};
+		let limit = limit.magnitude();
+		loop {
+			let mut digits = Vec::new();
+			for _ in 0..words {
+				digits.push(self.next_u32());
+			}
+			if let Some(top) = digits.last_mut() {
+				*top &= top_mask;
+			}
+			let value = BigUint::from_slice(&digits);
+			if &value < limit {
+				return Ok(BigInt::from_biguint(Sign::Plus, value));
+			}
+		}
+	}
+}
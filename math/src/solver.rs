@@ -0,0 +1,218 @@
+use crate::error::{Error, Result};
+use crate::number::Number;
+use intel_dfp::Decimal;
+
+// Number of iterations to attempt before assuming the solver is diverging
+const MAX_SOLVE_ITERATIONS: usize = 100;
+
+fn evaluate<F: FnMut(&Number) -> Result<Number>>(func: &mut F, x: &Decimal) -> Result<Decimal> {
+	let result = func(&Number::Decimal(x.clone()))?.to_decimal().into_owned();
+	if result.is_finite() {
+		Ok(result)
+	} else {
+		Err(Error::ValueOutOfRange)
+	}
+}
+
+fn magnitude(x: &Decimal) -> Decimal {
+	// Step sizes and tolerances are relative to the value, but never smaller than
+	// they would be for a value of one.
+	let abs = x.abs();
+	if abs > 1.into() {
+		abs
+	} else {
+		1.into()
+	}
+}
+
+/// Finds a root of `func` near `guess` using Newton's method. The derivative is
+/// estimated numerically, and the secant method is used instead when the derivative
+/// vanishes. The result is accurate to at least `precision` significant digits.
+pub fn solve<F: FnMut(&Number) -> Result<Number>>(
+	mut func: F,
+	guess: &Number,
+	precision: usize,
+) -> Result<Number> {
+	let tolerance = Decimal::from(-(precision as i32) - 2).exp10();
+	let step = Decimal::from(-12).exp10();
+	let nudge = Decimal::from(-3).exp10();
+	let two: Decimal = 2.into();
+
+	let mut x = guess.to_decimal().into_owned();
+	let mut prev: Option<(Decimal, Decimal)> = None;
+	for _ in 0..MAX_SOLVE_ITERATIONS {
+		let fx = evaluate(&mut func, &x)?;
+		if fx == Decimal::zero() {
+			return Ok(Number::Decimal(x));
+		}
+
+		// Estimate the derivative with a central difference
+		let h = &magnitude(&x) * &step;
+		let upper = evaluate(&mut func, &(&x + &h))?;
+		let lower = evaluate(&mut func, &(&x - &h))?;
+		let derivative = &(&upper - &lower) / &(&two * &h);
+
+		let mut next = &x - &(&fx / &derivative);
+		if !next.is_finite() {
+			// Derivative vanished, fall back to a secant through the previous point. If
+			// this is the first iteration, move away from the flat spot and try again.
+			next = match &prev {
+				Some((prev_x, prev_fx)) => &x - &(&fx * &(&(&x - prev_x) / &(&fx - prev_fx))),
+				None => &x + &(&magnitude(&x) * &nudge),
+			};
+			if !next.is_finite() {
+				return Err(Error::ValueOutOfRange);
+			}
+		}
+
+		if (&next - &x).abs() <= &tolerance * &magnitude(&next) {
+			return Ok(Number::Decimal(next));
+		}
+
+		prev = Some((x, fx));
+		x = next;
+	}
+
+	Err(Error::ValueOutOfRange)
+}
+
+// Maximum number of times an interval can be split in half during integration
+const MAX_INTEGRATE_DEPTH: usize = 16;
+
+// Maximum number of intervals that will be subdivided during integration, this keeps
+// the number of evaluations bounded for functions that are difficult to integrate.
+const MAX_INTEGRATE_SUBDIVISIONS: usize = 1000;
+
+struct SimpsonInterval {
+	a: Decimal,
+	fa: Decimal,
+	m: Decimal,
+	fm: Decimal,
+	b: Decimal,
+	fb: Decimal,
+	estimate: Decimal,
+}
+
+impl SimpsonInterval {
+	fn new(a: Decimal, fa: Decimal, m: Decimal, fm: Decimal, b: Decimal, fb: Decimal) -> Self {
+		let estimate = Self::estimate(&a, &fa, &fm, &b, &fb);
+		SimpsonInterval {
+			a,
+			fa,
+			m,
+			fm,
+			b,
+			fb,
+			estimate,
+		}
+	}
+
+	fn estimate(a: &Decimal, fa: &Decimal, fm: &Decimal, b: &Decimal, fb: &Decimal) -> Decimal {
+		let four: Decimal = 4.into();
+		let six: Decimal = 6.into();
+		&(&(b - a) / &six) * &(&(fa + &(&four * fm)) + fb)
+	}
+}
+
+fn integrate_interval<F: FnMut(&Number) -> Result<Number>>(
+	func: &mut F,
+	interval: SimpsonInterval,
+	tolerance: &Decimal,
+	depth: usize,
+	subdivisions: &mut usize,
+) -> Result<Decimal> {
+	let two: Decimal = 2.into();
+	let left_m = &(&interval.a + &interval.m) / &two;
+	let right_m = &(&interval.m + &interval.b) / &two;
+	let left_fm = evaluate(func, &left_m)?;
+	let right_fm = evaluate(func, &right_m)?;
+	let left = SimpsonInterval::new(
+		interval.a,
+		interval.fa,
+		left_m,
+		left_fm,
+		interval.m.clone(),
+		interval.fm.clone(),
+	);
+	let right = SimpsonInterval::new(
+		interval.m,
+		interval.fm,
+		right_m,
+		right_fm,
+		interval.b,
+		interval.fb,
+	);
+
+	// Compare the refined estimate against the estimate for the whole interval. When
+	// the difference is small enough, use Richardson extrapolation for the result.
+	let fifteen: Decimal = 15.into();
+	let refined = &left.estimate + &right.estimate;
+	let delta = &refined - &interval.estimate;
+	if depth == 0 || *subdivisions == 0 || delta.abs() <= &fifteen * tolerance {
+		return Ok(&refined + &(&delta / &fifteen));
+	}
+
+	*subdivisions -= 1;
+	let tolerance = tolerance / &two;
+	let left = integrate_interval(func, left, &tolerance, depth - 1, subdivisions)?;
+	let right = integrate_interval(func, right, &tolerance, depth - 1, subdivisions)?;
+	Ok(&left + &right)
+}
+
+/// Integrates `func` from `lower` to `upper` using adaptive Simpson's rule. The
+/// tolerance is chosen so that the result is accurate to about `precision`
+/// significant digits, but the number of subdivisions is bounded so the result may
+/// be less accurate for functions that are difficult to integrate.
+pub fn integrate<F: FnMut(&Number) -> Result<Number>>(
+	mut func: F,
+	lower: &Number,
+	upper: &Number,
+	precision: usize,
+) -> Result<Number> {
+	let a = lower.to_decimal().into_owned();
+	let b = upper.to_decimal().into_owned();
+	if !a.is_finite() || !b.is_finite() {
+		return Err(Error::ValueOutOfRange);
+	}
+
+	let two: Decimal = 2.into();
+	let m = &(&a + &b) / &two;
+	let fa = evaluate(&mut func, &a)?;
+	let fm = evaluate(&mut func, &m)?;
+	let fb = evaluate(&mut func, &b)?;
+	let interval = SimpsonInterval::new(a, fa, m, fm, b, fb);
+
+	// Tolerance is relative to the initial estimate of the integral
+	let tolerance = &Decimal::from(-(precision as i32)).exp10() * &magnitude(&interval.estimate);
+	let mut subdivisions = MAX_INTEGRATE_SUBDIVISIONS;
+	let result = integrate_interval(
+		&mut func,
+		interval,
+		&tolerance,
+		MAX_INTEGRATE_DEPTH,
+		&mut subdivisions,
+	)?;
+	if result.is_finite() {
+		Ok(Number::Decimal(result))
+	} else {
+		Err(Error::ValueOutOfRange)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn solve_square_root_of_two() {
+		let root = solve(|x| Ok(&(x * x) - &Number::from(2)), &Number::from(1), 16).unwrap();
+		assert!((root.to_f64() - 2.0f64.sqrt()).abs() < 1e-14);
+	}
+
+	#[test]
+	fn integrate_sine() {
+		let pi = Number::Decimal(Decimal::pi());
+		let area = integrate(|x| Ok(x.sin()), &Number::from(0), &pi, 12).unwrap();
+		assert!((area.to_f64() - 2.0).abs() < 1e-10);
+	}
+}
@@ -1,8 +1,13 @@
 use crate::error::{Error, Result};
-use crate::format::IntegerMode;
+use crate::format::{IntegerMode, IntegerOverflowMode};
 use crate::number::Number;
-use crate::storage::store;
-use crate::undo::{clear_undo_buffer, pop_undo_action, push_undo_action, UndoAction};
+use crate::storage::{
+	deserialize_inline, serialize_inline, store, DeserializeInput, SerializeOutput,
+};
+use crate::undo::{
+	clear_undo_buffer, pop_redo_action, pop_undo_action, push_redo_action, push_undo_action,
+	push_undo_action_preserving_redo, UndoAction,
+};
 use crate::value::{Value, ValueRef};
 use num_bigint::ToBigInt;
 
@@ -16,12 +21,26 @@ const MAX_STACK_ENTRIES: usize = 1024;
 
 pub struct Stack {
 	entries: Vec<ValueRef>,
-	push_new_entry: bool,
+	// Stack lift works as on HP calculators. When enabled, the next value entered with
+	// `input_value` is pushed onto the stack. When disabled, it replaces the top of the
+	// stack instead. Lift is enabled by pushing, popping, replacing, swapping, or
+	// rotating entries, and by `restore_entries` so that a number typed after restoring
+	// saved state does not overwrite the top entry. It is disabled when the stack is
+	// cleared or emptied by undo, and by `disable_stack_lift` (used by CLx). The Enter
+	// key does not use `enter`: it completes number entry, then pushes a copy of the
+	// top if duplication is on, both of which leave lift enabled.
+	stack_lift_enabled: bool,
 	empty: bool,
 	undo: bool,
 	notifications: Vec<Box<dyn Fn(&StackEvent)>>,
 }
 
+pub struct StackSnapshot {
+	entries: Vec<ValueRef>,
+	stack_lift_enabled: bool,
+	empty: bool,
+}
+
 pub enum StackEvent {
 	ValuePushed,
 	ValuePopped,
@@ -43,7 +62,7 @@ impl Stack {
 	pub fn new() -> Self {
 		Stack {
 			entries: Vec::new(),
-			push_new_entry: false,
+			stack_lift_enabled: false,
 			empty: true,
 			undo: false,
 			notifications: Vec::new(),
@@ -53,7 +72,7 @@ impl Stack {
 	pub fn new_with_undo() -> Self {
 		Stack {
 			entries: Vec::new(),
-			push_new_entry: false,
+			stack_lift_enabled: false,
 			empty: true,
 			undo: true,
 			notifications: Vec::new(),
@@ -77,7 +96,11 @@ impl Stack {
 		self.entries.len()
 	}
 
-	pub fn value_for_integer_mode(mode: &IntegerMode, value: Value) -> Value {
+	pub fn value_for_integer_mode(
+		mode: &IntegerMode,
+		overflow: IntegerOverflowMode,
+		value: Value,
+	) -> Value {
 		match mode {
 			IntegerMode::Float => value,
 			IntegerMode::BigInteger => {
@@ -89,6 +112,27 @@ impl Stack {
 			}
 			IntegerMode::SizedInteger(size, signed) => {
 				if let Ok(int) = value.to_int() {
+					if overflow == IntegerOverflowMode::Saturate {
+						// Clamp to the range of the integer size
+						let (min, max) = if *signed {
+							let limit = 2.to_bigint().unwrap().pow((*size - 1) as u32);
+							(-&limit, limit - 1.to_bigint().unwrap())
+						} else {
+							(
+								0.to_bigint().unwrap(),
+								2.to_bigint().unwrap().pow(*size as u32) - 1.to_bigint().unwrap(),
+							)
+						};
+						let int = if *int < min {
+							min
+						} else if *int > max {
+							max
+						} else {
+							int.into_owned()
+						};
+						return Value::Number(Number::Integer(int));
+					}
+
 					let mask = 2.to_bigint().unwrap().pow(*size as u32) - 1.to_bigint().unwrap();
 					let mut int = &*int & &mask;
 					if *signed {
@@ -114,7 +158,7 @@ impl Stack {
 		self.entries.push(store(value)?);
 
 		self.notify(StackEvent::ValuePushed);
-		self.push_new_entry = true;
+		self.stack_lift_enabled = true;
 		self.empty = false;
 		Ok(())
 	}
@@ -157,6 +201,7 @@ impl Stack {
 		self.entries[(len - 1) - idx] = value_ref;
 
 		self.notify(StackEvent::ValueChanged(idx));
+		self.stack_lift_enabled = true;
 		self.empty = false;
 		Ok(())
 	}
@@ -174,6 +219,7 @@ impl Stack {
 		self.entries[(len - 1) - idx] = value_ref;
 
 		self.notify(StackEvent::ValueChanged(idx));
+		self.stack_lift_enabled = true;
 		self.empty = false;
 		Ok(())
 	}
@@ -188,7 +234,7 @@ impl Stack {
 
 	fn set_top_internal(&mut self, value: Value) -> Result<()> {
 		self.set_entry_internal(0, value)?;
-		self.push_new_entry = true;
+		self.stack_lift_enabled = true;
 		self.empty = false;
 		Ok(())
 	}
@@ -213,7 +259,7 @@ impl Stack {
 			let _ = self.pop_internal();
 		}
 
-		self.push_new_entry = true;
+		self.stack_lift_enabled = true;
 		Ok(())
 	}
 
@@ -241,7 +287,7 @@ impl Stack {
 			self.entries.extend_from_slice(&items[1..]);
 
 			self.notify(StackEvent::TopReplacedWithEntries(items.len()));
-			self.push_new_entry = true;
+			self.stack_lift_enabled = true;
 			self.empty = false;
 		}
 		push_undo_action!(
@@ -255,6 +301,7 @@ impl Stack {
 		match self.entries.pop() {
 			Some(value) => {
 				self.notify(StackEvent::ValuePopped);
+				self.stack_lift_enabled = true;
 				Ok(value)
 			}
 			None => Err(Error::NotEnoughValues),
@@ -272,7 +319,57 @@ impl Stack {
 		let b = self.entry_ref(b_idx)?.clone();
 		*self.entry_mut(a_idx)? = b;
 		*self.entry_mut(b_idx)? = a;
-		self.push_new_entry = true;
+		self.stack_lift_enabled = true;
+		Ok(())
+	}
+
+	fn replace_multiple_internal(
+		&mut self,
+		count: usize,
+		items: Vec<ValueRef>,
+	) -> Result<Vec<ValueRef>> {
+		if count > self.entries.len() {
+			return Err(Error::NotEnoughValues);
+		}
+
+		#[cfg(feature = "limited_heap")]
+		if (self.entries.len() + items.len() - count) > MAX_STACK_ENTRIES {
+			return Err(Error::StackOverflow);
+		}
+
+		let old_values = self.entries[self.entries.len() - count..].to_vec();
+		for _ in 0..count {
+			self.pop_internal()?;
+		}
+		for item in items {
+			self.entries.push(item);
+			self.notify(StackEvent::ValuePushed);
+		}
+		self.stack_lift_enabled = true;
+		self.empty = false;
+		Ok(old_values)
+	}
+
+	pub fn replace_entries_with_multiple(&mut self, count: usize, items: Vec<Value>) -> Result<()> {
+		let mut item_refs = Vec::new();
+		for item in items {
+			item_refs.push(store(item)?);
+		}
+		let item_count = item_refs.len();
+		let old_values = self.replace_multiple_internal(count, item_refs)?;
+		push_undo_action!(self.undo, UndoAction::ReplaceMultiple(item_count, old_values));
+		Ok(())
+	}
+
+	pub fn pop_multiple(&mut self, count: usize) -> Result<()> {
+		if count > self.entries.len() {
+			return Err(Error::NotEnoughValues);
+		}
+		let values = self.entries[self.entries.len() - count..].to_vec();
+		for _ in 0..count {
+			self.pop_internal()?;
+		}
+		push_undo_action!(self.undo, UndoAction::PopMultiple(values));
 		Ok(())
 	}
 
@@ -282,9 +379,8 @@ impl Stack {
 		Ok(())
 	}
 
-	pub fn rotate_down(&mut self) {
+	fn rotate_down_internal(&mut self) {
 		if self.entries.len() > 1 {
-			push_undo_action!(self.undo, UndoAction::RotateDown);
 			let top = self.top_ref().unwrap().clone();
 			let _ = self.pop_internal();
 			self.entries.insert(0, top);
@@ -295,6 +391,13 @@ impl Stack {
 		}
 	}
 
+	pub fn rotate_down(&mut self) {
+		if self.entries.len() > 1 {
+			push_undo_action!(self.undo, UndoAction::RotateDown);
+			self.rotate_down_internal();
+		}
+	}
+
 	fn rotate_up_internal(&mut self) {
 		if self.entries.len() > 1 {
 			let bottom = self.entries[0].clone();
@@ -302,7 +405,14 @@ impl Stack {
 			self.entries.push(bottom);
 
 			self.notify(StackEvent::RotateUp);
-			self.push_new_entry = true;
+			self.stack_lift_enabled = true;
+		}
+	}
+
+	pub fn rotate_up(&mut self) {
+		if self.entries.len() > 1 {
+			push_undo_action!(self.undo, UndoAction::RotateUp);
+			self.rotate_up_internal();
 		}
 	}
 
@@ -310,87 +420,246 @@ impl Stack {
 		push_undo_action!(self.undo, UndoAction::Clear(self.entries.clone()));
 		self.entries.clear();
 		self.notify(StackEvent::Invalidate);
-		self.push_new_entry = false;
+		self.stack_lift_enabled = false;
 		self.empty = true;
 	}
 
 	pub fn enter(&mut self) -> Result<()> {
 		self.push(self.top()?.clone())?;
-		self.push_new_entry = false;
+		self.stack_lift_enabled = false;
 		Ok(())
 	}
 
+	/// Enters a new value onto the stack, replacing the top entry instead of pushing
+	/// if stack lift has been disabled.
 	pub fn input_value(&mut self, value: Value) -> Result<()> {
-		if self.push_new_entry {
+		if self.stack_lift_enabled || self.entries.len() == 0 {
 			self.push(value)
 		} else {
 			self.set_top(value)
 		}
 	}
 
+	pub fn stack_lift_enabled(&self) -> bool {
+		self.stack_lift_enabled
+	}
+
+	/// Causes the next value entered to replace the top of the stack instead of
+	/// being pushed.
+	pub fn disable_stack_lift(&mut self) {
+		self.stack_lift_enabled = false;
+	}
+
 	pub fn clear_undo_buffer(&mut self) {
 		if self.undo {
 			clear_undo_buffer();
 		}
 	}
 
+	pub fn undo_enabled(&self) -> bool {
+		self.undo
+	}
+
+	/// Discards the most recent undo action without applying it. This is used when the
+	/// change that was recorded has already been reverted by other means.
+	pub fn discard_undo_action(&mut self) {
+		if self.undo {
+			let _ = pop_undo_action();
+		}
+	}
+
+	/// Enables or disables recording of undo actions. This is used when stack
+	/// operations are performed internally and should not be visible to the user.
+	pub fn set_undo_enabled(&mut self, undo: bool) {
+		self.undo = undo;
+	}
+
 	pub fn undo(&mut self) -> Result<()> {
 		if self.undo {
-			match pop_undo_action()? {
-				UndoAction::Push => {
-					self.pop_internal()?;
+			let redo_action = self.undo_action(pop_undo_action()?)?;
+			push_redo_action(redo_action);
+			Ok(())
+		} else {
+			Err(Error::UndoBufferEmpty)
+		}
+	}
+
+	pub fn redo(&mut self) -> Result<()> {
+		if self.undo {
+			let undo_action = self.undo_action(pop_redo_action()?)?;
+			push_undo_action_preserving_redo(undo_action);
+			Ok(())
+		} else {
+			Err(Error::RedoBufferEmpty)
+		}
+	}
+
+	pub fn undo_action(&mut self, action: UndoAction) -> Result<UndoAction> {
+		// Apply the action and return the action that reverses it, so that
+		// undone actions can be redone.
+		if !self.undo {
+			return Err(Error::UndoBufferEmpty);
+		}
+		match action {
+			UndoAction::Push => {
+				let value = self.pop_internal()?;
+				Ok(UndoAction::Pop(value))
+			}
+			UndoAction::Pop(value) => {
+				if self.empty {
+					self.set_top_internal(value.get()?)?;
+				} else {
+					self.push_internal(value.get()?)?;
 				}
-				UndoAction::Pop(value) => {
-					if self.empty {
-						self.set_top_internal(value.get()?)?;
-					} else {
+				Ok(UndoAction::Push)
+			}
+			UndoAction::Replace(values) => {
+				if values.len() == 0 {
+					let value = self.pop_internal()?;
+					Ok(UndoAction::Pop(value))
+				} else {
+					let old_value = self.top_ref()?.clone();
+					self.set_top_internal(values[0].get()?)?;
+					for value in &values[1..] {
 						self.push_internal(value.get()?)?;
 					}
+					Ok(UndoAction::ReplaceTopWithMultiple(values.len(), old_value))
 				}
-				UndoAction::Replace(values) => {
-					if values.len() == 0 {
-						self.pop_internal()?;
-					} else {
-						self.set_top_internal(values[0].get()?)?;
-						for value in &values[1..] {
-							self.push_internal(value.get()?)?;
-						}
-					}
+			}
+			UndoAction::Swap(a, b) => {
+				self.swap_internal(a, b)?;
+				Ok(UndoAction::Swap(a, b))
+			}
+			UndoAction::Clear(values) => {
+				let mut value_refs = Vec::new();
+				for value in values.iter() {
+					value_refs.push(store(value.get()?)?);
 				}
-				UndoAction::Swap(a, b) => {
-					self.swap_internal(a, b)?;
+				if !self.empty {
+					value_refs.extend_from_slice(&self.entries);
 				}
-				UndoAction::Clear(values) => {
-					let mut value_refs = Vec::new();
-					for value in values.iter() {
-						value_refs.push(store(value.get()?)?);
-					}
-					if !self.empty {
-						value_refs.extend_from_slice(&self.entries);
-					}
-					self.entries = value_refs;
-					self.notify(StackEvent::Invalidate);
-					self.push_new_entry = true;
-					//self.editor = None;
-					self.empty = false;
+				self.entries = value_refs;
+				self.notify(StackEvent::Invalidate);
+				self.stack_lift_enabled = true;
+				//self.editor = None;
+				self.empty = false;
+				Ok(UndoAction::RestoreEntries(values.len()))
+			}
+			UndoAction::RotateDown => {
+				self.rotate_up_internal();
+				Ok(UndoAction::RotateUp)
+			}
+			UndoAction::RotateUp => {
+				self.rotate_down_internal();
+				Ok(UndoAction::RotateDown)
+			}
+			UndoAction::SetStackEntry(idx, value) => {
+				let old_value = self.entry_ref(idx)?.clone();
+				self.set_entry_internal(idx, value.get()?)?;
+				Ok(UndoAction::SetStackEntry(idx, old_value))
+			}
+			UndoAction::ReplaceTopWithMultiple(count, value) => {
+				if count > self.entries.len() {
+					return Err(Error::NotEnoughValues);
+				}
+				let old_values = self.entries[self.entries.len() - count..].to_vec();
+				self.replace_entries_internal(count, value.get()?)?;
+				Ok(UndoAction::Replace(old_values))
+			}
+			UndoAction::RestoreEntries(count) => {
+				// Remove entries that were restored to the bottom of the stack
+				if count > self.entries.len() {
+					return Err(Error::NotEnoughValues);
 				}
-				UndoAction::RotateDown => {
-					self.rotate_up_internal();
+				let values: Vec<ValueRef> = self.entries.drain(0..count).collect();
+				self.notify(StackEvent::Invalidate);
+				if self.entries.len() == 0 {
+					self.stack_lift_enabled = false;
+					self.empty = true;
 				}
-				UndoAction::SetStackEntry(idx, value) => {
-					self.set_entry_internal(idx, value.get()?)?;
+				Ok(UndoAction::Clear(values))
+			}
+			UndoAction::PopMultiple(values) => {
+				for value in &values {
+					self.push_internal(value.get()?)?;
+				}
+				Ok(UndoAction::PushMultiple(values.len()))
+			}
+			UndoAction::PushMultiple(count) => {
+				if count > self.entries.len() {
+					return Err(Error::NotEnoughValues);
 				}
-				UndoAction::ReplaceTopWithMultiple(count, value) => {
-					self.replace_entries_internal(count, value.get()?)?;
+				let values = self.entries[self.entries.len() - count..].to_vec();
+				for _ in 0..count {
+					self.pop_internal()?;
 				}
+				Ok(UndoAction::PopMultiple(values))
+			}
+			UndoAction::ReplaceMultiple(count, values) => {
+				let value_count = values.len();
+				let old_values = self.replace_multiple_internal(count, values)?;
+				Ok(UndoAction::ReplaceMultiple(value_count, old_values))
+			}
+			UndoAction::ClearVariables(_) | UndoAction::RestoreVariables(_) => {
+				// Variables are not part of the stack, this must be handled by the context
+				Err(Error::DataTypeMismatch)
 			}
-			Ok(())
-		} else {
-			Err(Error::UndoBufferEmpty)
 		}
 	}
 
 	pub fn invalidate_caches(&self) {
 		self.notify(StackEvent::Invalidate);
 	}
+
+	/// Captures the current contents of the stack so that it can be restored later
+	/// with `restore_snapshot`. Values are shared, so this is inexpensive.
+	pub fn snapshot(&self) -> StackSnapshot {
+		StackSnapshot {
+			entries: self.entries.clone(),
+			stack_lift_enabled: self.stack_lift_enabled,
+			empty: self.empty,
+		}
+	}
+
+	/// Restores the stack to a previously captured state without recording an
+	/// undo action.
+	pub fn restore_snapshot(&mut self, snapshot: StackSnapshot) {
+		self.entries = snapshot.entries;
+		self.stack_lift_enabled = snapshot.stack_lift_enabled;
+		self.empty = snapshot.empty;
+		self.notify(StackEvent::Invalidate);
+	}
+
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		output.write_u32(self.entries.len() as u32)?;
+		for entry in &self.entries {
+			serialize_inline(&entry.get()?, output)?;
+		}
+		Ok(())
+	}
+
+	pub fn deserialize_entries(input: &mut DeserializeInput) -> Result<Vec<ValueRef>> {
+		let count = input.read_u32()? as usize;
+
+		#[cfg(feature = "limited_heap")]
+		if count > MAX_STACK_ENTRIES {
+			return Err(Error::CorruptData);
+		}
+
+		let mut entries = Vec::new();
+		for _ in 0..count {
+			entries.push(store(deserialize_inline::<Value>(input)?)?);
+		}
+		Ok(entries)
+	}
+
+	pub fn restore_entries(&mut self, entries: Vec<ValueRef>) {
+		// Restoring the stack replaces its contents entirely, so the undo history
+		// is no longer valid.
+		self.clear_undo_buffer();
+		self.entries = entries;
+		self.notify(StackEvent::Invalidate);
+		self.stack_lift_enabled = true;
+		self.empty = self.entries.len() == 0;
+	}
 }
@@ -0,0 +1,215 @@
+use crate::error::{Error, Result};
+use crate::number::{Number, ToNumber};
+use intel_dfp::Decimal;
+
+pub struct LinearRegression {
+	pub slope: Number,
+	pub intercept: Number,
+	// Correlation coefficient, which is undefined when all points have the same y value
+	pub correlation: Option<Number>,
+}
+
+/// Computes the least squares line through a set of `(x, y)` points. Sums are kept
+/// exact where possible so that exactly linear data produces an exact fit.
+pub fn linear_regression(points: &[(Number, Number)]) -> Result<LinearRegression> {
+	if points.len() < 2 {
+		return Err(Error::ValueNotDefined);
+	}
+
+	let mut sum_x = 0.to_number();
+	let mut sum_y = 0.to_number();
+	let mut sum_xx = 0.to_number();
+	let mut sum_yy = 0.to_number();
+	let mut sum_xy = 0.to_number();
+	for (x, y) in points {
+		sum_x = &sum_x + x;
+		sum_y = &sum_y + y;
+		sum_xx = &sum_xx + &(x * x);
+		sum_yy = &sum_yy + &(y * y);
+		sum_xy = &sum_xy + &(x * y);
+	}
+
+	// Work with the scaled covariance and variances to avoid division until the end
+	let n = points.len().to_number();
+	let cov = &(&n * &sum_xy) - &(&sum_x * &sum_y);
+	let var_x = &(&n * &sum_xx) - &(&sum_x * &sum_x);
+	let var_y = &(&n * &sum_yy) - &(&sum_y * &sum_y);
+	if var_x.is_zero() {
+		// All points are on a vertical line, there is no slope
+		return Err(Error::ValueOutOfRange);
+	}
+
+	let slope = &cov / &var_x;
+	let intercept = &(&sum_y - &(&slope * &sum_x)) / &n;
+	let correlation = if var_y.is_zero() {
+		None
+	} else {
+		Some(&cov / &(&var_x * &var_y).sqrt())
+	};
+	Ok(LinearRegression {
+		slope,
+		intercept,
+		correlation,
+	})
+}
+
+// Coefficients for Acklam's rational approximation of the inverse normal
+// distribution, which is accurate to about nine digits before refinement.
+const INV_CDF_A: [&str; 6] = [
+	"-39.69683028665376",
+	"220.9460984245205",
+	"-275.9285104469687",
+	"138.3577518672690",
+	"-30.66479806614716",
+	"2.506628277459239",
+];
+const INV_CDF_B: [&str; 5] = [
+	"-54.47609879822406",
+	"161.5858368580409",
+	"-155.6989798598866",
+	"66.80131188771972",
+	"-13.28068155288572",
+];
+const INV_CDF_C: [&str; 6] = [
+	"-0.007784894002430293",
+	"-0.3223964580411365",
+	"-2.400758276161838",
+	"-2.549732539343734",
+	"4.374664141464968",
+	"2.938163982698783",
+];
+const INV_CDF_D: [&str; 4] = [
+	"0.007784695709041462",
+	"0.3224671290700398",
+	"2.445134137142996",
+	"3.754408661907416",
+];
+const INV_CDF_TAIL: &str = "0.02425";
+
+// Number of Halley iterations used to refine the inverse normal approximation. Each
+// iteration roughly triples the number of correct digits.
+const INV_CDF_REFINE_ITERATIONS: usize = 3;
+
+fn standard_normal_cdf(z: &Decimal) -> Decimal {
+	let two: Decimal = 2.into();
+	&(-&(z / &two.sqrt())).erfc() / &two
+}
+
+fn polynomial(coefficients: &[&str], x: &Decimal) -> Decimal {
+	let mut result = Decimal::zero();
+	for coefficient in coefficients {
+		result = &(&result * x) + &Decimal::from_str(coefficient).unwrap();
+	}
+	result
+}
+
+fn standard_normal_inv_cdf(p: &Decimal) -> Decimal {
+	let one: Decimal = 1.into();
+	let two: Decimal = 2.into();
+	let half = &one / &two;
+	let tail = Decimal::from_str(INV_CDF_TAIL).unwrap();
+
+	// Initial approximation, using a different rational function in the tails
+	let mut x = if p < &tail || p > &(&one - &tail) {
+		let q = if p < &tail { p.clone() } else { &one - p };
+		let q = (&-&two * &q.ln()).sqrt();
+		let x = &polynomial(&INV_CDF_C, &q) / &(&(&polynomial(&INV_CDF_D, &q) * &q) + &one);
+		if p < &tail {
+			x
+		} else {
+			-&x
+		}
+	} else {
+		let q = p - &half;
+		let r = &q * &q;
+		&(&polynomial(&INV_CDF_A, &r) * &q) / &(&(&polynomial(&INV_CDF_B, &r) * &r) + &one)
+	};
+
+	// Refine the approximation to full precision using Halley's method
+	let sqrt_2pi = (&two * &Decimal::pi()).sqrt();
+	for _ in 0..INV_CDF_REFINE_ITERATIONS {
+		let error = &standard_normal_cdf(&x) - p;
+		let u = &(&error * &sqrt_2pi) * &(&(&x * &x) / &two).exp();
+		x = &x - &(&u / &(&one + &(&(&x * &u) / &two)));
+	}
+	x
+}
+
+/// Computes the cumulative distribution function of the normal distribution with
+/// the given mean and standard deviation.
+pub fn normal_cdf(x: &Number, mean: &Number, stddev: &Number) -> Result<Number> {
+	if stddev.is_negative() || stddev.is_zero() {
+		return Err(Error::ValueOutOfRange);
+	}
+	let z = &(x - mean) / stddev;
+	Ok(Number::Decimal(standard_normal_cdf(&z.to_decimal())))
+}
+
+/// Computes the inverse of the cumulative distribution function of the normal
+/// distribution with the given mean and standard deviation. The probability must be
+/// strictly between zero and one.
+pub fn normal_inv_cdf(p: &Number, mean: &Number, stddev: &Number) -> Result<Number> {
+	if stddev.is_negative() || stddev.is_zero() {
+		return Err(Error::ValueOutOfRange);
+	}
+	let p = p.to_decimal();
+	if *p <= Decimal::zero() || *p >= 1.into() {
+		return Err(Error::ValueOutOfRange);
+	}
+	let z = Number::Decimal(standard_normal_inv_cdf(&p));
+	Ok(&(&z * stddev) + mean)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points(values: &[(i32, i32)]) -> Vec<(Number, Number)> {
+		values
+			.iter()
+			.map(|(x, y)| (x.to_number(), y.to_number()))
+			.collect()
+	}
+
+	#[test]
+	fn exact_linear_fit() {
+		let result = linear_regression(&points(&[(1, 2), (2, 4), (3, 6)])).unwrap();
+		assert!(result.slope == 2.to_number());
+		assert!(result.intercept.is_zero());
+		assert_eq!(result.correlation.unwrap().to_f64(), 1.0);
+	}
+
+	#[test]
+	fn horizontal_line_has_no_correlation() {
+		let result = linear_regression(&points(&[(1, 5), (2, 5), (3, 5)])).unwrap();
+		assert!(result.slope.is_zero());
+		assert!(result.intercept == 5.to_number());
+		assert!(result.correlation.is_none());
+	}
+
+	#[test]
+	fn vertical_line_has_no_slope() {
+		assert_eq!(
+			linear_regression(&points(&[(2, 1), (2, 3)])).err(),
+			Some(Error::ValueOutOfRange)
+		);
+	}
+
+	#[test]
+	fn normal_distribution() {
+		let mean = 0.to_number();
+		let stddev = 1.to_number();
+		let cdf = |x: &str| {
+			normal_cdf(&Number::from_str(x).unwrap(), &mean, &stddev)
+				.unwrap()
+				.to_f64()
+		};
+		assert!((cdf("0") - 0.5).abs() < 1e-15);
+		assert!((cdf("1.96") - 0.9750021048517795).abs() < 1e-12);
+
+		let quantile = normal_inv_cdf(&Number::from_str("0.5").unwrap(), &mean, &stddev).unwrap();
+		assert!(quantile.to_f64().abs() < 1e-15);
+		let quantile = normal_inv_cdf(&Number::from_str("0.975").unwrap(), &mean, &stddev).unwrap();
+		assert!((quantile.to_f64() - 1.959963984540054).abs() < 1e-12);
+	}
+}
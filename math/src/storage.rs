@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use crate::undo::prune_undo_buffer;
+use crate::undo::{clear_undo_buffer, prune_undo_buffer};
 use core::alloc::Layout;
 use core::marker::PhantomData;
 use core::ptr::NonNull;
@@ -94,6 +94,11 @@ struct SerializeSizer {
 	size: usize,
 }
 
+/// Serialization output that collects the serialized data into a byte vector.
+pub struct SerializeBytes {
+	bytes: Vec<u8>,
+}
+
 pub struct DeserializeInput<'a> {
 	buffer: &'a [u8],
 	offset: usize,
@@ -110,6 +115,14 @@ pub struct StorageRefArray<T: StorageObject> {
 	_type: PhantomData<T>,
 }
 
+/// Snapshot of storage usage, as returned by `stats`.
+pub struct StorageStats {
+	pub used_bytes: usize,
+	pub reclaimable_bytes: usize,
+	pub free_bytes: usize,
+	pub objects: usize,
+}
+
 struct StorageObjectHeader {
 	size: OffsetType,
 	refs: ReferenceType,
@@ -122,6 +135,10 @@ struct NormalStorageRefSerializer {
 
 struct DropStorageRefSerializer;
 
+/// Serializes referenced objects inline instead of by offset. This produces a self
+/// contained byte stream that does not depend on the contents of the storage heap.
+struct InlineStorageRefSerializer;
+
 struct ReclaimableStorageRefSerializer {
 	cleanup: Vec<Box<dyn FnOnce()>>,
 }
@@ -181,6 +198,16 @@ impl SerializeSizer {
 	}
 }
 
+impl SerializeBytes {
+	pub fn new() -> Self {
+		SerializeBytes { bytes: Vec::new() }
+	}
+
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.bytes
+	}
+}
+
 impl<'a> SerializeOutput for SerializeBuffer<'a> {
 	fn size_only(&self) -> bool {
 		false
@@ -208,8 +235,19 @@ impl SerializeOutput for SerializeSizer {
 	}
 }
 
+impl SerializeOutput for SerializeBytes {
+	fn size_only(&self) -> bool {
+		false
+	}
+
+	fn write(&mut self, data: &[u8]) -> Result<()> {
+		self.bytes.extend_from_slice(data);
+		Ok(())
+	}
+}
+
 impl<'a> DeserializeInput<'a> {
-	fn new(slice: &'a [u8]) -> Self {
+	pub fn new(slice: &'a [u8]) -> Self {
 		DeserializeInput {
 			buffer: slice,
 			offset: 0,
@@ -324,13 +362,9 @@ impl<T: StorageObject> Drop for StorageRef<T> {
 					(*header_ptr).size as usize + core::mem::size_of::<StorageObjectHeader>();
 				let prev_used_bytes = used_bytes();
 
-				HEAP.lock().deallocate(
+				dealloc_obj(
 					core::ptr::NonNull::new_unchecked(header_ptr as *mut u8),
-					Layout::from_size_align(
-						alloc_size,
-						core::mem::align_of::<StorageObjectHeader>(),
-					)
-					.unwrap(),
+					alloc_size,
 				);
 
 				if reclaimable {
@@ -437,16 +471,7 @@ impl<T: StorageObject> StorageRefArray<T> {
 					_type: PhantomData,
 				});
 			}
-			unsafe {
-				HEAP.lock().deallocate(
-					buffer,
-					Layout::from_size_align(
-						alloc_size,
-						core::mem::align_of::<StorageObjectHeader>(),
-					)
-					.unwrap(),
-				);
-			}
+			dealloc_obj(buffer, alloc_size);
 			return Err(Error::OutOfMemory);
 		}
 
@@ -508,6 +533,28 @@ impl<T: StorageObject> StorageRefArray<T> {
 		Ok(())
 	}
 
+	/// Creates an array from a list of value references.
+	fn from_refs(values: &[StorageRef<T>]) -> Result<Self> {
+		// Create a memory buffer large enough to hold the offsets of all values in the array
+		let size = core::mem::size_of::<OffsetType>() * values.len();
+		let (buffer, _alloc_size, _used_size) = alloc_obj(size, false)?;
+
+		// Populate the array entries, adding a reference to each value
+		let array_buffer = (buffer.as_ptr() as usize + core::mem::size_of::<StorageObjectHeader>())
+			as *mut OffsetType;
+		let array_slice = unsafe { core::slice::from_raw_parts_mut(array_buffer, values.len()) };
+		for i in 0..values.len() {
+			array_slice[i] = values[i].offset;
+			values[i].add_ref();
+		}
+
+		Ok(StorageRefArray {
+			offset: (buffer.as_ptr() as usize - HEAP.lock().bottom()) as OffsetType,
+			len: values.len(),
+			_type: PhantomData,
+		})
+	}
+
 	pub fn with_size(&self, new_len: usize, default_value: StorageRef<T>) -> Result<Self> {
 		self.duplicate(new_len, Some(default_value))
 	}
@@ -551,13 +598,9 @@ impl<T: StorageObject> Drop for StorageRefArray<T> {
 					(*header_ptr).size as usize + core::mem::size_of::<StorageObjectHeader>();
 				let prev_used_bytes = used_bytes();
 
-				HEAP.lock().deallocate(
+				dealloc_obj(
 					core::ptr::NonNull::new_unchecked(header_ptr as *mut u8),
-					Layout::from_size_align(
-						alloc_size,
-						core::mem::align_of::<StorageObjectHeader>(),
-					)
-					.unwrap(),
+					alloc_size,
 				);
 
 				if reclaimable {
@@ -736,6 +779,50 @@ impl StorageRefSerializer for DropStorageRefSerializer {
 	}
 }
 
+impl StorageRefSerializer for InlineStorageRefSerializer {
+	fn serialize<T: StorageObject, Out: SerializeOutput>(
+		&mut self,
+		value: &StorageRef<T>,
+		output: &mut Out,
+	) -> Result<()> {
+		// Serialize the contents of the referenced object in place
+		value.get()?.serialize(output, self)
+	}
+
+	fn serialize_array<T: StorageObject, Out: SerializeOutput>(
+		&mut self,
+		value: &StorageRefArray<T>,
+		output: &mut Out,
+	) -> Result<()> {
+		// Serialize the length followed by the contents of each element
+		output.write_u32(value.len as u32)?;
+		for i in 0..value.len {
+			self.serialize(&value.get(i)?, output)?;
+		}
+		Ok(())
+	}
+
+	unsafe fn deserialize<T: StorageObject>(
+		&self,
+		input: &mut DeserializeInput,
+	) -> Result<StorageRef<T>> {
+		// Read the object contents and place them into storage
+		store(T::deserialize(input, self)?)
+	}
+
+	unsafe fn deserialize_array<T: StorageObject>(
+		&self,
+		input: &mut DeserializeInput,
+	) -> Result<StorageRefArray<T>> {
+		let len = input.read_u32()? as usize;
+		let mut values = Vec::new();
+		for _ in 0..len {
+			values.push(self.deserialize(input)?);
+		}
+		StorageRefArray::from_refs(&values)
+	}
+}
+
 impl StorageRefSerializer for ReclaimableStorageRefSerializer {
 	fn serialize<T: StorageObject, Out: SerializeOutput>(
 		&mut self,
@@ -862,6 +949,7 @@ lazy_static! {
 		Mutex::new(Heap::new(backing_mem as usize, STORAGE_SIZE))
 	};
 	static ref RECLAIMABLE: Mutex<usize> = Mutex::new(0);
+	static ref OBJECTS: Mutex<usize> = Mutex::new(0);
 }
 
 fn alloc_result(layout: Layout) -> Result<(NonNull<u8>, usize)> {
@@ -898,10 +986,22 @@ fn alloc_obj(size: usize, reclaimable: bool) -> Result<(NonNull<u8>, usize, usiz
 			reclaimable,
 		};
 	}
+	*OBJECTS.lock() += 1;
 
 	Ok((buffer, alloc_size, used_size))
 }
 
+fn dealloc_obj(buffer: NonNull<u8>, alloc_size: usize) {
+	unsafe {
+		HEAP.lock().deallocate(
+			buffer,
+			Layout::from_size_align(alloc_size, core::mem::align_of::<StorageObjectHeader>())
+				.unwrap(),
+		);
+	}
+	*OBJECTS.lock() -= 1;
+}
+
 fn obj_add_ref(offset: OffsetType) {
 	let header_ptr = (HEAP.lock().bottom() + offset as usize) as *mut StorageObjectHeader;
 	unsafe {
@@ -961,13 +1061,7 @@ fn store_obj<T: StorageObject>(value: T, reclaimable: bool) -> Result<StorageRef
 		result
 	} {
 		// Serialization failed, deallocate and return error
-		unsafe {
-			HEAP.lock().deallocate(
-				buffer,
-				Layout::from_size_align(alloc_size, core::mem::align_of::<StorageObjectHeader>())
-					.unwrap(),
-			);
-		}
+		dealloc_obj(buffer, alloc_size);
 		return Err(error);
 	}
 
@@ -991,6 +1085,21 @@ pub fn store_reclaimable<T: StorageObject>(value: T) -> Result<StorageRef<T>> {
 	store_obj(value, true)
 }
 
+/// Serializes an object along with all objects that it references. The resulting data
+/// is self contained and can be restored with `deserialize_inline`.
+pub fn serialize_inline<T: StorageObject, Out: SerializeOutput>(
+	value: &T,
+	output: &mut Out,
+) -> Result<()> {
+	value.serialize(output, &mut InlineStorageRefSerializer)
+}
+
+/// Deserializes an object that was serialized with `serialize_inline`. Referenced objects
+/// are placed into storage as they are read.
+pub fn deserialize_inline<T: StorageObject>(input: &mut DeserializeInput) -> Result<T> {
+	unsafe { T::deserialize(input, &InlineStorageRefSerializer) }
+}
+
 pub fn used_bytes() -> usize {
 	HEAP.lock().used()
 }
@@ -1006,3 +1115,47 @@ pub fn free_bytes() -> usize {
 pub fn available_bytes() -> usize {
 	free_bytes() + reclaimable_bytes()
 }
+
+/// Gets a snapshot of storage usage, including the number of objects in storage.
+pub fn stats() -> StorageStats {
+	StorageStats {
+		used_bytes: used_bytes(),
+		reclaimable_bytes: reclaimable_bytes(),
+		free_bytes: free_bytes(),
+		objects: *OBJECTS.lock(),
+	}
+}
+
+/// Releases all reclaimable memory, such as the undo history. Objects are referenced by
+/// their offset in storage and can't be moved, so this is the memory that can be
+/// recovered. Returns the number of bytes freed.
+pub fn compact() -> usize {
+	let prev_free_bytes = free_bytes();
+	clear_undo_buffer();
+	free_bytes() - prev_free_bytes
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::stack::Stack;
+	use crate::value::Value;
+
+	#[test]
+	fn compact_frees_values_kept_for_undo() {
+		let _lock = crate::undo::TEST_LOCK.lock();
+
+		let mut stack = Stack::new_with_undo();
+		for i in 0..500 {
+			stack.push(Value::Number(i.into())).unwrap();
+		}
+		let used = available_bytes();
+
+		// Once the stack is cleared and dropped, the values are only kept by the undo
+		// buffer, and compacting frees them
+		stack.clear();
+		drop(stack);
+		assert!(compact() > 0);
+		assert!(available_bytes() > used);
+	}
+}
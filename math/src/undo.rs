@@ -1,3 +1,4 @@
+use crate::context::Location;
 use crate::error::{Error, Result};
 use crate::storage::{
 	store_reclaimable, DeserializeInput, SerializeOutput, StorageObject, StorageRef,
@@ -20,6 +21,13 @@ pub enum UndoAction {
 	RotateDown,
 	SetStackEntry(usize, ValueRef),
 	ReplaceTopWithMultiple(usize, ValueRef),
+	ClearVariables(Vec<(Location, ValueRef)>),
+	RotateUp,
+	RestoreEntries(usize),
+	RestoreVariables(Vec<Location>),
+	PopMultiple(Vec<ValueRef>),
+	PushMultiple(usize),
+	ReplaceMultiple(usize, Vec<ValueRef>),
 }
 
 type UndoActionRef = StorageRef<UndoAction>;
@@ -36,6 +44,13 @@ const UNDO_SERIALIZE_TYPE_CLEAR: u8 = 4;
 const UNDO_SERIALIZE_TYPE_ROTATE_DOWN: u8 = 5;
 const UNDO_SERIALIZE_TYPE_SET_STACK_ENTRY: u8 = 6;
 const UNDO_SERIALIZE_TYPE_REPLACE_TOP_WITH_MULTIPLE: u8 = 7;
+const UNDO_SERIALIZE_TYPE_CLEAR_VARIABLES: u8 = 8;
+const UNDO_SERIALIZE_TYPE_ROTATE_UP: u8 = 9;
+const UNDO_SERIALIZE_TYPE_RESTORE_ENTRIES: u8 = 10;
+const UNDO_SERIALIZE_TYPE_RESTORE_VARIABLES: u8 = 11;
+const UNDO_SERIALIZE_TYPE_POP_MULTIPLE: u8 = 12;
+const UNDO_SERIALIZE_TYPE_PUSH_MULTIPLE: u8 = 13;
+const UNDO_SERIALIZE_TYPE_REPLACE_MULTIPLE: u8 = 14;
 
 impl StorageObject for UndoAction {
 	fn serialize<Ref: StorageRefSerializer, Out: SerializeOutput>(
@@ -83,6 +98,47 @@ impl StorageObject for UndoAction {
 				output.write_u32(*count as u32)?;
 				storage_refs.serialize(value, output)?;
 			}
+			UndoAction::ClearVariables(variables) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_CLEAR_VARIABLES)?;
+				output.write_u32(variables.len() as u32)?;
+				for (location, value) in variables {
+					location.serialize(output)?;
+					storage_refs.serialize(value, output)?;
+				}
+			}
+			UndoAction::RotateUp => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_ROTATE_UP)?;
+			}
+			UndoAction::RestoreEntries(count) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_RESTORE_ENTRIES)?;
+				output.write_u32(*count as u32)?;
+			}
+			UndoAction::RestoreVariables(locations) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_RESTORE_VARIABLES)?;
+				output.write_u32(locations.len() as u32)?;
+				for location in locations {
+					location.serialize(output)?;
+				}
+			}
+			UndoAction::PopMultiple(values) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_POP_MULTIPLE)?;
+				output.write_u32(values.len() as u32)?;
+				for value in values {
+					storage_refs.serialize(value, output)?;
+				}
+			}
+			UndoAction::PushMultiple(count) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_PUSH_MULTIPLE)?;
+				output.write_u32(*count as u32)?;
+			}
+			UndoAction::ReplaceMultiple(count, values) => {
+				output.write_u8(UNDO_SERIALIZE_TYPE_REPLACE_MULTIPLE)?;
+				output.write_u32(*count as u32)?;
+				output.write_u32(values.len() as u32)?;
+				for value in values {
+					storage_refs.serialize(value, output)?;
+				}
+			}
 		}
 		Ok(())
 	}
@@ -128,6 +184,51 @@ impl StorageObject for UndoAction {
 				let value = storage_refs.deserialize(input)?;
 				Ok(UndoAction::ReplaceTopWithMultiple(count, value))
 			}
+			UNDO_SERIALIZE_TYPE_CLEAR_VARIABLES => {
+				let count = input.read_u32()? as usize;
+				let mut variables = Vec::new();
+				variables.reserve(count);
+				for _ in 0..count {
+					let location = Location::deserialize(input)?;
+					variables.push((location, storage_refs.deserialize(input)?));
+				}
+				Ok(UndoAction::ClearVariables(variables))
+			}
+			UNDO_SERIALIZE_TYPE_ROTATE_UP => Ok(UndoAction::RotateUp),
+			UNDO_SERIALIZE_TYPE_RESTORE_ENTRIES => {
+				Ok(UndoAction::RestoreEntries(input.read_u32()? as usize))
+			}
+			UNDO_SERIALIZE_TYPE_RESTORE_VARIABLES => {
+				let count = input.read_u32()? as usize;
+				let mut locations = Vec::new();
+				locations.reserve(count);
+				for _ in 0..count {
+					locations.push(Location::deserialize(input)?);
+				}
+				Ok(UndoAction::RestoreVariables(locations))
+			}
+			UNDO_SERIALIZE_TYPE_POP_MULTIPLE => {
+				let count = input.read_u32()? as usize;
+				let mut values = Vec::new();
+				values.reserve(count);
+				for _ in 0..count {
+					values.push(storage_refs.deserialize(input)?);
+				}
+				Ok(UndoAction::PopMultiple(values))
+			}
+			UNDO_SERIALIZE_TYPE_PUSH_MULTIPLE => {
+				Ok(UndoAction::PushMultiple(input.read_u32()? as usize))
+			}
+			UNDO_SERIALIZE_TYPE_REPLACE_MULTIPLE => {
+				let count = input.read_u32()? as usize;
+				let value_count = input.read_u32()? as usize;
+				let mut values = Vec::new();
+				values.reserve(value_count);
+				for _ in 0..value_count {
+					values.push(storage_refs.deserialize(input)?);
+				}
+				Ok(UndoAction::ReplaceMultiple(count, values))
+			}
 			_ => Err(Error::CorruptData),
 		}
 	}
@@ -172,17 +273,46 @@ impl UndoBuffer {
 
 lazy_static! {
 	static ref UNDO_BUFFER: Mutex<UndoBuffer> = Mutex::new(UndoBuffer::new());
+	static ref REDO_BUFFER: Mutex<UndoBuffer> = Mutex::new(UndoBuffer::new());
 }
 
+// Tests that use the global undo buffers hold this lock so that they do not observe
+// each other's actions
+#[cfg(test)]
+pub(crate) static TEST_LOCK: Mutex<()> = Mutex::new(());
+
 pub fn push_undo_action(action: UndoAction) {
+	// Any new action invalidates the actions that were undone
+	REDO_BUFFER.lock().clear();
+	push_undo_action_preserving_redo(action);
+}
+
+pub fn push_undo_action_preserving_redo(action: UndoAction) {
 	if let Ok(action) = store_reclaimable(action) {
 		let _ = UNDO_BUFFER.lock().push(action);
 	}
 }
 
+pub fn push_redo_action(action: UndoAction) {
+	if let Ok(action) = store_reclaimable(action) {
+		let _ = REDO_BUFFER.lock().push(action);
+	}
+}
+
 pub fn pop_undo_action() -> Result<UndoAction> {
 	let entry = UNDO_BUFFER.lock().pop()?;
+	copy_action_values(entry)
+}
+
+pub fn pop_redo_action() -> Result<UndoAction> {
+	let entry = REDO_BUFFER
+		.lock()
+		.pop()
+		.map_err(|_| Error::RedoBufferEmpty)?;
+	copy_action_values(entry)
+}
 
+fn copy_action_values(entry: UndoAction) -> Result<UndoAction> {
 	// When popping entries off the stack, store any values back onto the non-reclaimable
 	// storage so that it gets accounted for properly.
 	Ok(match entry {
@@ -205,14 +335,33 @@ pub fn pop_undo_action() -> Result<UndoAction> {
 		UndoAction::ReplaceTopWithMultiple(count, value) => {
 			UndoAction::ReplaceTopWithMultiple(count, Value::deep_copy_value(value)?)
 		}
+		UndoAction::ClearVariables(mut variables) => {
+			for (_, value) in &mut variables {
+				*value = Value::deep_copy_value(value.clone())?;
+			}
+			UndoAction::ClearVariables(variables)
+		}
+		UndoAction::PopMultiple(mut values) => {
+			for value in &mut values {
+				*value = Value::deep_copy_value(value.clone())?;
+			}
+			UndoAction::PopMultiple(values)
+		}
+		UndoAction::ReplaceMultiple(count, mut values) => {
+			for value in &mut values {
+				*value = Value::deep_copy_value(value.clone())?;
+			}
+			UndoAction::ReplaceMultiple(count, values)
+		}
 		entry => entry,
 	})
 }
 
 pub fn prune_undo_buffer() -> bool {
-	UNDO_BUFFER.lock().prune()
+	REDO_BUFFER.lock().prune() || UNDO_BUFFER.lock().prune()
 }
 
 pub fn clear_undo_buffer() {
-	UNDO_BUFFER.lock().clear()
+	UNDO_BUFFER.lock().clear();
+	REDO_BUFFER.lock().clear();
 }
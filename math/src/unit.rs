@@ -895,6 +895,39 @@ pub enum UnitType {
 }
 
 impl UnitType {
+	pub fn to_u8(&self) -> u8 {
+		match self {
+			UnitType::Angle => 0,
+			UnitType::Area => 1,
+			UnitType::Distance => 2,
+			UnitType::Energy => 3,
+			UnitType::Force => 4,
+			UnitType::Mass => 5,
+			UnitType::Power => 6,
+			UnitType::Pressure => 7,
+			UnitType::Temperature => 8,
+			UnitType::Time => 9,
+			UnitType::Volume => 10,
+		}
+	}
+
+	pub fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(UnitType::Angle),
+			1 => Some(UnitType::Area),
+			2 => Some(UnitType::Distance),
+			3 => Some(UnitType::Energy),
+			4 => Some(UnitType::Force),
+			5 => Some(UnitType::Mass),
+			6 => Some(UnitType::Power),
+			7 => Some(UnitType::Pressure),
+			8 => Some(UnitType::Temperature),
+			9 => Some(UnitType::Time),
+			10 => Some(UnitType::Volume),
+			_ => None,
+		}
+	}
+
 	pub fn to_str(&self) -> &str {
 		match self {
 			UnitType::Angle => "Angle",
@@ -937,9 +970,9 @@ impl MultiplierUnitConversion for AngleUnit {
 	fn multiplier_to_standard(&self) -> Number {
 		match self {
 			AngleUnit::Degrees => 1.to_number(),
-			AngleUnit::Radians => {
-				Decimal::from_str("57.29577951308232087679815481410517").to_number()
-			}
+			AngleUnit::Radians => Decimal::from_str("57.29577951308232087679815481410517")
+				.unwrap()
+				.to_number(),
 			AngleUnit::Gradians => 9.to_number() / 10.to_number(),
 		}
 	}
@@ -1138,6 +1171,108 @@ impl Unit {
 			Unit::Volume(_) => UnitType::Volume,
 		}
 	}
+
+	/// Gets the unit in the metric system that is normally used in place of this unit.
+	/// Units that are already metric, or have no metric counterpart, are unchanged.
+	pub fn metric_unit(&self) -> Unit {
+		match self {
+			Unit::Area(AreaUnit::Acres) => AreaUnit::Hectares.into(),
+			Unit::Distance(DistanceUnit::Inches) => DistanceUnit::Centimeters.into(),
+			Unit::Distance(DistanceUnit::Feet) | Unit::Distance(DistanceUnit::Yards) => {
+				DistanceUnit::Meters.into()
+			}
+			Unit::Distance(DistanceUnit::Miles) | Unit::Distance(DistanceUnit::NauticalMiles) => {
+				DistanceUnit::Kilometers.into()
+			}
+			Unit::Energy(EnergyUnit::BTU) => EnergyUnit::Kilojoules.into(),
+			Unit::Energy(EnergyUnit::FootPounds) | Unit::Energy(EnergyUnit::FootPoundals) => {
+				EnergyUnit::Joules.into()
+			}
+			Unit::Force(ForceUnit::PoundForce) | Unit::Force(ForceUnit::Poundal) => {
+				ForceUnit::Newton.into()
+			}
+			Unit::Force(ForceUnit::Kip) => ForceUnit::Kilonewton.into(),
+			Unit::Mass(MassUnit::Pounds) | Unit::Mass(MassUnit::Stones) => {
+				MassUnit::Kilograms.into()
+			}
+			Unit::Mass(MassUnit::Ounces) => MassUnit::Grams.into(),
+			Unit::Mass(MassUnit::Tons) | Unit::Mass(MassUnit::UKTons) => {
+				MassUnit::MetricTons.into()
+			}
+			Unit::Power(PowerUnit::MechanicalHorsepower)
+			| Unit::Power(PowerUnit::ElectricalHorsepower)
+			| Unit::Power(PowerUnit::TonsOfRefrigeration) => PowerUnit::Kilowatts.into(),
+			Unit::Pressure(PressureUnit::InchesOfMercury) => {
+				PressureUnit::MillimetersOfMercury.into()
+			}
+			Unit::Pressure(PressureUnit::InchesOfWater) => PressureUnit::MillimetersOfWater.into(),
+			Unit::Pressure(PressureUnit::PoundsPerSquareInch) => PressureUnit::Kilopascals.into(),
+			Unit::Temperature(TemperatureUnit::Fahrenheit) => TemperatureUnit::Celsius.into(),
+			Unit::Temperature(TemperatureUnit::Rankine) => TemperatureUnit::Kelvin.into(),
+			Unit::Volume(VolumeUnit::Gallons)
+			| Unit::Volume(VolumeUnit::Quarts)
+			| Unit::Volume(VolumeUnit::Pints)
+			| Unit::Volume(VolumeUnit::ImperialGallons)
+			| Unit::Volume(VolumeUnit::ImperialQuarts)
+			| Unit::Volume(VolumeUnit::ImperialPints) => VolumeUnit::Litre.into(),
+			Unit::Volume(VolumeUnit::Cups)
+			| Unit::Volume(VolumeUnit::FluidOunces)
+			| Unit::Volume(VolumeUnit::ImperialOunces)
+			| Unit::Volume(VolumeUnit::Tablespoons)
+			| Unit::Volume(VolumeUnit::Teaspoons)
+			| Unit::Volume(VolumeUnit::UKTablespoons)
+			| Unit::Volume(VolumeUnit::UKTeaspoons) => VolumeUnit::Millilitre.into(),
+			unit => *unit,
+		}
+	}
+
+	/// Gets the unit in the imperial system that is normally used in place of this unit.
+	/// Units that are already imperial, or have no imperial counterpart, are unchanged.
+	pub fn imperial_unit(&self) -> Unit {
+		match self {
+			Unit::Area(AreaUnit::Hectares) => AreaUnit::Acres.into(),
+			Unit::Distance(DistanceUnit::Nanometers)
+			| Unit::Distance(DistanceUnit::Micrometers)
+			| Unit::Distance(DistanceUnit::Millimeters)
+			| Unit::Distance(DistanceUnit::Centimeters) => DistanceUnit::Inches.into(),
+			Unit::Distance(DistanceUnit::Meters) => DistanceUnit::Feet.into(),
+			Unit::Distance(DistanceUnit::Kilometers) => DistanceUnit::Miles.into(),
+			Unit::Energy(EnergyUnit::Joules) | Unit::Energy(EnergyUnit::Millijoules) => {
+				EnergyUnit::FootPounds.into()
+			}
+			Unit::Energy(EnergyUnit::Kilojoules) | Unit::Energy(EnergyUnit::Megajoules) => {
+				EnergyUnit::BTU.into()
+			}
+			Unit::Force(ForceUnit::Newton) | Unit::Force(ForceUnit::KilogramForce) => {
+				ForceUnit::PoundForce.into()
+			}
+			Unit::Force(ForceUnit::Kilonewton) => ForceUnit::Kip.into(),
+			Unit::Force(ForceUnit::Dyne) => ForceUnit::Poundal.into(),
+			Unit::Mass(MassUnit::Grams) | Unit::Mass(MassUnit::Milligrams) => {
+				MassUnit::Ounces.into()
+			}
+			Unit::Mass(MassUnit::Kilograms) => MassUnit::Pounds.into(),
+			Unit::Mass(MassUnit::MetricTons) => MassUnit::Tons.into(),
+			Unit::Power(PowerUnit::Kilowatts) | Unit::Power(PowerUnit::MetricHorsepower) => {
+				PowerUnit::MechanicalHorsepower.into()
+			}
+			Unit::Pressure(PressureUnit::Pascals)
+			| Unit::Pressure(PressureUnit::Kilopascals)
+			| Unit::Pressure(PressureUnit::Bars)
+			| Unit::Pressure(PressureUnit::Millibars)
+			| Unit::Pressure(PressureUnit::Atmospheres)
+			| Unit::Pressure(PressureUnit::Torr) => PressureUnit::PoundsPerSquareInch.into(),
+			Unit::Pressure(PressureUnit::MillimetersOfMercury) => {
+				PressureUnit::InchesOfMercury.into()
+			}
+			Unit::Pressure(PressureUnit::MillimetersOfWater) => PressureUnit::InchesOfWater.into(),
+			Unit::Temperature(TemperatureUnit::Celsius) => TemperatureUnit::Fahrenheit.into(),
+			Unit::Temperature(TemperatureUnit::Kelvin) => TemperatureUnit::Rankine.into(),
+			Unit::Volume(VolumeUnit::Litre) => VolumeUnit::Quarts.into(),
+			Unit::Volume(VolumeUnit::Millilitre) => VolumeUnit::FluidOunces.into(),
+			unit => *unit,
+		}
+	}
 }
 
 impl From<AngleUnit> for Unit {
@@ -1577,6 +1712,103 @@ impl CompositeUnit {
 		Ok(result)
 	}
 
+	/// Reduces the units to base SI units (meters, kilograms, seconds and kelvin), scaling
+	/// the value to match. Derived units such as newtons are broken down into their base
+	/// units. Angles are expressed in radians.
+	pub fn to_base_si(&self, value: &Number) -> (Number, CompositeUnit) {
+		let mut result = value.clone();
+		let mut angle = 0;
+		let mut distance = 0;
+		let mut mass = 0;
+		let mut time = 0;
+		let mut temperature = 0;
+		for (_, (unit, power)) in self.units.iter() {
+			// Convert to the SI unit of the same type, and add the dimensions of that unit
+			let (base_unit, dimensions) = match unit {
+				Unit::Angle(_) => (Unit::Angle(AngleUnit::Radians), (1, 0, 0, 0, 0)),
+				Unit::Area(_) => (Unit::Distance(DistanceUnit::Meters), (0, 2, 0, 0, 0)),
+				Unit::Distance(_) => (Unit::Distance(DistanceUnit::Meters), (0, 1, 0, 0, 0)),
+				Unit::Energy(_) => (Unit::Energy(EnergyUnit::Joules), (0, 2, 1, -2, 0)),
+				Unit::Force(_) => (Unit::Force(ForceUnit::Newton), (0, 1, 1, -2, 0)),
+				Unit::Mass(_) => (Unit::Mass(MassUnit::Kilograms), (0, 0, 1, 0, 0)),
+				Unit::Power(_) => (Unit::Power(PowerUnit::Watts), (0, 2, 1, -3, 0)),
+				Unit::Pressure(_) => (Unit::Pressure(PressureUnit::Pascals), (0, -1, 1, -2, 0)),
+				Unit::Temperature(_) => {
+					(Unit::Temperature(TemperatureUnit::Kelvin), (0, 0, 0, 0, 1))
+				}
+				Unit::Time(_) => (Unit::Time(TimeUnit::Seconds), (0, 0, 0, 1, 0)),
+				Unit::Volume(_) => (Unit::Distance(DistanceUnit::Meters), (0, 3, 0, 0, 0)),
+			};
+			result = Self::convert_value_of_unit(&result, unit, &base_unit, *power).unwrap();
+			angle += dimensions.0 * power;
+			distance += dimensions.1 * power;
+			mass += dimensions.2 * power;
+			time += dimensions.3 * power;
+			temperature += dimensions.4 * power;
+		}
+
+		let mut units = BTreeMap::new();
+		for (unit, power) in &[
+			(Unit::Angle(AngleUnit::Radians), angle),
+			(Unit::Distance(DistanceUnit::Meters), distance),
+			(Unit::Mass(MassUnit::Kilograms), mass),
+			(Unit::Time(TimeUnit::Seconds), time),
+			(Unit::Temperature(TemperatureUnit::Kelvin), temperature),
+		] {
+			if *power != 0 {
+				units.insert(unit.unit_type(), (*unit, *power));
+			}
+		}
+		(result, CompositeUnit { units })
+	}
+
+	/// Converts a value with these units to the preferred metric unit of each type.
+	pub fn to_metric(&self, value: &Number) -> Result<(Number, CompositeUnit)> {
+		self.convert_each_unit(value, Unit::metric_unit)
+	}
+
+	/// Converts a value with these units to the preferred imperial unit of each type.
+	pub fn to_imperial(&self, value: &Number) -> Result<(Number, CompositeUnit)> {
+		self.convert_each_unit(value, Unit::imperial_unit)
+	}
+
+	fn convert_each_unit(
+		&self,
+		value: &Number,
+		target: fn(&Unit) -> Unit,
+	) -> Result<(Number, CompositeUnit)> {
+		let mut result = value.clone();
+		let mut units = self.clone();
+		for (unit, power) in units.units.values_mut() {
+			let target_unit = target(unit);
+			result = Self::convert_value_of_unit(&result, unit, &target_unit, *power)?;
+			*unit = target_unit;
+		}
+		Ok((result, units))
+	}
+
+	/// Takes the square root of a value with these units. The exponent of each unit is
+	/// halved, and units such as areas are broken down into base units if needed.
+	/// Fails if the result would have a fractional exponent.
+	pub fn sqrt(&self, value: &Number) -> Result<(Number, CompositeUnit)> {
+		let even = |units: &CompositeUnit| units.units.values().all(|(_, power)| power % 2 == 0);
+		let (value, units) = if even(self) {
+			(value.clone(), self.clone())
+		} else {
+			let (base_value, base_units) = self.to_base_si(value);
+			if !even(&base_units) {
+				return Err(Error::IncompatibleUnits);
+			}
+			(base_value, base_units)
+		};
+
+		let mut result = BTreeMap::new();
+		for (unit_type, (unit, power)) in units.units.iter() {
+			result.insert(*unit_type, (*unit, power / 2));
+		}
+		Ok((value.sqrt(), CompositeUnit { units: result }))
+	}
+
 	pub fn combine(&mut self, value: &Number, target_units: &CompositeUnit) -> Number {
 		let mut result = value.clone();
 		for (unit_type, unit) in target_units.units.iter() {
@@ -1592,7 +1824,32 @@ impl CompositeUnit {
 				self.units.insert(*unit_type, unit.clone());
 			}
 		}
-		self.collapse_composite_unit_types(result)
+		let result = self.collapse_composite_unit_types(result);
+		self.simplify_derived_units(result)
+	}
+
+	fn complexity(&self) -> i32 {
+		self.units.values().map(|(_, power)| power.abs()).sum()
+	}
+
+	fn simplify_derived_units(&mut self, value: Number) -> Number {
+		// Derived units can partially cancel with other units, for example joules per
+		// newton is a distance. Use the base SI form when it is simpler.
+		let derived = self.units.keys().any(|unit_type| match unit_type {
+			UnitType::Energy | UnitType::Force | UnitType::Power | UnitType::Pressure => true,
+			_ => false,
+		});
+		if !derived || self.units.len() < 2 {
+			return value;
+		}
+
+		let (base_value, base_units) = self.to_base_si(&value);
+		if base_units.complexity() < self.complexity() {
+			*self = base_units;
+			base_value
+		} else {
+			value
+		}
 	}
 }
 
@@ -7,9 +7,9 @@ use crate::storage::{
 	store, DeserializeInput, SerializeOutput, StorageObject, StorageRef, StorageRefSerializer,
 };
 use crate::time::{SimpleDateTimeFormat, SimpleDateTimeToString};
-use crate::unit::{AngleUnit, CompositeUnit, TimeUnit, Unit};
+use crate::unit::{AngleUnit, CompositeUnit, TimeUnit, Unit, UnitType};
 use crate::vector::Vector;
-use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use core::ops::Add;
 use num_bigint::BigInt;
 
@@ -25,6 +25,8 @@ use alloc::string::{String, ToString};
 #[cfg(not(feature = "std"))]
 use core::convert::TryFrom;
 
+const NANOSECONDS_PER_DAY: i64 = 86_400_000_000_000;
+
 #[derive(Clone)]
 pub enum Value {
 	Number(Number),
@@ -39,6 +41,18 @@ pub enum Value {
 
 pub type ValueRef = StorageRef<Value>;
 
+fn unix_epoch() -> NaiveDateTime {
+	NaiveDate::from_ymd_opt(1970, 1, 1)
+		.unwrap()
+		.and_hms_opt(0, 0, 0)
+		.unwrap()
+}
+
+fn unix_epoch_julian_day() -> Number {
+	// The Unix epoch is at midnight, halfway through Julian day 2440587
+	&4_881_175.to_number() / &2.to_number()
+}
+
 impl Value {
 	/// Deep copies a value onto the non-reclaimable heap. This is used when pulling values out
 	/// of reclaimable memory.
@@ -60,6 +74,10 @@ impl Value {
 		}
 	}
 
+	pub fn to_f64(&self) -> Result<f64> {
+		Ok(self.real_number()?.to_f64())
+	}
+
 	pub fn complex_number<'a>(&'a self) -> Result<Cow<'a, ComplexNumber>> {
 		match self {
 			Value::Number(num) => Ok(Cow::Owned(ComplexNumber::from_real(num.clone()))),
@@ -69,6 +87,23 @@ impl Value {
 		}
 	}
 
+	pub fn exact_int(&self) -> Result<BigInt> {
+		// Only accept values that are exactly an integer, without truncation
+		let num = self.real_number()?;
+		match num {
+			Number::Integer(int) => Ok(int.clone()),
+			Number::Rational(_, _) => Err(Error::NotARealNumber),
+			Number::Decimal(_) => {
+				let int = num.to_int()?.into_owned();
+				if (num - &Number::Integer(int.clone())).is_zero() {
+					Ok(int)
+				} else {
+					Err(Error::NotARealNumber)
+				}
+			}
+		}
+	}
+
 	pub fn to_int<'a>(&'a self) -> Result<Cow<'a, BigInt>> {
 		match self {
 			Value::Number(num) => num.to_int(),
@@ -92,6 +127,177 @@ impl Value {
 		}
 	}
 
+	fn map_int_value(&self, func: fn(&Number) -> Result<BigInt>) -> Result<Value> {
+		match self {
+			Value::Number(num) => Ok(Value::Number(Number::Integer(func(num)?))),
+			Value::NumberWithUnit(num, unit) => Ok(Value::NumberWithUnit(
+				Number::Integer(func(num)?),
+				unit.clone(),
+			)),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
+	pub fn floor_to_int_value(&self) -> Result<Value> {
+		self.map_int_value(Number::floor_to_int)
+	}
+
+	pub fn ceil_to_int_value(&self) -> Result<Value> {
+		self.map_int_value(Number::ceil_to_int)
+	}
+
+	pub fn round_to_int_value(&self) -> Result<Value> {
+		self.map_int_value(Number::round_to_int)
+	}
+
+	fn map_real_value(&self, func: fn(&Number) -> Number) -> Result<Value> {
+		match self {
+			Value::Number(num) => Ok(Value::Number(func(num))),
+			Value::NumberWithUnit(num, unit) => Ok(Value::NumberWithUnit(func(num), unit.clone())),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
+	pub fn integer_part_value(&self) -> Result<Value> {
+		self.map_real_value(Number::integer_part)
+	}
+
+	pub fn fractional_part_value(&self) -> Result<Value> {
+		self.map_real_value(Number::fractional_part)
+	}
+
+	pub fn simplified_value(&self) -> Result<Value> {
+		self.map_real_value(Number::simplified)
+	}
+
+	/// Rounds a real decimal value so that its least significant digit has the given
+	/// exponent. Other values are returned unchanged.
+	pub fn with_decimal_exponent(&self, exponent: isize) -> Value {
+		match self {
+			Value::Number(num) => Value::Number(num.with_decimal_exponent(exponent)),
+			Value::NumberWithUnit(num, unit) => {
+				Value::NumberWithUnit(num.with_decimal_exponent(exponent), unit.clone())
+			}
+			_ => self.clone(),
+		}
+	}
+
+	/// Rounds a real decimal value to the given number of significant digits. Other
+	/// values are returned unchanged.
+	pub fn with_significant_digits(&self, digits: usize) -> Value {
+		match self {
+			Value::Number(num) => Value::Number(num.with_significant_digits(digits)),
+			Value::NumberWithUnit(num, unit) => {
+				Value::NumberWithUnit(num.with_significant_digits(digits), unit.clone())
+			}
+			_ => self.clone(),
+		}
+	}
+
+	pub fn date_part(&self) -> Result<NaiveDate> {
+		match self {
+			Value::DateTime(dt) => Ok(dt.date()),
+			Value::Date(date) => Ok(*date),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn add_months(&self, months: i64) -> Result<Value> {
+		// Day of month is clamped to the end of the target month if needed
+		let months_abs = Months::new(u32::try_from(months.unsigned_abs())?);
+		match self {
+			Value::DateTime(dt) => Ok(Value::DateTime(
+				if months < 0 {
+					dt.checked_sub_months(months_abs)
+				} else {
+					dt.checked_add_months(months_abs)
+				}
+				.ok_or(Error::ValueOutOfRange)?,
+			)),
+			Value::Date(date) => Ok(Value::Date(
+				if months < 0 {
+					date.checked_sub_months(months_abs)
+				} else {
+					date.checked_add_months(months_abs)
+				}
+				.ok_or(Error::ValueOutOfRange)?,
+			)),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn add_years(&self, years: i64) -> Result<Value> {
+		self.add_months(years.checked_mul(12).ok_or(Error::ValueOutOfRange)?)
+	}
+
+	/// Gets the date and time of a date or date and time value. Dates are treated as
+	/// midnight at the start of the day.
+	pub fn datetime_part(&self) -> Result<NaiveDateTime> {
+		match self {
+			Value::DateTime(dt) => Ok(*dt),
+			Value::Date(date) => date.and_hms_opt(0, 0, 0).ok_or(Error::InvalidDate),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	/// Gets the number of seconds in a time quantity. Returns `None` for values that
+	/// are not a plain amount of time, such as rates.
+	pub fn duration_seconds(&self) -> Option<Number> {
+		if let Value::NumberWithUnit(num, units) = self {
+			if units.units.len() == 1 {
+				if let Some((_, 1)) = units.units.get(&UnitType::Time) {
+					let seconds = CompositeUnit::single_unit(TimeUnit::Seconds.into());
+					return units.coerce_to_other(num, &seconds).ok();
+				}
+			}
+		}
+		None
+	}
+
+	/// Gets the number of seconds since 1970-01-01 00:00:00. Fractional seconds are
+	/// discarded.
+	pub fn to_unix_time(&self) -> Result<Number> {
+		Ok(self.datetime_part()?.and_utc().timestamp().to_number())
+	}
+
+	pub fn from_unix_time(seconds: &Number) -> Result<Value> {
+		let seconds = i64::try_from(&*seconds.to_int()?)?;
+		let duration = Duration::try_seconds(seconds).ok_or(Error::ValueOutOfRange)?;
+		Ok(Value::DateTime(
+			unix_epoch()
+				.checked_add_signed(duration)
+				.ok_or(Error::ValueOutOfRange)?,
+		))
+	}
+
+	/// Gets the Julian day of a date and time, which counts days since noon on
+	/// January 1, 4713 BC in the proleptic Julian calendar. The result is exact.
+	pub fn to_julian_day(&self) -> Result<Number> {
+		// Count whole days separately so that dates far from the epoch don't overflow
+		let duration = self.datetime_part()?.signed_duration_since(unix_epoch());
+		let days = duration.num_days();
+		let nanos = (duration - Duration::days(days))
+			.num_nanoseconds()
+			.ok_or(Error::ValueOutOfRange)?;
+		let day_fraction = &nanos.to_number() / &NANOSECONDS_PER_DAY.to_number();
+		Ok(&(&days.to_number() + &day_fraction) + &unix_epoch_julian_day())
+	}
+
+	pub fn from_julian_day(day: &Number) -> Result<Value> {
+		let offset = day - &unix_epoch_julian_day();
+		let days = offset.floor_to_int()?;
+		let day_fraction = &offset - &Number::Integer(days.clone());
+		let nanos = (&day_fraction * &NANOSECONDS_PER_DAY.to_number()).round_to_int()?;
+		let days = Duration::try_days(i64::try_from(&days)?).ok_or(Error::ValueOutOfRange)?;
+		let nanos = Duration::nanoseconds(i64::try_from(&nanos)?);
+		Ok(Value::DateTime(
+			unix_epoch()
+				.checked_add_signed(days)
+				.and_then(|dt| dt.checked_add_signed(nanos))
+				.ok_or(Error::ValueOutOfRange)?,
+		))
+	}
+
 	pub fn to_string(&self) -> String {
 		match self {
 			Value::Number(num) => num.to_string(),
@@ -130,6 +336,36 @@ impl Value {
 		}
 	}
 
+	/// Checks that a value does not contain any floating point numbers. Values that are
+	/// not numeric are always considered exact.
+	pub fn is_exact(&self) -> Result<bool> {
+		match self {
+			Value::Number(num) | Value::NumberWithUnit(num, _) => Ok(num.is_exact()),
+			Value::Complex(value) => {
+				Ok(value.real_part().is_exact() && value.imaginary_part().is_exact())
+			}
+			Value::Vector(vector) => {
+				for i in 0..vector.len() {
+					if !vector.get(i)?.is_exact()? {
+						return Ok(false);
+					}
+				}
+				Ok(true)
+			}
+			Value::Matrix(matrix) => {
+				for row in 0..matrix.rows() {
+					for col in 0..matrix.cols() {
+						if !matrix.get(row, col)?.is_exact()? {
+							return Ok(false);
+						}
+					}
+				}
+				Ok(true)
+			}
+			_ => Ok(true),
+		}
+	}
+
 	pub fn is_vector_or_matrix(&self) -> bool {
 		match self {
 			Value::Vector(_) | Value::Matrix(_) => true,
@@ -137,6 +373,30 @@ impl Value {
 		}
 	}
 
+	/// Applies a function to each element of a vector or matrix. Other values are
+	/// passed to the function directly.
+	pub fn map_elements(&self, func: &dyn Fn(&Value) -> Result<Value>) -> Result<Value> {
+		match self {
+			Value::Vector(vector) => {
+				let mut result = vector.clone();
+				for i in 0..vector.len() {
+					result.set(i, func(&vector.get(i)?)?)?;
+				}
+				Ok(Value::Vector(result))
+			}
+			Value::Matrix(matrix) => {
+				let mut result = matrix.clone();
+				for row in 0..matrix.rows() {
+					for col in 0..matrix.cols() {
+						result.set(row, col, func(&matrix.get(row, col)?)?)?;
+					}
+				}
+				Ok(Value::Matrix(result))
+			}
+			_ => func(self),
+		}
+	}
+
 	pub fn pow(&self, power: &Value) -> Result<Value> {
 		if let Value::Complex(value) = self {
 			Self::check_complex(value.pow(&*power.complex_number()?))
@@ -150,6 +410,17 @@ impl Value {
 	pub fn sqrt(&self) -> Result<Value> {
 		if let Value::Complex(value) = self {
 			Self::check_complex(value.sqrt())
+		} else if let Value::NumberWithUnit(value, units) = self {
+			// Complex numbers can't have units, so there is no root of a negative value
+			if value.is_negative() {
+				return Err(Error::ValueOutOfRange);
+			}
+			let (value, units) = units.sqrt(value)?;
+			if units.unitless() {
+				Ok(Value::Number(value))
+			} else {
+				Ok(Value::NumberWithUnit(value, units))
+			}
 		} else {
 			let value = self.real_number()?;
 			if value.is_negative() {
@@ -160,6 +431,24 @@ impl Value {
 		}
 	}
 
+	pub fn abs(&self) -> Result<Value> {
+		match self {
+			Value::Number(num) => Ok(Value::Number(num.abs())),
+			Value::NumberWithUnit(num, unit) => Ok(Value::NumberWithUnit(num.abs(), unit.clone())),
+			Value::Complex(value) => Ok(Value::Number(value.magnitude())),
+			_ => Err(Error::DataTypeMismatch),
+		}
+	}
+
+	pub fn signum(&self) -> Result<Value> {
+		if let Value::Complex(value) = self {
+			// Sign of a complex number is the unit vector in the same direction
+			Self::check_complex(value / &ComplexNumber::from_real(value.magnitude()))
+		} else {
+			Ok(Value::Number(self.real_number()?.signum()))
+		}
+	}
+
 	pub fn log(&self) -> Result<Value> {
 		if let Value::Complex(value) = self {
 			Self::check_complex(value.log())
@@ -196,57 +485,48 @@ impl Value {
 		}
 	}
 
-	pub fn sin(&self, angle_mode: AngleUnit) -> Result<Value> {
+	fn trig_angle_in_radians(&self, angle_mode: AngleUnit) -> Result<Number> {
 		match self {
 			Value::NumberWithUnit(num, unit) => {
+				// Reduce angles in their own unit so that large angles keep their precision
+				if unit.units.len() == 1 {
+					if let Some((Unit::Angle(angle_unit), 1)) = unit.units.get(&UnitType::Angle) {
+						return Ok(num.reduced_angle_to_radians(*angle_unit).into_owned());
+					}
+				}
 				match unit
 					.clone()
 					.convert_single_unit(num, AngleUnit::Radians.into())
 				{
-					Ok(value) => Ok(Value::Number(value.sin())),
-					_ => Ok(Value::Number(num.angle_to_radians(angle_mode).sin())),
+					Ok(value) => Ok(value),
+					_ => Ok(num.reduced_angle_to_radians(angle_mode).into_owned()),
 				}
 			}
+			_ => Ok(self
+				.real_number()?
+				.reduced_angle_to_radians(angle_mode)
+				.into_owned()),
+		}
+	}
+
+	pub fn sin(&self, angle_mode: AngleUnit) -> Result<Value> {
+		match self {
 			Value::Complex(value) => Self::check_complex(value.sin()),
-			_ => Ok(Value::Number(
-				self.real_number()?.angle_to_radians(angle_mode).sin(),
-			)),
+			_ => Ok(Value::Number(self.trig_angle_in_radians(angle_mode)?.sin())),
 		}
 	}
 
 	pub fn cos(&self, angle_mode: AngleUnit) -> Result<Value> {
 		match self {
-			Value::NumberWithUnit(num, unit) => {
-				match unit
-					.clone()
-					.convert_single_unit(num, AngleUnit::Radians.into())
-				{
-					Ok(value) => Ok(Value::Number(value.cos())),
-					_ => Ok(Value::Number(num.angle_to_radians(angle_mode).cos())),
-				}
-			}
 			Value::Complex(value) => Self::check_complex(value.cos()),
-			_ => Ok(Value::Number(
-				self.real_number()?.angle_to_radians(angle_mode).cos(),
-			)),
+			_ => Ok(Value::Number(self.trig_angle_in_radians(angle_mode)?.cos())),
 		}
 	}
 
 	pub fn tan(&self, angle_mode: AngleUnit) -> Result<Value> {
 		match self {
-			Value::NumberWithUnit(num, unit) => {
-				match unit
-					.clone()
-					.convert_single_unit(num, AngleUnit::Radians.into())
-				{
-					Ok(value) => Ok(Value::Number(value.tan())),
-					_ => Ok(Value::Number(num.angle_to_radians(angle_mode).tan())),
-				}
-			}
 			Value::Complex(value) => Self::check_complex(value.tan()),
-			_ => Ok(Value::Number(
-				self.real_number()?.angle_to_radians(angle_mode).tan(),
-			)),
+			_ => Ok(Value::Number(self.trig_angle_in_radians(angle_mode)?.tan())),
 		}
 	}
 
@@ -298,6 +578,74 @@ impl Value {
 		}
 	}
 
+	fn coordinate_pair(&self, other: &Value) -> Result<(Number, Number, Option<CompositeUnit>)> {
+		// Convert both coordinates to the same units, if units are present
+		match (self, other) {
+			(Value::NumberWithUnit(a, a_unit), Value::NumberWithUnit(b, b_unit)) => Ok((
+				a_unit.coerce_to_other(a, b_unit)?,
+				b.clone(),
+				Some(b_unit.clone()),
+			)),
+			(Value::NumberWithUnit(a, unit), _) => {
+				Ok((a.clone(), other.real_number()?.clone(), Some(unit.clone())))
+			}
+			(_, Value::NumberWithUnit(b, unit)) => {
+				Ok((self.real_number()?.clone(), b.clone(), Some(unit.clone())))
+			}
+			_ => Ok((
+				self.real_number()?.clone(),
+				other.real_number()?.clone(),
+				None,
+			)),
+		}
+	}
+
+	pub fn atan2(&self, x: &Value, angle_mode: AngleUnit) -> Result<Value> {
+		let (y, x, _) = self.coordinate_pair(x)?;
+		Ok(Value::NumberWithUnit(
+			y.atan2(&x).angle_from_radians(angle_mode).into_owned(),
+			CompositeUnit::single_unit(angle_mode.into()),
+		))
+	}
+
+	pub fn hypot(&self, other: &Value) -> Result<Value> {
+		let (a, b, unit) = self.coordinate_pair(other)?;
+		match unit {
+			Some(unit) => Ok(Value::NumberWithUnit(a.hypot(&b), unit)),
+			None => Ok(Value::Number(a.hypot(&b))),
+		}
+	}
+
+	pub fn round_to_multiple(&self, step: &Value) -> Result<Value> {
+		let (value, step, unit) = self.coordinate_pair(step)?;
+		match unit {
+			Some(unit) => Ok(Value::NumberWithUnit(value.round_to_multiple(&step)?, unit)),
+			None => Ok(Value::Number(value.round_to_multiple(&step)?)),
+		}
+	}
+
+	pub fn round_to_significant_digits(&self, digits: usize) -> Result<Value> {
+		match self {
+			Value::Number(num) => Ok(Value::Number(num.round_to_significant_digits(digits)?)),
+			Value::NumberWithUnit(num, unit) => Ok(Value::NumberWithUnit(
+				num.round_to_significant_digits(digits)?,
+				unit.clone(),
+			)),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
+	pub fn truncate_to_multiple(&self, step: &Value) -> Result<Value> {
+		let (value, step, unit) = self.coordinate_pair(step)?;
+		match unit {
+			Some(unit) => Ok(Value::NumberWithUnit(
+				value.truncate_to_multiple(&step)?,
+				unit,
+			)),
+			None => Ok(Value::Number(value.truncate_to_multiple(&step)?)),
+		}
+	}
+
 	pub fn sinh(&self) -> Result<Value> {
 		match self {
 			Value::Complex(value) => Self::check_complex(value.sinh()),
@@ -361,6 +709,22 @@ impl Value {
 		}
 	}
 
+	pub fn gamma(&self) -> Result<Value> {
+		Ok(Value::Number(self.real_number()?.gamma()))
+	}
+
+	pub fn ln_gamma(&self) -> Result<Value> {
+		Ok(Value::Number(self.real_number()?.ln_gamma()))
+	}
+
+	pub fn erf(&self) -> Result<Value> {
+		Ok(Value::Number(self.real_number()?.erf()))
+	}
+
+	pub fn erfc(&self) -> Result<Value> {
+		Ok(Value::Number(self.real_number()?.erfc()))
+	}
+
 	pub fn add_unit(&self, unit: Unit) -> Result<Value> {
 		match self {
 			Value::Number(num) => Ok(Value::NumberWithUnit(
@@ -415,6 +779,43 @@ impl Value {
 		}
 	}
 
+	pub fn to_base_units(&self) -> Result<Value> {
+		match self {
+			Value::NumberWithUnit(num, units) => {
+				let (new_num, new_units) = units.to_base_si(num);
+				if new_units.unitless() {
+					Ok(Value::Number(new_num))
+				} else {
+					Ok(Value::NumberWithUnit(new_num, new_units))
+				}
+			}
+			Value::Number(_) => Ok(self.clone()),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
+	pub fn to_metric_units(&self) -> Result<Value> {
+		match self {
+			Value::NumberWithUnit(num, units) => {
+				let (new_num, new_units) = units.to_metric(num)?;
+				Ok(Value::NumberWithUnit(new_num, new_units))
+			}
+			Value::Number(_) => Ok(self.clone()),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
+	pub fn to_imperial_units(&self) -> Result<Value> {
+		match self {
+			Value::NumberWithUnit(num, units) => {
+				let (new_num, new_units) = units.to_imperial(num)?;
+				Ok(Value::NumberWithUnit(new_num, new_units))
+			}
+			Value::Number(_) => Ok(self.clone()),
+			_ => Err(Error::NotARealNumber),
+		}
+	}
+
 	fn datetime_add_secs(&self, dt: &NaiveDateTime, secs: &Number) -> Result<Value> {
 		let nano = i64::try_from(&*(secs * &1_000_000_000.to_number()).to_int()?)?;
 		Ok(Value::DateTime(dt.add(Duration::nanoseconds(nano))))
@@ -764,7 +1165,11 @@ impl Value {
 				Value::NumberWithUnit(right, right_unit) => {
 					let mut unit = left_unit.clone();
 					let left = unit.combine(left, right_unit);
-					Ok(Value::NumberWithUnit(&left * right, unit))
+					if unit.unitless() {
+						Ok(Value::Number(&left * right))
+					} else {
+						Ok(Value::NumberWithUnit(&left * right, unit))
+					}
 				}
 				Value::Complex(right) => {
 					Self::check_complex(&ComplexNumber::from_real(left.clone()) * right)
@@ -843,22 +1248,7 @@ impl Value {
 					}
 					Ok(Value::Matrix(result))
 				}
-				Value::Matrix(right) => {
-					if left.cols() != right.rows() {
-						return Err(Error::DimensionMismatch);
-					}
-					let mut result = Matrix::new(left.rows(), right.cols())?;
-					for row in 0..left.rows() {
-						for col in 0..right.cols() {
-							let mut sum = Value::Number(0.into());
-							for i in 0..left.cols() {
-								sum = (sum + (left.get(row, i)? * right.get(i, col)?)?)?;
-							}
-							result.set(row, col, sum)?;
-						}
-					}
-					Ok(Value::Matrix(result))
-				}
+				Value::Matrix(right) => Ok(Value::Matrix(left.multiply(right)?)),
 				Value::Vector(right) => {
 					if left.cols() != 1 {
 						return Err(Error::DimensionMismatch);
@@ -895,7 +1285,11 @@ impl Value {
 				Value::NumberWithUnit(right, right_unit) => {
 					let mut unit = left_unit.clone();
 					let left = unit.combine(left, &right_unit.inverse());
-					Ok(Value::NumberWithUnit(&left / right, unit))
+					if unit.unitless() {
+						Ok(Value::Number(&left / right))
+					} else {
+						Ok(Value::NumberWithUnit(&left / right, unit))
+					}
 				}
 				Value::Complex(right) => {
 					Self::check_complex(&ComplexNumber::from_real(left.clone()) / right)
@@ -1229,3 +1623,46 @@ impl StorageObject for Value {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::unit::{DistanceUnit, EnergyUnit, ForceUnit};
+
+	fn with_unit(value: i32, unit: Unit) -> Value {
+		Value::NumberWithUnit(value.into(), CompositeUnit::single_unit(unit))
+	}
+
+	fn units(value: &Value) -> Vec<(Unit, i32)> {
+		match value {
+			Value::NumberWithUnit(_, unit) => unit.units.values().cloned().collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	#[test]
+	fn units_cancel_and_combine() {
+		let meters = Unit::Distance(DistanceUnit::Meters);
+		let feet = Unit::Distance(DistanceUnit::Feet);
+
+		// Dividing compatible units leaves a number without units
+		let ratio = (with_unit(10, meters) / with_unit(2, meters)).unwrap();
+		assert!(units(&ratio).is_empty());
+		assert_eq!(ratio.to_f64().unwrap(), 5.0);
+		let ratio = (with_unit(10, meters) / with_unit(2, feet)).unwrap();
+		assert!(units(&ratio).is_empty());
+		assert!((ratio.to_f64().unwrap() - 10.0 / 0.6096).abs() < 1e-12);
+
+		// Multiplying the same unit collapses into a power
+		let area = (with_unit(2, meters) * with_unit(3, meters)).unwrap();
+		assert!(units(&area) == vec![(meters, 2)]);
+		assert_eq!(area.to_f64().unwrap(), 6.0);
+
+		// Derived units simplify when they partially cancel
+		let distance = (with_unit(6, Unit::Energy(EnergyUnit::Joules))
+			/ with_unit(2, Unit::Force(ForceUnit::Newton)))
+		.unwrap();
+		assert!(units(&distance) == vec![(meters, 1)]);
+		assert_eq!(distance.to_f64().unwrap(), 3.0);
+	}
+}
@@ -1,10 +1,18 @@
+#[cfg(feature = "std")]
+use crate::csv::{parse_number, split_fields};
 use crate::error::{Error, Result};
+#[cfg(feature = "std")]
+use crate::format::DecimalPointMode;
 use crate::number::ToNumber;
 use crate::storage::{
 	store, DeserializeInput, SerializeOutput, StorageObject, StorageRef, StorageRefArray,
 	StorageRefSerializer,
 };
 use crate::value::{Value, ValueRef};
+use core::cmp::Ordering;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 
 const MAX_CAPACITY: usize = 1000;
 const EXTRA_CAPACITY: usize = 4;
@@ -26,6 +34,27 @@ impl Vector {
 		})
 	}
 
+	/// Parses a vector from a single line of comma separated values, optionally
+	/// surrounded by brackets. When the decimal point is a comma, fields are expected
+	/// to be separated with semicolons.
+	#[cfg(feature = "std")]
+	pub fn from_str(text: &str, decimal_point: DecimalPointMode) -> Result<Self> {
+		let text = text.trim();
+		let text = text
+			.strip_prefix('[')
+			.and_then(|text| text.strip_suffix(']'))
+			.unwrap_or(text);
+		if text.trim().len() == 0 || text.contains('\n') {
+			return Err(Error::CorruptData);
+		}
+
+		let mut result = Vector::new()?;
+		for field in split_fields(text, decimal_point) {
+			result.push(Value::Number(parse_number(field, decimal_point)?))?;
+		}
+		Ok(result)
+	}
+
 	fn from_len_and_array(len: usize, array: StorageRefArray<Value>) -> Result<Self> {
 		let zero = store(Value::Number(0.into()))?;
 		Ok(Vector { len, array, zero })
@@ -148,6 +177,33 @@ impl Vector {
 		self.sum()? / Value::Number(self.len().to_number())
 	}
 
+	/// Sorts the elements in ascending order. All elements must be real numbers.
+	pub fn sorted(&self) -> Result<Vector> {
+		let mut entries = Vec::new();
+		for i in 0..self.len() {
+			match self.get(i)? {
+				Value::Number(num) => entries.push((num, self.get_ref(i)?)),
+				_ => return Err(Error::NotARealNumber),
+			}
+		}
+		entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+		// Rearrange the references to the existing values instead of storing new copies
+		let mut result = self.clone();
+		for (i, (_, value)) in entries.into_iter().enumerate() {
+			result.array.set(i, value)?;
+		}
+		Ok(result)
+	}
+
+	pub fn reversed(&self) -> Result<Vector> {
+		let mut result = self.clone();
+		for i in 0..self.len() {
+			result.array.set(i, self.get_ref(self.len() - 1 - i)?)?;
+		}
+		Ok(result)
+	}
+
 	pub fn magnitude(&self) -> Result<Value> {
 		self.dot(self)?.sqrt()
 	}
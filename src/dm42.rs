@@ -13,22 +13,42 @@ mod menu;
 mod screen;
 mod state;
 mod unit;
+mod viewer;
 
 use input::{InputQueue, KeyEvent};
 use screen::Screen;
-use state::{InputResult, State};
+use state::{InputResult, Preferences, State};
 
 pub fn calc_main<ScreenT: Screen, InputT: InputQueue>(mut screen: ScreenT, mut input: InputT) {
 	screen.clear();
 
-	let mut state = State::new();
+	let mut state = match input.load_state() {
+		Some(data) => {
+			// Start in the saved modes, then restore the rest of the saved state
+			let preferences = Preferences::from_saved_state(&data).unwrap_or(Preferences::new());
+			let mut state = State::new_with_preferences(preferences);
+			let _ = state.deserialize(&data);
+			state
+		}
+		None => {
+			let mut state = State::new();
+			#[cfg(feature = "std")]
+			state.context_mut().format_mut().apply_host_locale();
+			state
+		}
+	};
 	state.render(&mut screen);
 
 	loop {
 		if let Some(input_event) = state.wait_for_input(&mut input) {
 			match state.handle_input(input_event, &screen) {
 				Ok(InputResult::Normal) => (),
-				Ok(InputResult::Suspend) => input.suspend(),
+				Ok(InputResult::Suspend) => {
+					if let Ok(data) = state.serialize() {
+						input.save_state(&data);
+					}
+					input.suspend();
+				}
 				Err(error) => {
 					state.show_error(error);
 					state.render(&mut screen);
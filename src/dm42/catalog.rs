@@ -1,16 +1,32 @@
 use crate::dm42::functions::Function;
 use crate::dm42::menu::{Menu, MenuItem, MenuItemFunction, MenuItemLayout};
+use crate::dm42::state::State;
+use rscalc_layout::font::Font;
+use rscalc_layout::layout::{Layout, TokenType};
+use rscalc_layout::value::ValueLayout;
 use rscalc_math::constant::Constant;
+use rscalc_math::context::Location;
+use rscalc_math::error::{Error, Result};
 use rscalc_math::functions::StackFunction;
+use rscalc_math::number::Number;
 
 #[cfg(feature = "dm42")]
 use alloc::boxed::Box;
 #[cfg(feature = "dm42")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "dm42")]
 use alloc::vec::Vec;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum CatalogPage {
+	Business,
+	Complex,
 	Constants,
+	Macro,
+	Math,
+	Percent,
+	Special,
+	Stack,
 	Stats,
 	Time,
 	Transcendental,
@@ -19,9 +35,53 @@ pub enum CatalogPage {
 }
 
 impl CatalogPage {
+	pub fn to_u8(&self) -> u8 {
+		match self {
+			CatalogPage::Complex => 0,
+			CatalogPage::Constants => 1,
+			CatalogPage::Macro => 2,
+			CatalogPage::Math => 3,
+			CatalogPage::Percent => 4,
+			CatalogPage::Stack => 5,
+			CatalogPage::Stats => 6,
+			CatalogPage::Time => 7,
+			CatalogPage::Transcendental => 8,
+			CatalogPage::Units => 9,
+			CatalogPage::Vector => 10,
+			CatalogPage::Special => 11,
+			CatalogPage::Business => 12,
+		}
+	}
+
+	pub fn from_u8(value: u8) -> Option<Self> {
+		match value {
+			0 => Some(CatalogPage::Complex),
+			1 => Some(CatalogPage::Constants),
+			2 => Some(CatalogPage::Macro),
+			3 => Some(CatalogPage::Math),
+			4 => Some(CatalogPage::Percent),
+			5 => Some(CatalogPage::Stack),
+			6 => Some(CatalogPage::Stats),
+			7 => Some(CatalogPage::Time),
+			8 => Some(CatalogPage::Transcendental),
+			9 => Some(CatalogPage::Units),
+			10 => Some(CatalogPage::Vector),
+			11 => Some(CatalogPage::Special),
+			12 => Some(CatalogPage::Business),
+			_ => None,
+		}
+	}
+
 	pub fn to_str(&self) -> &'static str {
 		match self {
+			CatalogPage::Business => "Business",
+			CatalogPage::Complex => "Complex",
 			CatalogPage::Constants => "Constants",
+			CatalogPage::Macro => "Macro",
+			CatalogPage::Math => "Math",
+			CatalogPage::Percent => "Percent",
+			CatalogPage::Special => "Special",
+			CatalogPage::Stack => "Stack",
 			CatalogPage::Stats => "Statistics",
 			CatalogPage::Time => "Time",
 			CatalogPage::Transcendental => "Transcendental",
@@ -36,7 +96,14 @@ impl CatalogPage {
 		func: &dyn Fn(Function) -> Function,
 	) -> Menu {
 		match self {
+			CatalogPage::Business => business_catalog_menu(func),
+			CatalogPage::Complex => complex_catalog_menu(func),
 			CatalogPage::Constants => constant_catalog_menu(func),
+			CatalogPage::Macro => macro_catalog_menu(func),
+			CatalogPage::Math => math_catalog_menu(func),
+			CatalogPage::Percent => percent_catalog_menu(func),
+			CatalogPage::Special => special_catalog_menu(func),
+			CatalogPage::Stack => stack_catalog_menu(func),
 			CatalogPage::Stats => stats_catalog_menu(func),
 			CatalogPage::Time => time_catalog_menu(func),
 			CatalogPage::Transcendental => transcendental_catalog_menu(func),
@@ -72,7 +139,14 @@ pub fn catalog_menu(func: &dyn Fn(CatalogPage) -> Function) -> Menu {
 	Menu::new(
 		"Catalog",
 		create_parent_items(&[
+			("Business", func(CatalogPage::Business)),
+			("Complex", func(CatalogPage::Complex)),
 			("Constants", func(CatalogPage::Constants)),
+			("Macro", func(CatalogPage::Macro)),
+			("Math", func(CatalogPage::Math)),
+			("Percent", func(CatalogPage::Percent)),
+			("Special", func(CatalogPage::Special)),
+			("Stack", func(CatalogPage::Stack)),
 			("Statistics", func(CatalogPage::Stats)),
 			("Time", func(CatalogPage::Time)),
 			("Transcendental", func(CatalogPage::Transcendental)),
@@ -82,15 +156,177 @@ pub fn catalog_menu(func: &dyn Fn(CatalogPage) -> Function) -> Menu {
 	)
 }
 
+fn business_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Business",
+		create_action_items(&[
+			("markup", func(Function::Stack(StackFunction::Markup))),
+			("margin", func(Function::Stack(StackFunction::Margin))),
+			("discount", func(Function::Stack(StackFunction::Discount))),
+			(
+				"compound interest",
+				func(Function::Stack(StackFunction::CompoundInterest)),
+			),
+			(
+				"CAGR",
+				func(Function::Stack(StackFunction::CompoundAnnualGrowthRate)),
+			),
+		]),
+	)
+}
+
+fn complex_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Complex",
+		create_action_items(&[
+			("to polar", func(Function::Stack(StackFunction::ToPolar))),
+			(
+				"to rectangular",
+				func(Function::Stack(StackFunction::ToRectangular)),
+			),
+			("real part", func(Function::Stack(StackFunction::RealPart))),
+			(
+				"imaginary part",
+				func(Function::Stack(StackFunction::ImaginaryPart)),
+			),
+			(
+				"round parts",
+				func(Function::Stack(StackFunction::RoundComplex)),
+			),
+			(
+				"swap re and im",
+				func(Function::Stack(StackFunction::SwapComplexParts)),
+			),
+		]),
+	)
+}
+
 fn constant_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
 	Menu::new(
 		"Constants",
-		create_action_items(&[(
-			"c - Speed of Light",
-			func(Function::Stack(StackFunction::Constant(
-				Constant::SpeedOfLight,
-			))),
-		)]),
+		create_action_items(&[
+			(
+				"c - Speed of Light",
+				func(Function::Stack(StackFunction::Constant(
+					Constant::SpeedOfLight,
+				))),
+			),
+			("User Constants", func(Function::UserConstantCatalog)),
+			("Define Constant", func(Function::DefineConstant)),
+		]),
+	)
+}
+
+fn macro_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Macro",
+		create_action_items(&[
+			("Start Recording", func(Function::StartRecording)),
+			("Stop Recording", func(Function::StopRecording)),
+			("Solve", func(Function::Solve)),
+			("Integrate", func(Function::Integrate)),
+		]),
+	)
+}
+
+fn math_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Math",
+		create_action_items(&[
+			("abs", func(Function::Stack(StackFunction::Abs))),
+			("hypot", func(Function::Stack(StackFunction::Hypot))),
+			("divmod", func(Function::Stack(StackFunction::DivMod))),
+			("sign", func(Function::Stack(StackFunction::Sign))),
+			(
+				"round to multiple",
+				func(Function::Stack(StackFunction::RoundToMultiple)),
+			),
+			(
+				"round to significant digits",
+				func(Function::Stack(StackFunction::RoundToSignificantDigits)),
+			),
+			(
+				"truncate to multiple",
+				func(Function::Stack(StackFunction::TruncateToMultiple)),
+			),
+			("floor", func(Function::Stack(StackFunction::IntgFloor))),
+			("ceiling", func(Function::Stack(StackFunction::IntgCeil))),
+			("round", func(Function::Stack(StackFunction::IntgRound))),
+			(
+				"integer part",
+				func(Function::Stack(StackFunction::IntegerPart)),
+			),
+			(
+				"fractional part",
+				func(Function::Stack(StackFunction::FractionalPart)),
+			),
+			("clamp", func(Function::Stack(StackFunction::Clamp))),
+			(
+				"Degrees to DMS",
+				func(Function::Stack(StackFunction::ToDMS)),
+			),
+			(
+				"DMS to Degrees",
+				func(Function::Stack(StackFunction::FromDMS)),
+			),
+			(
+				"continued fraction",
+				func(Function::Stack(StackFunction::ContinuedFraction)),
+			),
+			(
+				"simplify fraction",
+				func(Function::Stack(StackFunction::Simplify)),
+			),
+			("random", func(Function::Random)),
+			("random integer", func(Function::RandomInt)),
+			("seed random", func(Function::SeedRandom)),
+		]),
+	)
+}
+
+fn percent_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Percent",
+		create_action_items(&[
+			("% of", func(Function::Stack(StackFunction::Percent))),
+			(
+				"% change",
+				func(Function::Stack(StackFunction::PercentChange)),
+			),
+			(
+				"% of total",
+				func(Function::Stack(StackFunction::PercentTotal)),
+			),
+		]),
+	)
+}
+
+fn special_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Special",
+		create_action_items(&[
+			("Γ", func(Function::Stack(StackFunction::Gamma))),
+			("lnΓ", func(Function::Stack(StackFunction::LnGamma))),
+			("erf", func(Function::Stack(StackFunction::Erf))),
+			("erfc", func(Function::Stack(StackFunction::Erfc))),
+		]),
+	)
+}
+
+fn stack_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
+	Menu::new(
+		"Stack",
+		create_action_items(&[
+			("Roll Up", func(Function::Stack(StackFunction::RollUp))),
+			("Clear X", func(Function::Stack(StackFunction::ClearX))),
+			("Drop N", func(Function::Stack(StackFunction::DropN))),
+			("Pick", func(Function::Stack(StackFunction::Pick))),
+			("Depth", func(Function::Stack(StackFunction::StackDepth))),
+			("Last Result", func(Function::Ans)),
+			("Redo", func(Function::Redo)),
+			("View Full Value", func(Function::ViewEntry)),
+			("View All Bases", func(Function::ViewAllBases)),
+		]),
 	)
 }
 
@@ -100,6 +336,19 @@ fn stats_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
 		create_action_items(&[
 			("sum", func(Function::Stack(StackFunction::Sum))),
 			("mean", func(Function::Stack(StackFunction::Mean))),
+			("Add Data Point", func(Function::StatAdd)),
+			("Remove Data Point", func(Function::StatRemove)),
+			("Clear Data", func(Function::StatClear)),
+			("Data Count", func(Function::StatN)),
+			("Linear Regression", func(Function::LinReg)),
+			(
+				"Normal CDF",
+				func(Function::Stack(StackFunction::NormalCDF)),
+			),
+			(
+				"Inverse Normal CDF",
+				func(Function::Stack(StackFunction::NormalInvCDF)),
+			),
 		]),
 	)
 }
@@ -111,6 +360,42 @@ fn time_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
 			("Now", func(Function::Stack(StackFunction::Now))),
 			("Date", func(Function::Stack(StackFunction::Date))),
 			("Time", func(Function::Stack(StackFunction::Time))),
+			(
+				"Day of Week",
+				func(Function::Stack(StackFunction::DayOfWeek)),
+			),
+			(
+				"Day of Year",
+				func(Function::Stack(StackFunction::DayOfYear)),
+			),
+			("ISO Week", func(Function::Stack(StackFunction::ISOWeek))),
+			("Quarter", func(Function::Stack(StackFunction::Quarter))),
+			(
+				"Add Months",
+				func(Function::Stack(StackFunction::AddMonths)),
+			),
+			("Add Years", func(Function::Stack(StackFunction::AddYears))),
+			("Hours to HMS", func(Function::Stack(StackFunction::ToHMS))),
+			(
+				"HMS to Hours",
+				func(Function::Stack(StackFunction::FromHMS)),
+			),
+			(
+				"To Julian Day",
+				func(Function::Stack(StackFunction::ToJulianDay)),
+			),
+			(
+				"From Julian Day",
+				func(Function::Stack(StackFunction::FromJulianDay)),
+			),
+			(
+				"To Unix Time",
+				func(Function::Stack(StackFunction::ToUnixTime)),
+			),
+			(
+				"From Unix Time",
+				func(Function::Stack(StackFunction::FromUnixTime)),
+			),
 		]),
 	)
 }
@@ -132,6 +417,7 @@ fn transcendental_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
 			("asin", func(Function::Stack(StackFunction::Asin))),
 			("acos", func(Function::Stack(StackFunction::Acos))),
 			("atan", func(Function::Stack(StackFunction::Atan))),
+			("atan2", func(Function::Stack(StackFunction::Atan2))),
 			("asinh", func(Function::Stack(StackFunction::Asinh))),
 			("acosh", func(Function::Stack(StackFunction::Acosh))),
 			("atanh", func(Function::Stack(StackFunction::Atanh))),
@@ -160,6 +446,52 @@ fn vector_catalog_menu(func: &dyn Fn(Function) -> Function) -> Menu {
 			("cross", func(Function::Stack(StackFunction::CrossProduct))),
 			("magnitude", func(Function::Stack(StackFunction::Magnitude))),
 			("normalize", func(Function::Stack(StackFunction::Normalize))),
+			(
+				"matrix power",
+				func(Function::Stack(StackFunction::MatrixPow)),
+			),
+			("trace", func(Function::Stack(StackFunction::Trace))),
+			("row sums", func(Function::Stack(StackFunction::RowSums))),
+			("column sums", func(Function::Stack(StackFunction::ColSums))),
+			("range", func(Function::Stack(StackFunction::Range))),
+			("linspace", func(Function::Stack(StackFunction::Linspace))),
+			("sort", func(Function::Stack(StackFunction::SortVector))),
+			(
+				"reverse",
+				func(Function::Stack(StackFunction::ReverseVector)),
+			),
+			(
+				"get element",
+				func(Function::Stack(StackFunction::VectorGet)),
+			),
+			(
+				"put element",
+				func(Function::Stack(StackFunction::VectorPut)),
+			),
+			("reshape", func(Function::Stack(StackFunction::Reshape))),
+			("flatten", func(Function::Stack(StackFunction::Flatten))),
+			("augment", func(Function::Stack(StackFunction::Augment))),
+			(
+				"stack matrices",
+				func(Function::Stack(StackFunction::StackMatrices)),
+			),
+			("submatrix", func(Function::Stack(StackFunction::Submatrix))),
+			(
+				"delete row",
+				func(Function::Stack(StackFunction::DeleteRow)),
+			),
+			(
+				"delete column",
+				func(Function::Stack(StackFunction::DeleteColumn)),
+			),
+			(
+				"LU decomposition",
+				func(Function::Stack(StackFunction::LUDecomposition)),
+			),
+			(
+				"eigenvalues",
+				func(Function::Stack(StackFunction::Eigenvalues)),
+			),
 		]),
 	)
 }
@@ -185,3 +517,106 @@ pub fn assign_menu() -> Menu {
 	menu.set_columns(3);
 	menu
 }
+
+pub fn assign_function_menu(func: Function) -> Menu {
+	let mut items = Vec::new();
+	for i in 0..18 {
+		items.push(MenuItem {
+			layout: MenuItemLayout::Dynamic(Box::new(move |state, _screen| {
+				if let Some(func) = state.custom_function(i) {
+					MenuItem::string_layout(func.to_string(state))
+				} else {
+					MenuItem::static_string_layout("(None)")
+				}
+			})),
+			function: MenuItemFunction::Action(Function::AssignCatalogFunction(
+				i,
+				Box::new(func.clone()),
+			)),
+		});
+	}
+	let mut menu = Menu::new("Assign To Custom Key", items);
+	menu.set_columns(3);
+	menu
+}
+
+pub fn user_constant_catalog_menu(
+	state: &State,
+	func: &dyn Fn(Function) -> Function,
+) -> Result<Menu> {
+	let mut items = Vec::new();
+	for name in state.user_constant_names() {
+		items.push(MenuItem {
+			layout: MenuItemLayout::Dynamic(Box::new(move |state, screen| {
+				// Show the name of the constant followed by its value
+				let mut label = String::new();
+				label.push(name);
+				label += " = ";
+				let label_width = screen.metrics().width(Font::Small, &label);
+				let mut layout_items = Vec::new();
+				layout_items.push(Layout::Text(label, Font::Small, TokenType::Label));
+				if let Ok(value) = state.user_constant(name) {
+					layout_items.push(value.single_line_simple_layout(
+						state.context().format(),
+						Font::Small,
+						screen.metrics(),
+						screen.width() - label_width - 48,
+					));
+				}
+				Layout::LeftAlign(Box::new(Layout::Horizontal(layout_items)))
+			})),
+			function: MenuItemFunction::ActionWithDelete(
+				func(Function::RecallConstant(name)),
+				Function::DeleteConstant(name),
+			),
+		});
+	}
+
+	if items.len() == 0 {
+		return Err(Error::ValueNotDefined);
+	}
+	Ok(Menu::new("User Constants", items))
+}
+
+pub fn variable_catalog_menu(state: &State) -> Result<Menu> {
+	let mut items = Vec::new();
+	for location in state.context().memory_locations() {
+		let name = match &location {
+			Location::Integer(idx) => Number::Integer((*idx).into()).to_string(),
+			Location::Variable(ch) => {
+				let mut string = String::new();
+				string.push(*ch);
+				string
+			}
+			Location::StackOffset(_) => continue,
+		};
+		let value_location = location.clone();
+		items.push(MenuItem {
+			layout: MenuItemLayout::Dynamic(Box::new(move |state, screen| {
+				// Show the name of the variable followed by its current value
+				let name = name.to_string() + " = ";
+				let name_width = screen.metrics().width(Font::Small, &name);
+				let mut layout_items = Vec::new();
+				layout_items.push(Layout::Text(name, Font::Small, TokenType::Label));
+				if let Ok(value) = state.context().read(&value_location) {
+					layout_items.push(value.single_line_simple_layout(
+						state.context().format(),
+						Font::Small,
+						screen.metrics(),
+						screen.width() - name_width - 48,
+					));
+				}
+				Layout::LeftAlign(Box::new(Layout::Horizontal(layout_items)))
+			})),
+			function: MenuItemFunction::ActionWithDelete(
+				Function::RecallVariable(location.clone()),
+				Function::DeleteVariable(location),
+			),
+		});
+	}
+
+	if items.len() == 0 {
+		return Err(Error::ValueNotDefined);
+	}
+	Ok(Menu::new("Variables", items))
+}
@@ -692,6 +692,15 @@ impl InputQueue for DM42InputQueue {
 	fn suspend(&self) {
 		set_state(STAT_PGM_END);
 	}
+
+	// Program memory stays powered while the calculator is off, so the state is kept
+	// across suspend without saving it. There is no file access from the program yet,
+	// so the state does not persist after the program exits.
+	fn load_state(&self) -> Option<Vec<u8>> {
+		None
+	}
+
+	fn save_state(&self, _data: &[u8]) {}
 }
 
 extern "C" fn program_entry() -> ! {
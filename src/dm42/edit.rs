@@ -1,10 +1,14 @@
 use intel_dfp::Decimal;
 use num_bigint::{BigInt, ToBigInt};
 use rscalc_layout::layout::TokenType;
+use rscalc_math::complex::ComplexNumber;
 use rscalc_math::error::{Error, Result};
 use rscalc_math::format::{DecimalPointMode, Format};
 use rscalc_math::number::Number;
+use rscalc_math::value::Value;
 
+#[cfg(feature = "dm42")]
+use alloc::boxed::Box;
 #[cfg(feature = "dm42")]
 use alloc::string::String;
 #[cfg(feature = "dm42")]
@@ -13,33 +17,74 @@ use alloc::vec::Vec;
 const MAX_FRACTION_DIGITS: usize = 34;
 const MAX_EXPONENT: i32 = 9999;
 
+// Prefixes that can be typed after a number to scale it, such as "4.7k" or "4k7"
+const SI_PREFIXES: [(char, i32); 16] = [
+	('y', -24),
+	('z', -21),
+	('a', -18),
+	('f', -15),
+	('p', -12),
+	('n', -9),
+	('μ', -6),
+	('m', -3),
+	('k', 3),
+	('M', 6),
+	('G', 9),
+	('T', 12),
+	('P', 15),
+	('E', 18),
+	('Z', 21),
+	('Y', 24),
+];
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum NumberEditorState {
 	Integer,
 	Fraction,
+	Denominator,
 	Exponent,
 }
 
+#[derive(Clone)]
+struct SIPrefixEntry {
+	prefix: char,
+	exponent: i32,
+	// Number of fraction digits entered before the prefix, or None if the prefix
+	// was typed in place of the decimal point
+	fraction_digits: Option<usize>,
+}
+
+#[derive(Clone)]
 pub struct NumberEditor {
 	sign: bool,
 	integer: BigInt,
 	fraction_digits: Vec<u8>,
+	denominator_digits: Vec<u8>,
 	exponent_sign: bool,
 	exponent: Option<i32>,
+	si_prefix: Option<SIPrefixEntry>,
 	radix: u8,
 	state: NumberEditorState,
+	real_part: Option<Box<NumberEditor>>,
 }
 
 impl NumberEditor {
 	pub fn new(format: &Format) -> Self {
+		Self::new_with_radix(format.integer_radix)
+	}
+
+	fn new_with_radix(radix: u8) -> Self {
 		NumberEditor {
 			sign: false,
 			integer: 0.into(),
 			fraction_digits: Vec::new(),
+			denominator_digits: Vec::new(),
 			exponent_sign: false,
 			exponent: None,
-			radix: format.integer_radix,
+			si_prefix: None,
+			radix,
 			state: NumberEditorState::Integer,
+			real_part: None,
 		}
 	}
 
@@ -57,6 +102,11 @@ impl NumberEditor {
 					self.fraction_digits.push(digit);
 				}
 			}
+			NumberEditorState::Denominator => {
+				if self.denominator_digits.len() < MAX_FRACTION_DIGITS {
+					self.denominator_digits.push(digit);
+				}
+			}
 			NumberEditorState::Exponent => {
 				let new_exponent = match self.exponent {
 					Some(exponent) => (exponent * 10) + digit as i32,
@@ -73,29 +123,95 @@ impl NumberEditor {
 	pub fn push_char(&mut self, ch: char) -> Result<()> {
 		match ch {
 			'0'..='9' => self.push_digit(ch as u32 as u8 - '0' as u32 as u8),
+			'A'..='Z' | 'a'..='z' | 'μ' | 'µ' if self.radix == 10 => self.push_si_prefix(ch),
 			'A'..='Z' => self.push_digit(ch as u32 as u8 - 'A' as u32 as u8 + 10),
 			'a'..='z' => self.push_digit(ch as u32 as u8 - 'a' as u32 as u8 + 10),
 			'.' => {
-				if self.state == NumberEditorState::Integer && self.radix == 10 {
-					self.state = NumberEditorState::Fraction;
-					Ok(())
-				} else {
-					Err(Error::InvalidEntry)
+				if self.radix != 10 || self.si_prefix.is_some() {
+					return Err(Error::InvalidEntry);
+				}
+				match self.state {
+					NumberEditorState::Integer => {
+						self.state = NumberEditorState::Fraction;
+						Ok(())
+					}
+					NumberEditorState::Fraction => {
+						// A second decimal point turns the digits after the first one into
+						// the numerator of a fraction, so "1.3.4" is entered as 1 3/4.
+						self.state = NumberEditorState::Denominator;
+						Ok(())
+					}
+					_ => Err(Error::InvalidEntry),
 				}
 			}
 			_ => Err(Error::InvalidEntry),
 		}
 	}
 
+	fn push_si_prefix(&mut self, ch: char) -> Result<()> {
+		// There is no key for the micro sign, so it is entered as a "u". The micro sign
+		// character is also accepted, but the Greek mu is used as only it has a glyph.
+		let prefix = match ch {
+			'u' | 'µ' => 'μ',
+			_ => ch,
+		};
+		let exponent = SI_PREFIXES
+			.iter()
+			.find(|(candidate, _)| *candidate == prefix)
+			.ok_or(Error::InvalidEntry)?
+			.1;
+		if self.si_prefix.is_some() {
+			return Err(Error::InvalidEntry);
+		}
+		let fraction_digits = match self.state {
+			NumberEditorState::Integer => {
+				// A prefix typed before any decimal point acts as the decimal point, so
+				// "4k7" is entered as 4.7k.
+				self.state = NumberEditorState::Fraction;
+				None
+			}
+			NumberEditorState::Fraction => Some(self.fraction_digits.len()),
+			_ => return Err(Error::InvalidEntry),
+		};
+		self.si_prefix = Some(SIPrefixEntry {
+			prefix,
+			exponent,
+			fraction_digits,
+		});
+		Ok(())
+	}
+
 	pub fn exponent(&mut self) {
-		if self.state != NumberEditorState::Exponent && self.radix == 10 {
+		if self.state != NumberEditorState::Exponent
+			&& self.state != NumberEditorState::Denominator
+			&& self.si_prefix.is_none()
+			&& self.radix == 10
+		{
 			self.state = NumberEditorState::Exponent;
 		}
 	}
 
+	/// Starts entry of the imaginary part of a complex number. The number entered so
+	/// far becomes the real part. Does nothing if the imaginary part is already being
+	/// entered.
+	pub fn imaginary(&mut self) {
+		if self.real_part.is_none() && self.radix == 10 {
+			let real = core::mem::replace(self, Self::new_with_radix(self.radix));
+			self.real_part = Some(Box::new(real));
+		}
+	}
+
+	fn is_empty(&self) -> bool {
+		!self.sign
+			&& self.integer == 0.to_bigint().unwrap()
+			&& self.state == NumberEditorState::Integer
+	}
+
 	pub fn neg(&mut self) {
 		match self.state {
-			NumberEditorState::Integer | NumberEditorState::Fraction => {
+			NumberEditorState::Integer
+			| NumberEditorState::Fraction
+			| NumberEditorState::Denominator => {
 				self.sign = !self.sign;
 			}
 			NumberEditorState::Exponent => {
@@ -105,20 +221,45 @@ impl NumberEditor {
 	}
 
 	pub fn backspace(&mut self) -> bool {
+		// Deleting past the start of the imaginary part goes back to the real part
+		if self.is_empty() {
+			if let Some(real) = self.real_part.take() {
+				*self = *real;
+				return true;
+			}
+		}
+
 		match self.state {
 			NumberEditorState::Integer => {
 				self.integer /= self.radix.to_bigint().unwrap();
-				if self.integer == 0.to_bigint().unwrap() {
+				if self.integer == 0.to_bigint().unwrap() && self.real_part.is_none() {
 					return false;
 				}
 			}
 			NumberEditorState::Fraction => {
-				if self.fraction_digits.len() == 0 {
+				let prefix_is_last = match &self.si_prefix {
+					Some(si_prefix) => {
+						si_prefix.fraction_digits.unwrap_or(0) == self.fraction_digits.len()
+					}
+					None => false,
+				};
+				if prefix_is_last {
+					if self.si_prefix.take().unwrap().fraction_digits.is_none() {
+						self.state = NumberEditorState::Integer;
+					}
+				} else if self.fraction_digits.len() == 0 {
 					self.state = NumberEditorState::Integer;
 				} else {
 					self.fraction_digits.pop();
 				}
 			}
+			NumberEditorState::Denominator => {
+				if self.denominator_digits.len() == 0 {
+					self.state = NumberEditorState::Fraction;
+				} else {
+					self.denominator_digits.pop();
+				}
+			}
 			NumberEditorState::Exponent => {
 				if let Some(exponent) = self.exponent {
 					let new_exponent = exponent / 10;
@@ -139,20 +280,50 @@ impl NumberEditor {
 
 	pub fn to_string(&self, format: &Format) -> String {
 		let mut result = String::new();
-		if self.sign {
+		if let Some(real) = &self.real_part {
+			result += real.to_string(format).as_str();
+			result += if self.sign { " - " } else { " + " };
+		} else if self.sign {
 			result += "-";
 		}
+		if self.state == NumberEditorState::Denominator {
+			// Mixed numbers are shown with a space between the whole number and the
+			// fraction, and the whole number is left out when it is zero.
+			if self.integer != 0.to_bigint().unwrap() {
+				result += format.format_bigint(&self.integer).as_str();
+				result += " ";
+			}
+			result += Self::grouped_digits_to_string(&self.fraction_digits, format).as_str();
+			result += "/";
+			result += Self::grouped_digits_to_string(&self.denominator_digits, format).as_str();
+			return result;
+		}
 		result += format.format_bigint(&self.integer).as_str();
 		if self.state != NumberEditorState::Integer {
-			result += match format.decimal_point {
-				DecimalPointMode::Period => ".",
-				DecimalPointMode::Comma => ",",
-			};
-			let mut decimal_chars = Vec::new();
-			for digit in &self.fraction_digits {
-				decimal_chars.push(digit + '0' as u32 as u8);
+			match &self.si_prefix {
+				Some(SIPrefixEntry {
+					prefix,
+					fraction_digits: None,
+					..
+				}) => {
+					result.push(*prefix);
+					result += Self::digits_to_string(&self.fraction_digits).as_str();
+				}
+				Some(SIPrefixEntry {
+					prefix,
+					fraction_digits: Some(count),
+					..
+				}) => {
+					result += Self::decimal_point_str(format);
+					result += Self::digits_to_string(&self.fraction_digits[..*count]).as_str();
+					result.push(*prefix);
+					result += Self::digits_to_string(&self.fraction_digits[*count..]).as_str();
+				}
+				None => {
+					result += Self::decimal_point_str(format);
+					result += Self::digits_to_string(&self.fraction_digits).as_str();
+				}
 			}
-			result += String::from_utf8(decimal_chars).unwrap().as_str();
 		}
 		if self.state == NumberEditorState::Exponent {
 			result += "ᴇ";
@@ -166,10 +337,75 @@ impl NumberEditor {
 					.as_str();
 			}
 		}
+		if self.real_part.is_some() {
+			result += "ℹ";
+		}
 
 		result
 	}
 
+	fn decimal_point_str(format: &Format) -> &'static str {
+		match format.decimal_point {
+			DecimalPointMode::Period => ".",
+			DecimalPointMode::Comma => ",",
+		}
+	}
+
+	fn digits_to_string(digits: &[u8]) -> String {
+		let mut chars = Vec::new();
+		for digit in digits {
+			chars.push(digit + '0' as u32 as u8);
+		}
+		String::from_utf8(chars).unwrap()
+	}
+
+	// Groups digits with the same separators used when displaying integers, so that the
+	// parts of a fraction appear as they will once entered. Leading zeros are kept.
+	fn grouped_digits_to_string(digits: &[u8], format: &Format) -> String {
+		let mut chars = Vec::new();
+		for (i, digit) in digits.iter().rev().enumerate() {
+			if format.decimal_group_boundary(i) {
+				match format.decimal_point {
+					DecimalPointMode::Period => chars.push(',' as u32 as u8),
+					DecimalPointMode::Comma => chars.push('.' as u32 as u8),
+				}
+			}
+			chars.push(digit + '0' as u32 as u8);
+		}
+		chars.reverse();
+		String::from_utf8(chars).unwrap()
+	}
+
+	fn digits_to_bigint(digits: &[u8]) -> BigInt {
+		let mut result: BigInt = 0.into();
+		for digit in digits {
+			result *= 10;
+			result += *digit;
+		}
+		result
+	}
+
+	fn has_zero_denominator(&self) -> bool {
+		self.state == NumberEditorState::Denominator
+			&& Self::digits_to_bigint(&self.denominator_digits) == 0.to_bigint().unwrap()
+	}
+
+	/// Gets the value being entered, which is complex if an imaginary part was given.
+	pub fn value(&self) -> Result<Value> {
+		if self.has_zero_denominator() {
+			return Err(Error::InvalidEntry);
+		}
+		match &self.real_part {
+			Some(real) => {
+				if real.has_zero_denominator() {
+					return Err(Error::InvalidEntry);
+				}
+				Value::check_complex(ComplexNumber::from_parts(real.number(), self.number()))
+			}
+			None => Ok(Value::Number(self.number())),
+		}
+	}
+
 	pub fn number(&self) -> Number {
 		if self.state == NumberEditorState::Integer {
 			if self.sign {
@@ -179,6 +415,19 @@ impl NumberEditor {
 			}
 		}
 
+		if self.state == NumberEditorState::Denominator {
+			// Fractions are entered as exact rational numbers
+			let numerator = Self::digits_to_bigint(&self.fraction_digits);
+			let denominator = Self::digits_to_bigint(&self.denominator_digits);
+			let numerator = &self.integer * &denominator + numerator;
+			let result = &Number::Integer(numerator) / &Number::Integer(denominator);
+			if self.sign {
+				return -result;
+			} else {
+				return result;
+			}
+		}
+
 		let mut result = Number::bigint_to_decimal(&self.integer);
 
 		let one: Decimal = 1.into();
@@ -190,7 +439,7 @@ impl NumberEditor {
 			factor = &factor / &ten;
 		}
 
-		let exponent: Decimal = match self.exponent {
+		let exponent = match self.exponent {
 			Some(exponent) => {
 				if self.exponent_sign {
 					-exponent
@@ -199,6 +448,10 @@ impl NumberEditor {
 				}
 			}
 			None => 0,
+		};
+		let exponent: Decimal = match &self.si_prefix {
+			Some(si_prefix) => exponent + si_prefix.exponent,
+			None => exponent,
 		}
 		.into();
 
@@ -212,8 +465,46 @@ impl NumberEditor {
 
 	pub fn token_type(&self) -> TokenType {
 		match self.state {
-			NumberEditorState::Integer => TokenType::Integer,
+			NumberEditorState::Integer | NumberEditorState::Denominator => TokenType::Integer,
 			_ => TokenType::Float,
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn digits_up_to_radix() {
+		let mut editor = NumberEditor::new_with_radix(36);
+		editor.push_char('Z').unwrap();
+		assert_eq!(editor.value().unwrap().to_f64().unwrap(), 35.0);
+
+		let mut editor = NumberEditor::new_with_radix(16);
+		assert_eq!(editor.push_char('G').err(), Some(Error::InvalidEntry));
+	}
+
+	#[test]
+	fn fraction_entry() {
+		let format = Format::new();
+		let mut editor = NumberEditor::new(&format);
+		for ch in ".3.4".chars() {
+			editor.push_char(ch).unwrap();
+		}
+		assert_eq!(editor.to_string(&format), "3/4");
+		assert!(editor.number() == Number::Rational(3.into(), 4u32.into()));
+		let quotient = &Number::Integer(3.into()) / &Number::Integer(4.into());
+		assert_eq!(
+			format.format_number(&editor.number()).to_str(),
+			format.format_number(&quotient).to_str()
+		);
+
+		let mut editor = NumberEditor::new(&format);
+		for ch in "1.1.2".chars() {
+			editor.push_char(ch).unwrap();
+		}
+		assert_eq!(editor.to_string(&format), "1 1/2");
+		assert!(editor.number() == Number::Rational(3.into(), 2u32.into()));
+	}
+}
@@ -1,15 +1,20 @@
-use crate::dm42::catalog::{assign_menu, catalog_menu, CatalogPage};
+use crate::dm42::catalog::{
+	assign_function_menu, assign_menu, catalog_menu, user_constant_catalog_menu,
+	variable_catalog_menu, CatalogPage,
+};
 use crate::dm42::input::InputEvent;
-use crate::dm42::menu::settings_menu;
+use crate::dm42::menu::{clear_variables_menu, compact_menu, settings_menu};
 use crate::dm42::screen::{RenderMode, Screen};
 use crate::dm42::state::{State, StatusBarLeftDisplayType};
 use crate::dm42::unit::{unit_catalog_menu, unit_catalog_menu_of_type, unit_menu_of_type};
 use rscalc_layout::font::Font;
 use rscalc_layout::layout::{LayoutRenderer, Rect, TokenType};
-use rscalc_math::error::Result;
-use rscalc_math::format::{AlternateFormatMode, Format, IntegerMode};
+use rscalc_math::context::Location;
+use rscalc_math::error::{Error, Result};
+use rscalc_math::format::{AlternateFormatMode, Format, FormatMode, IntegerMode};
 use rscalc_math::functions::StackFunction;
-use rscalc_math::unit::UnitType;
+use rscalc_math::storage::{compact, DeserializeInput, SerializeOutput};
+use rscalc_math::unit::{AngleUnit, UnitType};
 
 #[cfg(not(feature = "dm42"))]
 use std::cell::RefCell;
@@ -26,6 +31,10 @@ use core::cell::RefCell;
 #[cfg(feature = "dm42")]
 use crate::dm42::device::{set_time_24_hour, time_24_hour};
 
+// Choices for the number of digits shown before switching to scientific notation
+const SCI_HIGH_THRESHOLDS: [usize; 5] = [6, 9, 15, 20, 30];
+const SCI_LOW_THRESHOLDS: [usize; 5] = [2, 3, 4, 5, 6];
+
 #[derive(PartialEq, Eq, Clone)]
 #[allow(dead_code)]
 pub enum Function {
@@ -48,6 +57,7 @@ pub enum Function {
 	AssignAddInvUnitCatalogPage(usize, UnitType),
 	AssignConvertUnitCatalogMenu(usize),
 	AssignConvertUnitCatalogPage(usize, UnitType),
+	AssignUserConstantCatalog(usize),
 	AssignCatalogFunction(usize, Box<Function>),
 	RemoveCustomAssign(usize),
 	UnitMenu(UnitType),
@@ -61,9 +71,262 @@ pub enum Function {
 	FontSizeToggle,
 	AlternateFormatModeToggle,
 	NewMatrix,
+	VariableCatalog,
+	RecallVariable(Location),
+	DeleteVariable(Location),
+	ClearVars,
+	ConfirmClearVars,
+	SetPrecision,
+	Redo,
+	StartRecording,
+	StopRecording,
+	Macro(Vec<InputEvent>),
+	SetRadix,
+	ViewEntry,
+	Solve,
+	Integrate,
+	StatAdd,
+	StatRemove,
+	StatClear,
+	StatN,
+	LinReg,
+	Hyperbolic,
+	KeepEditingOnErrorToggle,
+	SetGrouping,
+	Random,
+	RandomInt,
+	SeedRandom,
+	Ans,
+	EnterDuplicatesToggle,
+	DefineConstant,
+	UserConstantCatalog,
+	RecallConstant(char),
+	DeleteConstant(char),
+	SciHighThresholdToggle,
+	SciLowThresholdToggle,
+	Compact,
+	StartupAngleModeToggle,
+	StartupFormatModeToggle,
+	ViewAllBases,
+	CharacterToCode,
+	CodeToCharacter,
+	StrictOperandsToggle,
+	ConfirmCompact,
 }
 
 impl Function {
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		// Identifiers must remain stable, new functions should be given new identifiers
+		match self {
+			Function::Stack(value) => {
+				output.write_u8(0)?;
+				value.serialize(output)?;
+			}
+			Function::Input(value) => {
+				output.write_u8(1)?;
+				value.serialize(output)?;
+			}
+			Function::SignedInteger => output.write_u8(2)?,
+			Function::UnsignedInteger => output.write_u8(3)?,
+			Function::CatalogPage(value) => {
+				output.write_u8(4)?;
+				output.write_u8(value.to_u8())?;
+			}
+			Function::AddUnitCatalogMenu => output.write_u8(5)?,
+			Function::AddUnitCatalogPage(value) => {
+				output.write_u8(6)?;
+				output.write_u8(value.to_u8())?;
+			}
+			Function::AddInvUnitCatalogMenu => output.write_u8(7)?,
+			Function::AddInvUnitCatalogPage(value) => {
+				output.write_u8(8)?;
+				output.write_u8(value.to_u8())?;
+			}
+			Function::ConvertUnitCatalogMenu => output.write_u8(9)?,
+			Function::ConvertUnitCatalogPage(value) => {
+				output.write_u8(10)?;
+				output.write_u8(value.to_u8())?;
+			}
+			Function::UnitMenu(value) => {
+				output.write_u8(21)?;
+				output.write_u8(value.to_u8())?;
+			}
+			Function::SettingsMenu => output.write_u8(22)?,
+			Function::SystemMenu => output.write_u8(23)?,
+			Function::Time24HourToggle => output.write_u8(24)?,
+			Function::StatusBarLeftDisplayToggle => output.write_u8(25)?,
+			Function::StackLabelXYZToggle => output.write_u8(26)?,
+			Function::ShowEmptySoftKeyToggle => output.write_u8(27)?,
+			Function::StatusBarToggle => output.write_u8(28)?,
+			Function::FontSizeToggle => output.write_u8(29)?,
+			Function::AlternateFormatModeToggle => output.write_u8(30)?,
+			Function::NewMatrix => output.write_u8(31)?,
+			Function::VariableCatalog => output.write_u8(32)?,
+			Function::RecallVariable(value) => {
+				output.write_u8(33)?;
+				value.serialize(output)?;
+			}
+			Function::DeleteVariable(value) => {
+				output.write_u8(34)?;
+				value.serialize(output)?;
+			}
+			Function::ClearVars => output.write_u8(35)?,
+			Function::ConfirmClearVars => output.write_u8(36)?,
+			Function::SetPrecision => output.write_u8(37)?,
+			Function::Redo => output.write_u8(38)?,
+			Function::StartRecording => output.write_u8(39)?,
+			Function::StopRecording => output.write_u8(40)?,
+			Function::Macro(events) => {
+				output.write_u8(41)?;
+				output.write_u32(events.len() as u32)?;
+				for event in events {
+					event.serialize(output)?;
+				}
+			}
+			Function::SetRadix => output.write_u8(42)?,
+			Function::ViewEntry => output.write_u8(43)?,
+			Function::Solve => output.write_u8(44)?,
+			Function::Integrate => output.write_u8(45)?,
+			Function::StatAdd => output.write_u8(46)?,
+			Function::StatRemove => output.write_u8(47)?,
+			Function::StatClear => output.write_u8(48)?,
+			Function::StatN => output.write_u8(49)?,
+			Function::LinReg => output.write_u8(50)?,
+			Function::Hyperbolic => output.write_u8(51)?,
+			Function::KeepEditingOnErrorToggle => output.write_u8(52)?,
+			Function::SetGrouping => output.write_u8(53)?,
+			Function::Random => output.write_u8(54)?,
+			Function::RandomInt => output.write_u8(55)?,
+			Function::SeedRandom => output.write_u8(56)?,
+			Function::Ans => output.write_u8(57)?,
+			Function::EnterDuplicatesToggle => output.write_u8(58)?,
+			Function::DefineConstant => output.write_u8(59)?,
+			Function::UserConstantCatalog => output.write_u8(60)?,
+			Function::RecallConstant(name) => {
+				output.write_u8(61)?;
+				output.write_u32(*name as u32)?;
+			}
+			Function::DeleteConstant(name) => {
+				output.write_u8(62)?;
+				output.write_u32(*name as u32)?;
+			}
+			Function::SciHighThresholdToggle => output.write_u8(63)?,
+			Function::SciLowThresholdToggle => output.write_u8(64)?,
+			Function::Compact => output.write_u8(65)?,
+			Function::StartupAngleModeToggle => output.write_u8(66)?,
+			Function::StartupFormatModeToggle => output.write_u8(67)?,
+			Function::ViewAllBases => output.write_u8(68)?,
+			Function::CharacterToCode => output.write_u8(69)?,
+			Function::CodeToCharacter => output.write_u8(70)?,
+			Function::StrictOperandsToggle => output.write_u8(71)?,
+			Function::ConfirmCompact => output.write_u8(72)?,
+			// Custom key assignment functions only exist while navigating menus and
+			// are never saved.
+			Function::AssignCatalogMenu(..)
+			| Function::AssignCatalogPage(..)
+			| Function::AssignAddUnitCatalogMenu(..)
+			| Function::AssignAddUnitCatalogPage(..)
+			| Function::AssignAddInvUnitCatalogMenu(..)
+			| Function::AssignAddInvUnitCatalogPage(..)
+			| Function::AssignConvertUnitCatalogMenu(..)
+			| Function::AssignConvertUnitCatalogPage(..)
+			| Function::AssignUserConstantCatalog(..)
+			| Function::AssignCatalogFunction(..)
+			| Function::RemoveCustomAssign(..) => return Err(Error::DataTypeMismatch),
+		}
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		Ok(match input.read_u8()? {
+			0 => Function::Stack(StackFunction::deserialize(input)?),
+			1 => Function::Input(InputEvent::deserialize(input)?),
+			2 => Function::SignedInteger,
+			3 => Function::UnsignedInteger,
+			4 => Function::CatalogPage(
+				CatalogPage::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?,
+			),
+			5 => Function::AddUnitCatalogMenu,
+			6 => Function::AddUnitCatalogPage(
+				UnitType::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?,
+			),
+			7 => Function::AddInvUnitCatalogMenu,
+			8 => Function::AddInvUnitCatalogPage(
+				UnitType::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?,
+			),
+			9 => Function::ConvertUnitCatalogMenu,
+			10 => Function::ConvertUnitCatalogPage(
+				UnitType::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?,
+			),
+			21 => {
+				Function::UnitMenu(UnitType::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?)
+			}
+			22 => Function::SettingsMenu,
+			23 => Function::SystemMenu,
+			24 => Function::Time24HourToggle,
+			25 => Function::StatusBarLeftDisplayToggle,
+			26 => Function::StackLabelXYZToggle,
+			27 => Function::ShowEmptySoftKeyToggle,
+			28 => Function::StatusBarToggle,
+			29 => Function::FontSizeToggle,
+			30 => Function::AlternateFormatModeToggle,
+			31 => Function::NewMatrix,
+			32 => Function::VariableCatalog,
+			33 => Function::RecallVariable(Location::deserialize(input)?),
+			34 => Function::DeleteVariable(Location::deserialize(input)?),
+			35 => Function::ClearVars,
+			36 => Function::ConfirmClearVars,
+			37 => Function::SetPrecision,
+			38 => Function::Redo,
+			39 => Function::StartRecording,
+			40 => Function::StopRecording,
+			41 => {
+				let count = input.read_u32()?;
+				let mut events = Vec::new();
+				for _ in 0..count {
+					events.push(InputEvent::deserialize(input)?);
+				}
+				Function::Macro(events)
+			}
+			42 => Function::SetRadix,
+			43 => Function::ViewEntry,
+			44 => Function::Solve,
+			45 => Function::Integrate,
+			46 => Function::StatAdd,
+			47 => Function::StatRemove,
+			48 => Function::StatClear,
+			49 => Function::StatN,
+			50 => Function::LinReg,
+			51 => Function::Hyperbolic,
+			52 => Function::KeepEditingOnErrorToggle,
+			53 => Function::SetGrouping,
+			54 => Function::Random,
+			55 => Function::RandomInt,
+			56 => Function::SeedRandom,
+			57 => Function::Ans,
+			58 => Function::EnterDuplicatesToggle,
+			59 => Function::DefineConstant,
+			60 => Function::UserConstantCatalog,
+			61 => Function::RecallConstant(
+				core::char::from_u32(input.read_u32()?).ok_or(Error::CorruptData)?,
+			),
+			62 => Function::DeleteConstant(
+				core::char::from_u32(input.read_u32()?).ok_or(Error::CorruptData)?,
+			),
+			63 => Function::SciHighThresholdToggle,
+			64 => Function::SciLowThresholdToggle,
+			65 => Function::Compact,
+			66 => Function::StartupAngleModeToggle,
+			67 => Function::StartupFormatModeToggle,
+			68 => Function::ViewAllBases,
+			69 => Function::CharacterToCode,
+			70 => Function::CodeToCharacter,
+			71 => Function::StrictOperandsToggle,
+			72 => Function::ConfirmCompact,
+			_ => return Err(Error::CorruptData),
+		})
+	}
+
 	pub fn to_string(&self, state: &State) -> String {
 		match self {
 			Function::Stack(func) => func.to_string(state.context()),
@@ -95,6 +358,7 @@ impl Function {
 			Function::AssignConvertUnitCatalogPage(_, unit_type) => {
 				"▸".to_string() + unit_type.to_str()
 			}
+			Function::AssignUserConstantCatalog(_) => "User".to_string(),
 			Function::AssignCatalogFunction(_, func) => func.to_string(state),
 			Function::RemoveCustomAssign(_) => "(None)".to_string(),
 			Function::UnitMenu(unit_type) => unit_type.to_str().to_string(),
@@ -107,7 +371,69 @@ impl Function {
 			Function::StatusBarToggle => "StatusBar".to_string(),
 			Function::FontSizeToggle => "Font".to_string(),
 			Function::AlternateFormatModeToggle => "Alt".to_string(),
+			Function::KeepEditingOnErrorToggle => "KeepIn".to_string(),
+			Function::EnterDuplicatesToggle => "EntDup".to_string(),
+			Function::StrictOperandsToggle => "Strict".to_string(),
+			Function::SciHighThresholdToggle => "SciHi".to_string(),
+			Function::SciLowThresholdToggle => "SciLo".to_string(),
+			Function::Compact => "Compact".to_string(),
+			Function::ConfirmCompact => "Reclaim".to_string(),
+			Function::StartupAngleModeToggle => "StAng".to_string(),
+			Function::StartupFormatModeToggle => "StFmt".to_string(),
+			Function::ViewAllBases => "Bases".to_string(),
+			Function::CharacterToCode => "→Code".to_string(),
+			Function::CodeToCharacter => "→Char".to_string(),
+			Function::Random => "Rand".to_string(),
+			Function::RandomInt => "RandI".to_string(),
+			Function::SeedRandom => "Seed".to_string(),
+			Function::Ans => "Ans".to_string(),
+			Function::DefineConstant => "DefC".to_string(),
+			Function::UserConstantCatalog => "User".to_string(),
+			Function::RecallConstant(name) => {
+				let mut result = String::new();
+				result.push(*name);
+				result
+			}
+			Function::DeleteConstant(_) => "Delete".to_string(),
 			Function::NewMatrix => "New".to_string(),
+			Function::VariableCatalog => "Vars".to_string(),
+			Function::RecallVariable(location) => match location {
+				Location::Variable(ch) => {
+					let mut result = "Rcl ".to_string();
+					result.push(*ch);
+					result
+				}
+				Location::Integer(idx) => "Rcl ".to_string() + &idx.to_string(),
+				Location::StackOffset(idx) => "Rcl ".to_string() + &(idx + 1).to_string(),
+			},
+			Function::DeleteVariable(_) => "Delete".to_string(),
+			Function::ClearVars => "ClrV".to_string(),
+			Function::ConfirmClearVars => "Clear".to_string(),
+			Function::SetPrecision => state.context().format().precision.to_string() + " Dig",
+			Function::SetGrouping => state.context().format().grouping.to_string() + " Grp",
+			Function::ViewEntry => "View".to_string(),
+			Function::SetRadix => match state.context().format().integer_radix {
+				8 | 10 | 16 => "Base".to_string(),
+				radix => "▪B".to_string() + &radix.to_string(),
+			},
+			Function::Redo => "Redo".to_string(),
+			Function::StartRecording => {
+				if state.is_recording_macro() {
+					"▪Rec".to_string()
+				} else {
+					"Rec".to_string()
+				}
+			}
+			Function::StopRecording => "Stop".to_string(),
+			Function::Macro(_) => "Macro".to_string(),
+			Function::Solve => "Solve".to_string(),
+			Function::Integrate => "∫".to_string(),
+			Function::StatAdd => "Σ+".to_string(),
+			Function::StatRemove => "Σ-".to_string(),
+			Function::StatClear => "ClΣ".to_string(),
+			Function::StatN => "n".to_string(),
+			Function::LinReg => "LinR".to_string(),
+			Function::Hyperbolic => "hyp".to_string(),
 		}
 	}
 
@@ -116,6 +442,9 @@ impl Function {
 			Function::Stack(func) => {
 				state.end_edit()?;
 				func.execute(state.context_mut())?;
+				if func.computes_value() {
+					state.update_ans()?;
+				}
 			}
 			Function::Input(input) => {
 				state.handle_input(*input, screen)?;
@@ -130,6 +459,11 @@ impl Function {
 					.function_keys_mut()
 					.show_menu(FunctionMenu::UnsignedInteger);
 			}
+			Function::Hyperbolic => {
+				state
+					.function_keys_mut()
+					.show_menu(FunctionMenu::Hyperbolic);
+			}
 			Function::CatalogPage(page) => {
 				state.show_menu(page.menu(&|page| Function::CatalogPage(page), &|func| func))?;
 			}
@@ -191,6 +525,7 @@ impl Function {
 						Function::ConvertUnitCatalogMenu => {
 							Function::AssignConvertUnitCatalogMenu(*idx)
 						}
+						Function::UserConstantCatalog => Function::AssignUserConstantCatalog(*idx),
 						_ => Function::AssignCatalogFunction(*idx, Box::new(func)),
 					},
 				))?;
@@ -318,7 +653,8 @@ impl Function {
 			Function::StatusBarLeftDisplayToggle => {
 				state.set_status_bar_left_display(match state.status_bar_left_display() {
 					StatusBarLeftDisplayType::CurrentTime => StatusBarLeftDisplayType::FreeMemory,
-					StatusBarLeftDisplayType::FreeMemory => StatusBarLeftDisplayType::CurrentTime,
+					StatusBarLeftDisplayType::FreeMemory => StatusBarLeftDisplayType::StackDepth,
+					StatusBarLeftDisplayType::StackDepth => StatusBarLeftDisplayType::CurrentTime,
 				});
 			}
 			Function::StackLabelXYZToggle => {
@@ -350,7 +686,137 @@ impl Function {
 				};
 				state.context_mut().format_mut().alt_mode = value;
 			}
+			Function::KeepEditingOnErrorToggle => {
+				let value = !state.keep_editing_on_error();
+				state.set_keep_editing_on_error(value);
+			}
+			Function::EnterDuplicatesToggle => {
+				let value = !state.enter_duplicates();
+				state.set_enter_duplicates(value);
+			}
+			Function::StrictOperandsToggle => {
+				let value = !state.strict_operands();
+				state.set_strict_operands(value);
+			}
+			Function::SciHighThresholdToggle => {
+				// Cycle through the common thresholds, then back to following the precision
+				let value = match state.context().format().sci_high_threshold {
+					None => Some(SCI_HIGH_THRESHOLDS[0]),
+					Some(digits) => SCI_HIGH_THRESHOLDS
+						.iter()
+						.cloned()
+						.find(|threshold| *threshold > digits),
+				};
+				state.context_mut().format_mut().sci_high_threshold = value;
+			}
+			Function::SciLowThresholdToggle => {
+				let digits = state.context().format().sci_low_threshold;
+				let value = SCI_LOW_THRESHOLDS
+					.iter()
+					.cloned()
+					.find(|threshold| *threshold > digits)
+					.unwrap_or(SCI_LOW_THRESHOLDS[0]);
+				state.context_mut().format_mut().sci_low_threshold = value;
+			}
 			Function::NewMatrix => state.function_keys_mut().show_menu(FunctionMenu::NewMatrix),
+			Function::VariableCatalog => {
+				let menu = variable_catalog_menu(state)?;
+				state.show_menu(menu)?;
+			}
+			Function::RecallVariable(location) => {
+				state.end_edit()?;
+				let value = state.context().read(location)?;
+				state.context_mut().push(value)?;
+			}
+			Function::DeleteVariable(location) => {
+				state.context_mut().delete(location)?;
+
+				// Refresh the variable list, or leave it if there are no variables left
+				match variable_catalog_menu(state) {
+					Ok(menu) => state.replace_menu(menu),
+					Err(_) => state.exit_menu(),
+				}
+			}
+			Function::DefineConstant => state.prompt_define_constant()?,
+			Function::UserConstantCatalog => {
+				let menu = user_constant_catalog_menu(state, &|func| func)?;
+				state.show_menu(menu)?;
+			}
+			Function::AssignUserConstantCatalog(idx) => {
+				let menu = user_constant_catalog_menu(state, &|func| {
+					Function::AssignCatalogFunction(*idx, Box::new(func))
+				})?;
+				state.show_menu(menu)?;
+			}
+			Function::RecallConstant(name) => {
+				state.end_edit()?;
+				let value = state.user_constant(*name)?;
+				state.context_mut().push(value)?;
+			}
+			Function::DeleteConstant(name) => {
+				state.delete_user_constant(*name)?;
+
+				// Refresh the constant list, or leave it if there are no constants left
+				match user_constant_catalog_menu(state, &|func| func) {
+					Ok(menu) => state.replace_menu(menu),
+					Err(_) => state.exit_menu(),
+				}
+			}
+			Function::Compact => state.show_menu(compact_menu())?,
+			Function::ConfirmCompact => {
+				compact();
+			}
+			Function::StartupAngleModeToggle => {
+				let preferences = state.preferences_mut();
+				preferences.angle_mode = match preferences.angle_mode {
+					AngleUnit::Degrees => AngleUnit::Radians,
+					AngleUnit::Radians => AngleUnit::Gradians,
+					AngleUnit::Gradians => AngleUnit::Degrees,
+				};
+			}
+			Function::StartupFormatModeToggle => {
+				let preferences = state.preferences_mut();
+				preferences.format_mode = match preferences.format_mode {
+					FormatMode::Normal => FormatMode::Rational,
+					FormatMode::Rational => FormatMode::Scientific,
+					FormatMode::Scientific => FormatMode::Engineering,
+					FormatMode::Engineering => FormatMode::Normal,
+				};
+			}
+			Function::ClearVars => state.show_menu(clear_variables_menu())?,
+			Function::ConfirmClearVars => state.clear_all_variables(),
+			Function::SetPrecision => state.prompt_precision()?,
+			Function::SetGrouping => state.prompt_grouping()?,
+			Function::Random => state.random()?,
+			Function::RandomInt => state.random_int()?,
+			Function::SeedRandom => state.seed_random()?,
+			Function::Ans => state.push_ans()?,
+			Function::SetRadix => state.prompt_radix()?,
+			Function::ViewEntry => state.view_entry()?,
+			Function::ViewAllBases => state.view_all_bases()?,
+			Function::CharacterToCode => state.prompt_character_code()?,
+			Function::CodeToCharacter => state.view_character()?,
+			Function::Redo => {
+				// Ending the edit would push the number being entered, which discards the
+				// actions that can be redone. Cancel the edit instead.
+				state.cancel_edit();
+				state.redo()?;
+			}
+			Function::StartRecording => state.start_macro_recording(),
+			Function::StopRecording => {
+				let events = state.stop_macro_recording()?;
+				if events.len() != 0 {
+					state.show_menu(assign_function_menu(Function::Macro(events)))?;
+				}
+			}
+			Function::Macro(events) => state.run_macro(events, screen)?,
+			Function::Solve => state.prompt_solve()?,
+			Function::Integrate => state.prompt_integrate()?,
+			Function::StatAdd => state.stat_add()?,
+			Function::StatRemove => state.stat_remove()?,
+			Function::StatClear => state.stat_clear(),
+			Function::StatN => state.stat_count()?,
+			Function::LinReg => state.linear_regression()?,
 		}
 		Ok(())
 	}
@@ -361,6 +827,7 @@ pub enum FunctionMenu {
 	Custom,
 	Disp,
 	Mode,
+	Hyperbolic,
 	Base,
 	SignedInteger,
 	UnsignedInteger,
@@ -379,10 +846,20 @@ impl FunctionMenu {
 				Some(Function::Stack(StackFunction::RationalFormat)),
 				Some(Function::Stack(StackFunction::ScientificFormat)),
 				Some(Function::Stack(StackFunction::EngineeringFormat)),
+				Some(Function::SetPrecision),
+				Some(Function::Stack(StackFunction::SIPrefixes)),
 				Some(Function::Stack(StackFunction::AlternateHex)),
 				Some(Function::Stack(StackFunction::AlternateFloat)),
+				Some(Function::Stack(StackFunction::ComplexPolar)),
+				Some(Function::Stack(StackFunction::AlternateContinuedFraction)),
+				Some(Function::Stack(StackFunction::DurationFormat)),
+				Some(Function::Stack(StackFunction::PlusSign)),
+				Some(Function::Stack(StackFunction::TrailingZeros)),
+				Some(Function::Stack(StackFunction::SignificantFigures)),
+				Some(Function::Stack(StackFunction::BaseUnitsDisplay)),
 				Some(Function::Stack(StackFunction::ThousandsSeparatorOff)),
 				Some(Function::Stack(StackFunction::ThousandsSeparatorOn)),
+				Some(Function::SetGrouping),
 				Some(Function::Stack(StackFunction::DecimalPointPeriod)),
 				Some(Function::Stack(StackFunction::DecimalPointComma)),
 			]
@@ -391,6 +868,18 @@ impl FunctionMenu {
 				Some(Function::Stack(StackFunction::Degrees)),
 				Some(Function::Stack(StackFunction::Radians)),
 				Some(Function::Stack(StackFunction::Gradians)),
+				Some(Function::Stack(StackFunction::SymbolicAngles)),
+				Some(Function::Hyperbolic),
+				Some(Function::Stack(StackFunction::ExactOnly)),
+			]
+			.to_vec(),
+			FunctionMenu::Hyperbolic => [
+				Some(Function::Stack(StackFunction::Sinh)),
+				Some(Function::Stack(StackFunction::Cosh)),
+				Some(Function::Stack(StackFunction::Tanh)),
+				Some(Function::Stack(StackFunction::Asinh)),
+				Some(Function::Stack(StackFunction::Acosh)),
+				Some(Function::Stack(StackFunction::Atanh)),
 			]
 			.to_vec(),
 			FunctionMenu::Base => [
@@ -400,6 +889,13 @@ impl FunctionMenu {
 				Some(Function::Stack(StackFunction::Float)),
 				Some(Function::SignedInteger),
 				Some(Function::UnsignedInteger),
+				Some(Function::SetRadix),
+				Some(Function::Stack(StackFunction::SaturateIntegers)),
+				Some(Function::ViewAllBases),
+				Some(Function::CharacterToCode),
+				Some(Function::CodeToCharacter),
+				Some(Function::Stack(StackFunction::FloatToBits)),
+				Some(Function::Stack(StackFunction::BitsToFloat)),
 			]
 			.to_vec(),
 			FunctionMenu::SignedInteger => [
@@ -429,6 +925,11 @@ impl FunctionMenu {
 				Some(Function::Stack(StackFunction::ShiftRight)),
 				Some(Function::Stack(StackFunction::RotateLeft)),
 				Some(Function::Stack(StackFunction::RotateRight)),
+				Some(Function::Stack(StackFunction::PopCount)),
+				Some(Function::Stack(StackFunction::CountLeadingZeros)),
+				Some(Function::Stack(StackFunction::CountTrailingZeros)),
+				Some(Function::Stack(StackFunction::ByteSwap)),
+				Some(Function::Stack(StackFunction::BitReverse)),
 			]
 			.to_vec(),
 			FunctionMenu::Stats => [
@@ -439,6 +940,10 @@ impl FunctionMenu {
 			FunctionMenu::Matrix => [
 				Some(Function::NewMatrix),
 				Some(Function::Stack(StackFunction::Transpose)),
+				Some(Function::Stack(StackFunction::MatrixPow)),
+				Some(Function::Stack(StackFunction::Trace)),
+				Some(Function::Stack(StackFunction::RowSums)),
+				Some(Function::Stack(StackFunction::ColSums)),
 				Some(Function::Stack(StackFunction::DotProduct)),
 				Some(Function::Stack(StackFunction::CrossProduct)),
 				Some(Function::Stack(StackFunction::Magnitude)),
@@ -491,13 +996,13 @@ impl FunctionKeyState {
 
 	fn quick_functions(&self, format: &Format) -> Vec<Option<Function>> {
 		let mut result = Vec::new();
-		if format.integer_radix == 16 {
-			result.push(Some(Function::Input(InputEvent::Character('A'))));
-			result.push(Some(Function::Input(InputEvent::Character('B'))));
-			result.push(Some(Function::Input(InputEvent::Character('C'))));
-			result.push(Some(Function::Input(InputEvent::Character('D'))));
-			result.push(Some(Function::Input(InputEvent::Character('E'))));
-			result.push(Some(Function::Input(InputEvent::Character('F'))));
+		if format.integer_radix > 10 && format.integer_radix <= 16 {
+			// Provide keys for the letter digits available in the current radix. Larger
+			// radix values use alpha mode to enter digits.
+			for digit in 10..format.integer_radix {
+				let ch = core::char::from_u32('A' as u32 + digit as u32 - 10).unwrap();
+				result.push(Some(Function::Input(InputEvent::Character(ch))));
+			}
 		}
 		result.append(&mut self.quick_functions.clone());
 		result
@@ -595,6 +1100,39 @@ impl FunctionKeyState {
 		}
 	}
 
+	pub fn serialize_custom_functions<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		output.write_u32(self.custom_functions.len() as u32)?;
+		for func in &self.custom_functions {
+			match func {
+				Some(func) => {
+					output.write_u8(1)?;
+					func.serialize(output)?;
+				}
+				None => output.write_u8(0)?,
+			}
+		}
+		Ok(())
+	}
+
+	pub fn deserialize_custom_functions(
+		input: &mut DeserializeInput,
+	) -> Result<Vec<Option<Function>>> {
+		let count = input.read_u32()?;
+		let mut result = Vec::new();
+		for _ in 0..count {
+			match input.read_u8()? {
+				0 => result.push(None),
+				1 => result.push(Some(Function::deserialize(input)?)),
+				_ => return Err(Error::CorruptData),
+			}
+		}
+		Ok(result)
+	}
+
+	pub fn set_custom_functions(&mut self, funcs: Vec<Option<Function>>) {
+		self.custom_functions = funcs;
+	}
+
 	pub fn set_custom_function(&mut self, idx: usize, func: Option<Function>) {
 		if let Some(dest) = self.custom_functions.get_mut(idx) {
 			*dest = func;
@@ -1,3 +1,6 @@
+use rscalc_math::error::{Error, Result};
+use rscalc_math::storage::{DeserializeInput, SerializeOutput};
+
 #[cfg(feature = "dm42")]
 use alloc::string::{String, ToString};
 
@@ -139,6 +142,153 @@ pub enum InputEvent {
 }
 
 impl InputEvent {
+	pub fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		match self {
+			InputEvent::Character(ch) => {
+				output.write_u8(0)?;
+				output.write_u32(*ch as u32)?;
+			}
+			InputEvent::FunctionKey(idx, shift) => {
+				output.write_u8(1)?;
+				output.write_u8(*idx)?;
+				output.write_u8(*shift as u8)?;
+			}
+			InputEvent::SigmaPlus => output.write_u8(2)?,
+			InputEvent::SigmaMinus => output.write_u8(3)?,
+			InputEvent::Recip => output.write_u8(4)?,
+			InputEvent::Pow => output.write_u8(5)?,
+			InputEvent::Sqrt => output.write_u8(6)?,
+			InputEvent::Square => output.write_u8(7)?,
+			InputEvent::Log => output.write_u8(8)?,
+			InputEvent::TenX => output.write_u8(9)?,
+			InputEvent::Ln => output.write_u8(10)?,
+			InputEvent::EX => output.write_u8(11)?,
+			InputEvent::Xeq => output.write_u8(12)?,
+			InputEvent::Gto => output.write_u8(13)?,
+			InputEvent::Sto => output.write_u8(14)?,
+			InputEvent::Complex => output.write_u8(15)?,
+			InputEvent::Rcl => output.write_u8(16)?,
+			InputEvent::Percent => output.write_u8(17)?,
+			InputEvent::RotateDown => output.write_u8(18)?,
+			InputEvent::Pi => output.write_u8(19)?,
+			InputEvent::Sin => output.write_u8(20)?,
+			InputEvent::Asin => output.write_u8(21)?,
+			InputEvent::Cos => output.write_u8(22)?,
+			InputEvent::Acos => output.write_u8(23)?,
+			InputEvent::Tan => output.write_u8(24)?,
+			InputEvent::Atan => output.write_u8(25)?,
+			InputEvent::Enter => output.write_u8(26)?,
+			InputEvent::Swap => output.write_u8(27)?,
+			InputEvent::Undo => output.write_u8(28)?,
+			InputEvent::Neg => output.write_u8(29)?,
+			InputEvent::Modes => output.write_u8(30)?,
+			InputEvent::E => output.write_u8(31)?,
+			InputEvent::Disp => output.write_u8(32)?,
+			InputEvent::Backspace => output.write_u8(33)?,
+			InputEvent::Clear => output.write_u8(34)?,
+			InputEvent::Up => output.write_u8(35)?,
+			InputEvent::ShiftUp => output.write_u8(36)?,
+			InputEvent::Solver => output.write_u8(37)?,
+			InputEvent::Integrate => output.write_u8(38)?,
+			InputEvent::Matrix => output.write_u8(39)?,
+			InputEvent::Div => output.write_u8(40)?,
+			InputEvent::Stat => output.write_u8(41)?,
+			InputEvent::Down => output.write_u8(42)?,
+			InputEvent::ShiftDown => output.write_u8(43)?,
+			InputEvent::Base => output.write_u8(44)?,
+			InputEvent::Convert => output.write_u8(45)?,
+			InputEvent::Logic => output.write_u8(46)?,
+			InputEvent::Mul => output.write_u8(47)?,
+			InputEvent::Prob => output.write_u8(48)?,
+			InputEvent::Assign => output.write_u8(49)?,
+			InputEvent::Custom => output.write_u8(50)?,
+			InputEvent::ProgramFunc => output.write_u8(51)?,
+			InputEvent::Sub => output.write_u8(52)?,
+			InputEvent::Print => output.write_u8(53)?,
+			InputEvent::Exit => output.write_u8(54)?,
+			InputEvent::Off => output.write_u8(55)?,
+			InputEvent::Setup => output.write_u8(56)?,
+			InputEvent::Show => output.write_u8(57)?,
+			InputEvent::Run => output.write_u8(58)?,
+			InputEvent::Program => output.write_u8(59)?,
+			InputEvent::Add => output.write_u8(60)?,
+			InputEvent::Catalog => output.write_u8(61)?,
+			InputEvent::Screenshot => output.write_u8(62)?,
+		}
+		Ok(())
+	}
+
+	pub fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		Ok(match input.read_u8()? {
+			0 => InputEvent::Character(
+				core::char::from_u32(input.read_u32()?).ok_or(Error::CorruptData)?,
+			),
+			1 => InputEvent::FunctionKey(input.read_u8()?, input.read_u8()? != 0),
+			2 => InputEvent::SigmaPlus,
+			3 => InputEvent::SigmaMinus,
+			4 => InputEvent::Recip,
+			5 => InputEvent::Pow,
+			6 => InputEvent::Sqrt,
+			7 => InputEvent::Square,
+			8 => InputEvent::Log,
+			9 => InputEvent::TenX,
+			10 => InputEvent::Ln,
+			11 => InputEvent::EX,
+			12 => InputEvent::Xeq,
+			13 => InputEvent::Gto,
+			14 => InputEvent::Sto,
+			15 => InputEvent::Complex,
+			16 => InputEvent::Rcl,
+			17 => InputEvent::Percent,
+			18 => InputEvent::RotateDown,
+			19 => InputEvent::Pi,
+			20 => InputEvent::Sin,
+			21 => InputEvent::Asin,
+			22 => InputEvent::Cos,
+			23 => InputEvent::Acos,
+			24 => InputEvent::Tan,
+			25 => InputEvent::Atan,
+			26 => InputEvent::Enter,
+			27 => InputEvent::Swap,
+			28 => InputEvent::Undo,
+			29 => InputEvent::Neg,
+			30 => InputEvent::Modes,
+			31 => InputEvent::E,
+			32 => InputEvent::Disp,
+			33 => InputEvent::Backspace,
+			34 => InputEvent::Clear,
+			35 => InputEvent::Up,
+			36 => InputEvent::ShiftUp,
+			37 => InputEvent::Solver,
+			38 => InputEvent::Integrate,
+			39 => InputEvent::Matrix,
+			40 => InputEvent::Div,
+			41 => InputEvent::Stat,
+			42 => InputEvent::Down,
+			43 => InputEvent::ShiftDown,
+			44 => InputEvent::Base,
+			45 => InputEvent::Convert,
+			46 => InputEvent::Logic,
+			47 => InputEvent::Mul,
+			48 => InputEvent::Prob,
+			49 => InputEvent::Assign,
+			50 => InputEvent::Custom,
+			51 => InputEvent::ProgramFunc,
+			52 => InputEvent::Sub,
+			53 => InputEvent::Print,
+			54 => InputEvent::Exit,
+			55 => InputEvent::Off,
+			56 => InputEvent::Setup,
+			57 => InputEvent::Show,
+			58 => InputEvent::Run,
+			59 => InputEvent::Program,
+			60 => InputEvent::Add,
+			61 => InputEvent::Catalog,
+			62 => InputEvent::Screenshot,
+			_ => return Err(Error::CorruptData),
+		})
+	}
+
 	pub fn to_string(&self) -> String {
 		match self {
 			InputEvent::Character(ch) => {
@@ -816,4 +966,8 @@ pub trait InputQueue {
 	}
 
 	fn suspend(&self);
+
+	/// Returns calculator state saved by `save_state`, if there is any.
+	fn load_state(&self) -> Option<Vec<u8>>;
+	fn save_state(&self, data: &[u8]);
 }
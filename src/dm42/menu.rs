@@ -4,9 +4,10 @@ use crate::dm42::state::{State, StatusBarLeftDisplayType};
 use core::cell::RefCell;
 use rscalc_layout::font::Font;
 use rscalc_layout::layout::{Layout, LayoutRenderer, Rect, TokenType};
-use rscalc_math::format::AlternateFormatMode;
+use rscalc_math::format::{AlternateFormatMode, FormatMode};
 use rscalc_math::number::Number;
-use rscalc_math::storage::{available_bytes, free_bytes, reclaimable_bytes, used_bytes};
+use rscalc_math::storage::{available_bytes, free_bytes, reclaimable_bytes, stats, used_bytes};
+use rscalc_math::unit::AngleUnit;
 
 #[cfg(not(feature = "dm42"))]
 use std::borrow::Cow;
@@ -23,6 +24,7 @@ use alloc::vec::Vec;
 #[derive(PartialEq, Eq, Clone)]
 pub enum MenuItemFunction {
 	Action(Function),
+	ActionWithDelete(Function, Function),
 	InMenuAction(Function),
 	InMenuActionWithDelete(Function, Function),
 	ConversionAction(Function, Function, Function),
@@ -286,6 +288,48 @@ impl Menu {
 	}
 }
 
+pub fn clear_variables_menu() -> Menu {
+	let mut items = Vec::new();
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Yes, clear all variables")),
+		function: MenuItemFunction::Action(Function::ConfirmClearVars),
+	});
+
+	Menu::new_with_bottom(
+		"Clear All Variables?",
+		items,
+		Box::new(|_state, _screen| {
+			Layout::LeftAlign(Box::new(Layout::Text(
+				"Press EXIT to cancel".to_string(),
+				Font::Small,
+				TokenType::Text,
+			)))
+		}),
+	)
+}
+
+pub fn compact_menu() -> Menu {
+	let mut items = Vec::new();
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout(
+			"Yes, clear undo and redo history",
+		)),
+		function: MenuItemFunction::Action(Function::ConfirmCompact),
+	});
+
+	Menu::new_with_bottom(
+		"Reclaim Memory?",
+		items,
+		Box::new(|_state, _screen| {
+			Layout::LeftAlign(Box::new(Layout::Text(
+				"Press EXIT to cancel".to_string(),
+				Font::Small,
+				TokenType::Text,
+			)))
+		}),
+	)
+}
+
 pub fn setup_menu() -> Menu {
 	let mut items = Vec::new();
 
@@ -295,6 +339,16 @@ pub fn setup_menu() -> Menu {
 		function: MenuItemFunction::InMenuAction(Function::SettingsMenu),
 	});
 
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Clear All Variables >")),
+		function: MenuItemFunction::InMenuAction(Function::ClearVars),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Reclaim Memory >")),
+		function: MenuItemFunction::InMenuAction(Function::Compact),
+	});
+
 	#[cfg(feature = "dm42")]
 	items.push(MenuItem {
 		layout: MenuItemLayout::Static(MenuItem::static_string_layout("System Settings >")),
@@ -343,6 +397,13 @@ pub fn setup_menu() -> Menu {
 				legend_items,
 			))));
 
+			// Add number of objects in storage
+			bottom_items.push(Layout::LeftAlign(Box::new(Layout::Text(
+				Number::Integer(stats().objects.into()).to_string() + " objects stored",
+				Font::Smallest,
+				TokenType::Text,
+			))));
+
 			// Add temporary memory available
 			#[cfg(feature = "dm42")]
 			bottom_items.push(Layout::LeftAlign(Box::new(Layout::Text(
@@ -368,6 +429,7 @@ pub fn settings_menu() -> Menu {
 					+ match state.status_bar_left_display() {
 						StatusBarLeftDisplayType::CurrentTime => "[Current Time]",
 						StatusBarLeftDisplayType::FreeMemory => "[Free Memory]",
+						StatusBarLeftDisplayType::StackDepth => "[Stack Depth]",
 					},
 			)
 		})),
@@ -459,6 +521,101 @@ pub fn settings_menu() -> Menu {
 		function: MenuItemFunction::InMenuAction(Function::AlternateFormatModeToggle),
 	});
 
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Keep Input on Error   ".to_string()
+					+ if state.keep_editing_on_error() {
+						"[On]"
+					} else {
+						"[Off]"
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::KeepEditingOnErrorToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Enter Duplicates   ".to_string()
+					+ if state.enter_duplicates() {
+						"[On]"
+					} else {
+						"[Off]"
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::EnterDuplicatesToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Strict Operands   ".to_string()
+					+ if state.strict_operands() {
+						"[On]"
+					} else {
+						"[Off]"
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::StrictOperandsToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Scientific Above   ".to_string()
+					+ &match state.context().format().sci_high_threshold {
+						Some(digits) => "[".to_string() + &digits.to_string() + " Digits]",
+						None => "[Precision]".to_string(),
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::SciHighThresholdToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Scientific Below   [".to_string()
+					+ &state.context().format().sci_low_threshold.to_string()
+					+ " Zeros]",
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::SciLowThresholdToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Startup Angle   ".to_string()
+					+ match state.preferences().angle_mode {
+						AngleUnit::Degrees => "[Degrees]",
+						AngleUnit::Radians => "[Radians]",
+						AngleUnit::Gradians => "[Gradians]",
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::StartupAngleModeToggle),
+	});
+
+	items.push(MenuItem {
+		layout: MenuItemLayout::Dynamic(Box::new(|state, _screen| {
+			MenuItem::string_layout(
+				"Startup Format   ".to_string()
+					+ match state.preferences().format_mode {
+						FormatMode::Normal => "[Normal]",
+						FormatMode::Rational => "[Rational]",
+						FormatMode::Scientific => "[Scientific]",
+						FormatMode::Engineering => "[Engineering]",
+					},
+			)
+		})),
+		function: MenuItemFunction::InMenuAction(Function::StartupFormatModeToggle),
+	});
+
 	// Return the menu object
 	Menu::new("Settings", items)
 }
@@ -5,6 +5,7 @@ use gdk_pixbuf::{Colorspace, Pixbuf};
 use glib::source::{timeout_add_local, Continue};
 use gtk::*;
 use rscalc_layout::layout::Rect;
+use std::path::PathBuf;
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -326,4 +327,21 @@ impl InputQueue for VirtualInputQueue {
 	}
 
 	fn suspend(&self) {}
+
+	fn load_state(&self) -> Option<Vec<u8>> {
+		std::fs::read(state_path()).ok()
+	}
+
+	fn save_state(&self, data: &[u8]) {
+		if let Err(error) = std::fs::write(state_path(), data) {
+			eprintln!("failed to save calculator state: {}", error);
+		}
+	}
+}
+
+fn state_path() -> PathBuf {
+	match std::env::var_os("HOME") {
+		Some(home) => PathBuf::from(home).join(".rscalc_state"),
+		None => PathBuf::from("rscalc_state"),
+	}
 }
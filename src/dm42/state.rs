@@ -1,11 +1,14 @@
-use crate::dm42::catalog::{assign_menu, catalog_menu};
+use crate::dm42::catalog::{assign_menu, catalog_menu, variable_catalog_menu};
 use crate::dm42::edit::NumberEditor;
 use crate::dm42::functions::{Function, FunctionKeyState, FunctionMenu};
 use crate::dm42::input::{AlphaMode, InputEvent, InputMode, InputQueue};
 use crate::dm42::menu::{setup_menu, Menu, MenuItemFunction};
 use crate::dm42::screen::{RenderMode, Screen};
 use crate::dm42::unit::unit_menu;
+use crate::dm42::viewer::Viewer;
 use chrono::NaiveDateTime;
+use core::convert::{TryFrom, TryInto};
+use num_bigint::BigInt;
 use rscalc_layout::decimal::DecimalLayout;
 use rscalc_layout::font::Font;
 use rscalc_layout::layout::{Layout, LayoutRenderer, Rect, TokenType};
@@ -15,16 +18,25 @@ use rscalc_layout::value::{AlternateLayoutType, ValueLayout};
 use rscalc_math::constant::Constant;
 use rscalc_math::context::{Context, Location};
 use rscalc_math::error::{Error, Result};
-use rscalc_math::format::{Format, IntegerMode};
-use rscalc_math::number::ToNumber;
-use rscalc_math::storage::available_bytes;
+use rscalc_math::format::{Format, FormatMode, FormatResult, IntegerMode, MAX_PRECISION};
+use rscalc_math::number::{Number, ToNumber};
+use rscalc_math::random::Random;
+use rscalc_math::solver;
+use rscalc_math::stats;
+use rscalc_math::storage::{
+	available_bytes, deserialize_inline, serialize_inline, store, DeserializeInput, SerializeBytes,
+	SerializeOutput,
+};
 use rscalc_math::time::{Now, SimpleDateTimeFormat, SimpleDateTimeToString};
-use rscalc_math::unit::AngleUnit;
-use rscalc_math::value::Value;
+use rscalc_math::unit::{AngleUnit, Unit};
+use rscalc_math::value::{Value, ValueRef};
+use rscalc_math::vector::Vector;
 
 #[cfg(not(feature = "dm42"))]
 use std::cell::RefCell;
 #[cfg(not(feature = "dm42"))]
+use std::collections::BTreeMap;
+#[cfg(not(feature = "dm42"))]
 use std::rc::Rc;
 
 #[cfg(feature = "dm42")]
@@ -32,6 +44,8 @@ use crate::dm42::device::{read_power_voltage, show_system_setup_menu, usb_powere
 #[cfg(feature = "dm42")]
 use alloc::boxed::Box;
 #[cfg(feature = "dm42")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "dm42")]
 use alloc::rc::Rc;
 #[cfg(feature = "dm42")]
 use alloc::string::{String, ToString};
@@ -41,6 +55,13 @@ use alloc::vec::Vec;
 use core::cell::RefCell;
 
 const MAX_MEMORY_INDEX_DIGITS: usize = 2;
+const MAX_SETTING_DIGITS: usize = 2;
+const MAX_MACRO_DEPTH: usize = 8;
+
+// Header for serialized calculator state. The version must be incremented whenever the
+// layout of the serialized data changes.
+const STATE_SERIALIZE_MAGIC: u32 = 0x53435352;
+const STATE_SERIALIZE_VERSION: u32 = 17;
 
 /// Cached state for rendering the status bar. This is used to optimize the rendering
 /// of the status bar such that it is only drawn when it is updated.
@@ -54,6 +75,13 @@ struct CachedStatusBarState {
 	left_string: String,
 }
 
+/// Point in the statistics dataset. Single variable data only has an `x` value, and
+/// two variable data is entered as a vector of `[x y]`.
+struct StatPoint {
+	x: Number,
+	y: Option<Number>,
+}
+
 #[derive(Clone)]
 struct LocationEntryState {
 	name: &'static str,
@@ -67,17 +95,35 @@ enum InputState {
 	NumberInput,
 	Recall,
 	Store,
+	Precision,
+	Radix,
+	Grouping,
+	Solve,
+	Integrate,
+	DefineConstant,
+	CharacterCode,
 	Menu,
+	View,
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum StatusBarLeftDisplayType {
 	CurrentTime,
 	FreeMemory,
+	StackDepth,
+}
+
+/// Modes that the calculator starts in. These are saved with the state, but are only
+/// changed from the settings menu and not by switching modes during normal use.
+#[derive(Clone, Copy)]
+pub struct Preferences {
+	pub angle_mode: AngleUnit,
+	pub format_mode: FormatMode,
 }
 
 pub struct State {
 	context: Context,
+	preferences: Preferences,
 	input_mode: InputMode,
 	function_keys: FunctionKeyState,
 	status_bar_left_display: StatusBarLeftDisplayType,
@@ -85,6 +131,7 @@ pub struct State {
 	location_entry: LocationEntryState,
 	error: Option<Error>,
 	menus: Vec<Menu>,
+	viewer: Option<Viewer>,
 	editor: Option<NumberEditor>,
 	status_bar_enabled: bool,
 	base_font: Font,
@@ -92,6 +139,17 @@ pub struct State {
 	cached_status_bar_state: CachedStatusBarState,
 	force_refresh: bool,
 	force_render_on_status_update: bool,
+	macro_recording: Option<Vec<InputEvent>>,
+	macro_menu_start: Option<usize>,
+	last_macro: Option<Vec<InputEvent>>,
+	macro_depth: usize,
+	stat_data: Vec<StatPoint>,
+	keep_editing_on_error: bool,
+	random: Random,
+	ans: Option<ValueRef>,
+	enter_duplicates: bool,
+	strict_operands: bool,
+	user_constants: BTreeMap<char, ValueRef>,
 }
 
 pub enum InputResult {
@@ -125,6 +183,45 @@ impl LocationEntryState {
 	}
 }
 
+impl Preferences {
+	pub fn new() -> Self {
+		Preferences {
+			angle_mode: AngleUnit::Degrees,
+			format_mode: FormatMode::Rational,
+		}
+	}
+
+	/// Reads the preferences from state saved with `State::serialize` without restoring
+	/// the rest of the state.
+	pub fn from_saved_state(data: &[u8]) -> Result<Self> {
+		let mut input = DeserializeInput::new(data);
+		if input.read_u32()? != STATE_SERIALIZE_MAGIC
+			|| input.read_u32()? != STATE_SERIALIZE_VERSION
+		{
+			return Err(Error::CorruptData);
+		}
+		Self::deserialize(&mut input)
+	}
+
+	fn serialize<Out: SerializeOutput>(&self, output: &mut Out) -> Result<()> {
+		output.write_u16(Unit::Angle(self.angle_mode).to_u16())?;
+		output.write_u8(self.format_mode.to_u8())?;
+		Ok(())
+	}
+
+	fn deserialize(input: &mut DeserializeInput) -> Result<Self> {
+		let angle_mode = match Unit::from_u16(input.read_u16()?) {
+			Some(Unit::Angle(angle_mode)) => angle_mode,
+			_ => return Err(Error::CorruptData),
+		};
+		let format_mode = FormatMode::from_u8(input.read_u8()?).ok_or(Error::CorruptData)?;
+		Ok(Preferences {
+			angle_mode,
+			format_mode,
+		})
+	}
+}
+
 #[cfg(not(feature = "dm42"))]
 fn clock_minute_updated() -> bool {
 	true
@@ -137,7 +234,14 @@ fn clock_minute_updated() -> bool {
 
 impl State {
 	pub fn new() -> Self {
+		Self::new_with_preferences(Preferences::new())
+	}
+
+	/// Creates the calculator state, starting in the modes given by the preferences.
+	pub fn new_with_preferences(preferences: Preferences) -> Self {
 		let mut context = Context::new_with_undo();
+		context.set_angle_mode(preferences.angle_mode);
+		context.set_format_mode(preferences.format_mode);
 		let stack_renderer = StackRenderer::new(context.stack_mut());
 
 		let input_mode = InputMode {
@@ -157,6 +261,7 @@ impl State {
 
 		State {
 			context,
+			preferences,
 			input_mode,
 			function_keys: FunctionKeyState::new(),
 			status_bar_left_display: StatusBarLeftDisplayType::CurrentTime,
@@ -164,6 +269,7 @@ impl State {
 			location_entry: LocationEntryState::new(""),
 			error: None,
 			menus: Vec::new(),
+			viewer: None,
 			editor: None,
 			status_bar_enabled: true,
 			base_font: Font::Large,
@@ -171,9 +277,135 @@ impl State {
 			cached_status_bar_state,
 			force_refresh: true,
 			force_render_on_status_update: false,
+			macro_recording: None,
+			macro_menu_start: None,
+			last_macro: None,
+			macro_depth: 0,
+			stat_data: Vec::new(),
+			keep_editing_on_error: false,
+			random: Random::new(State::clock_seed()),
+			ans: None,
+			enter_duplicates: true,
+			strict_operands: true,
+			user_constants: BTreeMap::new(),
 		}
 	}
 
+	/// Saves the preferences, stack, variables, user constants, display and entry settings,
+	/// statistics data, ANS, the last macro, and custom function assignments into a self
+	/// contained buffer that can be restored with `deserialize`.
+	pub fn serialize(&self) -> Result<Vec<u8>> {
+		let mut output = SerializeBytes::new();
+		output.write_u32(STATE_SERIALIZE_MAGIC)?;
+		output.write_u32(STATE_SERIALIZE_VERSION)?;
+		self.preferences.serialize(&mut output)?;
+		self.function_keys.serialize_custom_functions(&mut output)?;
+		output.write_u32(self.user_constants.len() as u32)?;
+		for (name, value) in &self.user_constants {
+			output.write_u32(*name as u32)?;
+			serialize_inline(&value.get()?, &mut output)?;
+		}
+		output.write_u8(self.enter_duplicates as u8)?;
+		output.write_u8(self.keep_editing_on_error as u8)?;
+		output.write_u8(self.strict_operands as u8)?;
+		output.write_u32(self.stat_data.len() as u32)?;
+		for point in &self.stat_data {
+			serialize_inline(&point.x, &mut output)?;
+			match &point.y {
+				Some(y) => {
+					output.write_u8(1)?;
+					serialize_inline(y, &mut output)?;
+				}
+				None => output.write_u8(0)?,
+			}
+		}
+		match &self.ans {
+			Some(ans) => {
+				output.write_u8(1)?;
+				serialize_inline(&ans.get()?, &mut output)?;
+			}
+			None => output.write_u8(0)?,
+		}
+		match &self.last_macro {
+			Some(events) => {
+				output.write_u8(1)?;
+				output.write_u32(events.len() as u32)?;
+				for event in events {
+					event.serialize(&mut output)?;
+				}
+			}
+			None => output.write_u8(0)?,
+		}
+		self.context.serialize(&mut output)?;
+		Ok(output.into_bytes())
+	}
+
+	/// Restores state that was saved with `serialize`. If the data is invalid the current
+	/// state is left unchanged.
+	pub fn deserialize(&mut self, data: &[u8]) -> Result<()> {
+		let mut input = DeserializeInput::new(data);
+		if input.read_u32()? != STATE_SERIALIZE_MAGIC
+			|| input.read_u32()? != STATE_SERIALIZE_VERSION
+		{
+			return Err(Error::CorruptData);
+		}
+		let preferences = Preferences::deserialize(&mut input)?;
+		let custom_functions = FunctionKeyState::deserialize_custom_functions(&mut input)?;
+		let count = input.read_u32()?;
+		let mut user_constants = BTreeMap::new();
+		for _ in 0..count {
+			let name = core::char::from_u32(input.read_u32()?).ok_or(Error::CorruptData)?;
+			let value = store(deserialize_inline::<Value>(&mut input)?)?;
+			user_constants.insert(name, value);
+		}
+		let enter_duplicates = input.read_u8()? != 0;
+		let keep_editing_on_error = input.read_u8()? != 0;
+		let strict_operands = input.read_u8()? != 0;
+		let count = input.read_u32()?;
+		let mut stat_data = Vec::new();
+		for _ in 0..count {
+			let x = deserialize_inline::<Number>(&mut input)?;
+			let y = match input.read_u8()? {
+				0 => None,
+				_ => Some(deserialize_inline::<Number>(&mut input)?),
+			};
+			stat_data.push(StatPoint { x, y });
+		}
+		let ans = match input.read_u8()? {
+			0 => None,
+			_ => Some(store(deserialize_inline::<Value>(&mut input)?)?),
+		};
+		let last_macro = match input.read_u8()? {
+			0 => None,
+			_ => {
+				let count = input.read_u32()?;
+				let mut events = Vec::new();
+				for _ in 0..count {
+					events.push(InputEvent::deserialize(&mut input)?);
+				}
+				Some(events)
+			}
+		};
+		self.context.deserialize_into(&mut input)?;
+		self.preferences = preferences;
+		self.function_keys.set_custom_functions(custom_functions);
+		self.user_constants = user_constants;
+		self.enter_duplicates = enter_duplicates;
+		self.keep_editing_on_error = keep_editing_on_error;
+		self.strict_operands = strict_operands;
+		self.stat_data = stat_data;
+		self.ans = ans;
+		self.last_macro = last_macro;
+
+		self.editor = None;
+		self.menus.clear();
+		self.viewer = None;
+		self.input_state = InputState::Normal;
+		self.force_refresh = true;
+		self.stack_renderer.borrow_mut().invalidate_rendering();
+		Ok(())
+	}
+
 	pub fn context(&self) -> &Context {
 		&self.context
 	}
@@ -215,6 +447,38 @@ impl State {
 		self.force_refresh = true;
 	}
 
+	pub fn keep_editing_on_error(&self) -> bool {
+		self.keep_editing_on_error
+	}
+
+	pub fn set_keep_editing_on_error(&mut self, value: bool) {
+		self.keep_editing_on_error = value;
+	}
+
+	pub fn preferences(&self) -> &Preferences {
+		&self.preferences
+	}
+
+	pub fn preferences_mut(&mut self) -> &mut Preferences {
+		&mut self.preferences
+	}
+
+	pub fn enter_duplicates(&self) -> bool {
+		self.enter_duplicates
+	}
+
+	pub fn set_enter_duplicates(&mut self, value: bool) {
+		self.enter_duplicates = value;
+	}
+
+	pub fn strict_operands(&self) -> bool {
+		self.strict_operands
+	}
+
+	pub fn set_strict_operands(&mut self, value: bool) {
+		self.strict_operands = value;
+	}
+
 	pub fn base_font(&self) -> Font {
 		self.base_font
 	}
@@ -227,7 +491,9 @@ impl State {
 
 	pub fn show_error(&mut self, error: Error) {
 		self.error = Some(error);
-		self.input_state = InputState::Normal;
+		if !self.keep_editing_on_error || self.editor.is_none() {
+			self.input_state = InputState::Normal;
+		}
 		self.input_mode.alpha = AlphaMode::Normal;
 	}
 
@@ -246,17 +512,82 @@ impl State {
 		self.context.undo()
 	}
 
+	pub fn redo(&mut self) -> Result<()> {
+		self.context.redo()
+	}
+
 	pub fn end_edit(&mut self) -> Result<()> {
 		if let Some(editor) = &self.editor {
-			let value = editor.number();
+			let value = editor.value()?;
 			self.editor = None;
 			self.input_state = InputState::Normal;
-			self.context.push(Value::Number(value))?;
+			self.context.input_value(value)?;
 		}
 		self.input_mode.alpha = AlphaMode::Normal;
 		Ok(())
 	}
 
+	pub fn cancel_edit(&mut self) {
+		if self.editor.is_some() {
+			self.editor = None;
+			self.input_state = InputState::Normal;
+		}
+		self.input_mode.alpha = AlphaMode::Normal;
+	}
+
+	/// Remembers the value on top of the stack as the last result, so that it can be
+	/// recalled with `Ans` even after the stack has been cleared.
+	pub fn update_ans(&mut self) -> Result<()> {
+		if let Ok(value) = self.context.top() {
+			self.ans = Some(store(value)?);
+		}
+		Ok(())
+	}
+
+	pub fn push_ans(&mut self) -> Result<()> {
+		self.end_edit()?;
+		let value = self.ans.as_ref().ok_or(Error::ValueNotDefined)?.get()?;
+		self.context.push(value)
+	}
+
+	fn is_calculation(input: InputEvent) -> bool {
+		match input {
+			InputEvent::Add
+			| InputEvent::Sub
+			| InputEvent::Mul
+			| InputEvent::Div
+			| InputEvent::Recip
+			| InputEvent::Pow
+			| InputEvent::Sqrt
+			| InputEvent::Square
+			| InputEvent::Log
+			| InputEvent::TenX
+			| InputEvent::Ln
+			| InputEvent::EX
+			| InputEvent::Percent
+			| InputEvent::Sin
+			| InputEvent::Cos
+			| InputEvent::Tan
+			| InputEvent::Asin
+			| InputEvent::Acos
+			| InputEvent::Atan
+			| InputEvent::Complex
+			| InputEvent::SigmaPlus => true,
+			_ => false,
+		}
+	}
+
+	/// When strict operands are off, a binary operator with a single value on the stack
+	/// takes zero as the missing operand, as if the stack were filled with zeros.
+	fn fill_missing_operand(&mut self) -> Result<()> {
+		if !self.strict_operands && self.context.stack_len() == 1 {
+			let x = self.context.top()?;
+			self.context
+				.replace_top_with_multiple(vec![store(Value::Number(0.into()))?, store(x)?])?;
+		}
+		Ok(())
+	}
+
 	fn handle_common_input(
 		&mut self,
 		input: InputEvent,
@@ -265,18 +596,22 @@ impl State {
 		match input {
 			InputEvent::Add => {
 				self.end_edit()?;
+				self.fill_missing_operand()?;
 				self.context.add()?;
 			}
 			InputEvent::Sub => {
 				self.end_edit()?;
+				self.fill_missing_operand()?;
 				self.context.sub()?;
 			}
 			InputEvent::Mul => {
 				self.end_edit()?;
+				self.fill_missing_operand()?;
 				self.context.mul()?;
 			}
 			InputEvent::Div => {
 				self.end_edit()?;
+				self.fill_missing_operand()?;
 				self.context.div()?;
 			}
 			InputEvent::Recip => {
@@ -285,6 +620,7 @@ impl State {
 			}
 			InputEvent::Pow => {
 				self.end_edit()?;
+				self.fill_missing_operand()?;
 				self.context.pow()?;
 			}
 			InputEvent::Sqrt => {
@@ -361,6 +697,8 @@ impl State {
 				self.input_state = InputState::Store;
 				self.location_entry = LocationEntryState::new("Sto");
 			}
+			InputEvent::Solver => self.prompt_solve()?,
+			InputEvent::Integrate => self.prompt_integrate()?,
 			InputEvent::Complex => {
 				self.end_edit()?;
 				self.context.complex()?;
@@ -438,6 +776,10 @@ impl State {
 			}
 			_ => (),
 		}
+
+		if State::is_calculation(input) {
+			self.update_ans()?;
+		}
 		Ok(InputResult::Normal)
 	}
 
@@ -454,7 +796,11 @@ impl State {
 			}
 			InputEvent::Enter => {
 				self.end_edit()?;
-				self.context.push(self.context.top()?)?;
+				// When duplication is turned off, Enter only completes number entry, which
+				// is handled in the number input state.
+				if self.enter_duplicates {
+					self.context.push(self.context.top()?)?;
+				}
 			}
 			InputEvent::Backspace => {
 				self.end_edit()?;
@@ -487,6 +833,13 @@ impl State {
 
 		match input {
 			InputEvent::Character(ch) => match ch {
+				'i' | 'I'
+					if self.context.format().integer_mode == IntegerMode::Float
+						&& self.context.format().integer_radix == 10 =>
+				{
+					editor.imaginary();
+					self.input_mode.alpha = AlphaMode::Normal;
+				}
 				'0'..='9' | 'A'..='Z' | 'a'..='z' | '.' => {
 					if ch != '.' || self.context.format().integer_mode == IntegerMode::Float {
 						editor.push_char(ch)?;
@@ -516,12 +869,39 @@ impl State {
 				self.editor = None;
 				self.input_state = InputState::Normal;
 			}
-			_ => return self.handle_common_input(input, screen),
+			_ => {
+				if !self.keep_editing_on_error {
+					return self.handle_common_input(input, screen);
+				}
+
+				// If the operation fails, put the stack back the way it was and keep the
+				// number being edited so that it can be corrected without retyping it.
+				let editor = editor.clone();
+				let snapshot = self.context.stack().snapshot();
+				let result = self.handle_common_input(input, screen);
+				if result.is_err() {
+					if self.editor.is_none() {
+						// The edited number was pushed onto the stack, remove it again
+						self.context.stack_mut().discard_undo_action();
+					}
+					self.context.stack_mut().restore_snapshot(snapshot);
+					self.editor = Some(editor);
+					self.input_state = InputState::NumberInput;
+				}
+				return result;
+			}
 		}
 		Ok(InputResult::Normal)
 	}
 
 	fn handle_recall_input(&mut self, input: InputEvent) -> Result<InputResult> {
+		if input == InputEvent::Catalog {
+			// Show the list of stored variables to recall from
+			self.input_state = InputState::Normal;
+			self.show_menu(variable_catalog_menu(self)?)?;
+			return Ok(InputResult::Normal);
+		}
+
 		match self.handle_location_input(input) {
 			LocationInputResult::Intermediate(result) => Ok(result),
 			LocationInputResult::Finished(location) => {
@@ -563,6 +943,84 @@ impl State {
 		}
 	}
 
+	fn handle_define_constant_input(&mut self, input: InputEvent) -> Result<InputResult> {
+		match self.handle_location_input(input) {
+			LocationInputResult::Intermediate(result) => Ok(result),
+			LocationInputResult::Finished(Location::Variable(name)) => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				self.define_user_constant(name, self.context.top()?)?;
+				Ok(InputResult::Normal)
+			}
+			LocationInputResult::Exit => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				Ok(InputResult::Normal)
+			}
+			// Constants are named with letters only
+			LocationInputResult::Finished(_) | LocationInputResult::Invalid => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				Err(Error::InvalidEntry)
+			}
+		}
+	}
+
+	fn handle_character_code_input(&mut self, input: InputEvent) -> Result<InputResult> {
+		match input {
+			InputEvent::Character(ch) => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				self.context
+					.push(Value::Number(Number::Integer((ch as u32).into())))?;
+			}
+			InputEvent::Exit | InputEvent::Backspace => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+			}
+			InputEvent::Off => {
+				self.input_mode.alpha = AlphaMode::Normal;
+				return Ok(InputResult::Suspend);
+			}
+			_ => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				return Err(Error::InvalidEntry);
+			}
+		}
+		Ok(InputResult::Normal)
+	}
+
+	fn handle_macro_variable_input(
+		&mut self,
+		input: InputEvent,
+		screen: &dyn Screen,
+	) -> Result<InputResult> {
+		match self.handle_location_input(input) {
+			LocationInputResult::Intermediate(result) => Ok(result),
+			LocationInputResult::Finished(location) => {
+				let state = self.input_state;
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				match state {
+					InputState::Integrate => self.integrate(location, screen)?,
+					_ => self.solve(location, screen)?,
+				}
+				Ok(InputResult::Normal)
+			}
+			LocationInputResult::Exit => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				Ok(InputResult::Normal)
+			}
+			LocationInputResult::Invalid => {
+				self.input_state = InputState::Normal;
+				self.input_mode.alpha = AlphaMode::Normal;
+				Err(Error::InvalidEntry)
+			}
+		}
+	}
+
 	fn handle_menu_input(&mut self, input: InputEvent, screen: &dyn Screen) -> Result<InputResult> {
 		let menu = self.menus.last_mut().unwrap();
 		match input {
@@ -573,7 +1031,8 @@ impl State {
 				let function = menu.selected_function();
 				self.force_refresh = true;
 				match function {
-					MenuItemFunction::Action(action) => {
+					MenuItemFunction::Action(action)
+					| MenuItemFunction::ActionWithDelete(action, _) => {
 						self.input_state = InputState::Normal;
 						self.menus.clear();
 						action.execute(self, screen)?;
@@ -594,6 +1053,7 @@ impl State {
 				let function = menu.selected_function();
 				match function {
 					MenuItemFunction::Action(_)
+					| MenuItemFunction::ActionWithDelete(_, _)
 					| MenuItemFunction::InMenuAction(_)
 					| MenuItemFunction::InMenuActionWithDelete(_, _) => (),
 					MenuItemFunction::ConversionAction(_, action, _) => {
@@ -607,6 +1067,7 @@ impl State {
 				let function = menu.selected_function();
 				match function {
 					MenuItemFunction::Action(_)
+					| MenuItemFunction::ActionWithDelete(_, _)
 					| MenuItemFunction::InMenuAction(_)
 					| MenuItemFunction::InMenuActionWithDelete(_, _) => (),
 					MenuItemFunction::ConversionAction(_, _, action) => {
@@ -622,7 +1083,8 @@ impl State {
 					MenuItemFunction::Action(_)
 					| MenuItemFunction::InMenuAction(_)
 					| MenuItemFunction::ConversionAction(_, _, _) => (),
-					MenuItemFunction::InMenuActionWithDelete(_, action) => {
+					MenuItemFunction::ActionWithDelete(_, action)
+					| MenuItemFunction::InMenuActionWithDelete(_, action) => {
 						action.execute(self, screen)?;
 					}
 				}
@@ -654,16 +1116,7 @@ impl State {
 			InputEvent::Sin => self.direct_select_menu_item(19, screen)?,
 			InputEvent::Cos => self.direct_select_menu_item(20, screen)?,
 			InputEvent::Tan => self.direct_select_menu_item(21, screen)?,
-			InputEvent::Exit => {
-				self.menus.pop();
-				if let Some(menu) = self.menus.last_mut() {
-					menu.force_refresh();
-				} else {
-					self.input_state = InputState::Normal;
-					self.cached_status_bar_state.left_string = String::new();
-					self.force_refresh = true;
-				}
-			}
+			InputEvent::Exit => self.exit_menu(),
 			InputEvent::Off => return Ok(InputResult::Suspend),
 			_ => (),
 		}
@@ -672,20 +1125,131 @@ impl State {
 
 	pub fn handle_input(&mut self, input: InputEvent, screen: &dyn Screen) -> Result<InputResult> {
 		if self.error.is_some() {
+			// Failed operations leave their operands on the stack, so any key other than
+			// the dismissal keys clears the error and is then handled normally. This
+			// allows the input to be corrected and the operation retried immediately.
 			self.error = None;
-			return match input {
-				InputEvent::Off => Ok(InputResult::Suspend),
-				_ => Ok(InputResult::Normal),
-			};
+			match input {
+				InputEvent::Off => return Ok(InputResult::Suspend),
+				InputEvent::Exit | InputEvent::Backspace => return Ok(InputResult::Normal),
+				_ => (),
+			}
 		}
 
-		match self.input_state {
+		let was_recording = self.macro_recording.is_some();
+		let menu_was_open = !self.menus.is_empty();
+
+		let result = match self.input_state {
 			InputState::Normal => self.handle_normal_input(input, screen),
 			InputState::NumberInput => self.handle_number_input(input, screen),
 			InputState::Recall => self.handle_recall_input(input),
 			InputState::Store => self.handle_store_input(input),
+			InputState::Solve | InputState::Integrate => {
+				self.handle_macro_variable_input(input, screen)
+			}
+			InputState::DefineConstant => self.handle_define_constant_input(input),
+			InputState::CharacterCode => self.handle_character_code_input(input),
+			InputState::Precision | InputState::Radix | InputState::Grouping => {
+				self.handle_setting_input(input)
+			}
 			InputState::Menu => self.handle_menu_input(input, screen),
+			InputState::View => self.handle_view_input(input, screen),
+		};
+
+		// Only keys handled successfully while recording was active both before and
+		// after the key are recorded. This leaves out the keys that start and stop the
+		// recording, and keys that failed and have no effect on replay.
+		if was_recording && result.is_ok() {
+			if let Some(events) = &mut self.macro_recording {
+				if self.menus.is_empty() {
+					self.macro_menu_start = None;
+				} else if !menu_was_open {
+					// Remember where menu navigation started, so that the keys used to
+					// reach the stop recording function can be removed.
+					self.macro_menu_start = Some(events.len());
+				}
+				events.push(input);
+			}
+		}
+		result
+	}
+
+	fn handle_view_input(&mut self, input: InputEvent, screen: &dyn Screen) -> Result<InputResult> {
+		let viewer = match self.viewer.as_mut() {
+			Some(viewer) => viewer,
+			None => {
+				self.input_state = InputState::Normal;
+				return Ok(InputResult::Normal);
+			}
+		};
+
+		match input {
+			InputEvent::Up => viewer.up(),
+			InputEvent::Down => viewer.down(screen),
+			InputEvent::Exit | InputEvent::Backspace | InputEvent::Enter => {
+				self.viewer = None;
+				self.input_state = InputState::Normal;
+				self.force_refresh = true;
+			}
+			InputEvent::Off => return Ok(InputResult::Suspend),
+			_ => (),
+		}
+		Ok(InputResult::Normal)
+	}
+
+	fn handle_setting_input(&mut self, input: InputEvent) -> Result<InputResult> {
+		match input {
+			InputEvent::Character(ch @ '0'..='9') => {
+				self.location_entry
+					.value
+					.push(ch as u32 as u8 - '0' as u32 as u8);
+				// Group size is always a single digit
+				let max_digits = match self.input_state {
+					InputState::Grouping => 1,
+					_ => MAX_SETTING_DIGITS,
+				};
+				if self.location_entry.value.len() >= max_digits {
+					self.finish_setting_input()?;
+				}
+			}
+			InputEvent::Enter => {
+				if self.location_entry.value.len() > 0 {
+					self.finish_setting_input()?;
+				} else {
+					self.input_state = InputState::Normal;
+					return Err(Error::InvalidEntry);
+				}
+			}
+			InputEvent::Backspace => {
+				if self.location_entry.value.pop().is_none() {
+					self.input_state = InputState::Normal;
+				}
+			}
+			InputEvent::Exit => {
+				self.input_state = InputState::Normal;
+			}
+			InputEvent::Off => return Ok(InputResult::Suspend),
+			_ => {
+				self.input_state = InputState::Normal;
+				return Err(Error::InvalidEntry);
+			}
+		}
+		Ok(InputResult::Normal)
+	}
+
+	fn finish_setting_input(&mut self) -> Result<()> {
+		let value = self.location_entry.int_value();
+		let setting = self.input_state;
+		self.input_state = InputState::Normal;
+		match setting {
+			InputState::Precision => self.context.set_precision(value)?,
+			InputState::Radix => self.context.set_custom_integer_radix(value)?,
+			InputState::Grouping => self.context.set_grouping(value)?,
+			_ => unreachable!(),
 		}
+		self.force_refresh = true;
+		self.stack_renderer.borrow_mut().invalidate_rendering();
+		Ok(())
 	}
 
 	fn handle_location_input(&mut self, input: InputEvent) -> LocationInputResult {
@@ -837,6 +1401,13 @@ impl State {
 					changed = true;
 				}
 			}
+			StatusBarLeftDisplayType::StackDepth => {
+				let depth = self.context.stack_len().to_number().to_string() + " on stack";
+				if depth != self.cached_status_bar_state.left_string {
+					self.cached_status_bar_state.left_string = depth;
+					changed = true;
+				}
+			}
 		}
 
 		changed
@@ -1007,7 +1578,17 @@ impl State {
 				Font::Smallest,
 				&status_bar_rect,
 			),
-			_ => (),
+			10 => (),
+			radix => {
+				let string = "B".to_string() + Format::new().format_bigint(&radix.into()).as_str();
+				self.draw_status_bar_indicator(
+					&mut renderer,
+					&mut x,
+					&string,
+					Font::Smallest,
+					&status_bar_rect,
+				)
+			}
 		}
 
 		// Render integer format indicator
@@ -1179,7 +1760,11 @@ impl State {
 		};
 
 		// If the hex representation is enabled and valid, show it below
-		let (layout, alt_layout) = Value::Number(editor.number()).add_alternate_layout(
+		let value = match editor.value() {
+			Ok(value) => value,
+			Err(_) => Value::Number(editor.number()),
+		};
+		let (layout, alt_layout) = value.add_alternate_layout(
 			layout,
 			self.context.format(),
 			self.base_font.smaller().smaller(),
@@ -1237,6 +1822,12 @@ impl State {
 				return;
 			}
 		}
+		if self.input_state == InputState::View {
+			if let Some(viewer) = &self.viewer {
+				viewer.render(screen);
+				return;
+			}
+		}
 
 		// Check for updates to status bar and render if changed
 		if self.update_status_bar_state()
@@ -1275,9 +1866,15 @@ impl State {
 					stack_label_offset = 1;
 				}
 			}
-			InputState::Recall | InputState::Store => {
-				self.render_location_edit(screen, &mut stack_area)
-			}
+			InputState::Recall
+			| InputState::Store
+			| InputState::Solve
+			| InputState::Integrate
+			| InputState::DefineConstant
+			| InputState::CharacterCode
+			| InputState::Precision
+			| InputState::Radix
+			| InputState::Grouping => self.render_location_edit(screen, &mut stack_area),
 			_ => (),
 		}
 
@@ -1303,7 +1900,7 @@ impl State {
 	pub fn update_header(&mut self, screen: &mut dyn Screen) {
 		if self.force_render_on_status_update {
 			self.render(screen);
-		} else if self.input_state != InputState::Menu {
+		} else if self.input_state != InputState::Menu && self.input_state != InputState::View {
 			// When specifically updating the header, always render the header
 			self.update_status_bar_state();
 			self.draw_status_bar(screen);
@@ -1316,7 +1913,8 @@ impl State {
 		menu.force_refresh();
 		if let Some(function) = menu.specific_function(idx) {
 			match function {
-				MenuItemFunction::Action(action) => {
+				MenuItemFunction::Action(action)
+				| MenuItemFunction::ActionWithDelete(action, _) => {
 					self.input_state = InputState::Normal;
 					self.menus.clear();
 					self.force_refresh = true;
@@ -1340,6 +1938,385 @@ impl State {
 		Ok(())
 	}
 
+	pub fn replace_menu(&mut self, menu: Menu) {
+		self.menus.pop();
+		self.menus.push(menu);
+		self.force_refresh = true;
+	}
+
+	pub fn exit_menu(&mut self) {
+		self.menus.pop();
+		if let Some(menu) = self.menus.last_mut() {
+			menu.force_refresh();
+		} else {
+			self.input_state = InputState::Normal;
+			self.cached_status_bar_state.left_string = String::new();
+			self.force_refresh = true;
+		}
+	}
+
+	pub fn is_recording_macro(&self) -> bool {
+		self.macro_recording.is_some()
+	}
+
+	pub fn start_macro_recording(&mut self) {
+		self.macro_recording = Some(Vec::new());
+		self.macro_menu_start = None;
+	}
+
+	pub fn stop_macro_recording(&mut self) -> Result<Vec<InputEvent>> {
+		match self.macro_recording.take() {
+			Some(mut events) => {
+				// When stopped from a menu, drop the keys that navigated to it
+				if let Some(start) = self.macro_menu_start.take() {
+					events.truncate(start);
+				}
+				if events.len() != 0 {
+					self.last_macro = Some(events.clone());
+				}
+				Ok(events)
+			}
+			None => Err(Error::InvalidEntry),
+		}
+	}
+
+	pub fn run_macro(&mut self, events: &[InputEvent], screen: &dyn Screen) -> Result<()> {
+		// Guard against macros that invoke themselves through a custom key
+		if self.macro_depth >= MAX_MACRO_DEPTH {
+			return Err(Error::StackOverflow);
+		}
+
+		// Events generated by the macro are not recorded, only the key that started it
+		let recording = self.macro_recording.take();
+		self.macro_depth += 1;
+		let mut result = Ok(());
+		for event in events {
+			if let Err(error) = self.handle_input(*event, screen) {
+				result = Err(error);
+				break;
+			}
+		}
+		self.macro_depth -= 1;
+		self.macro_recording = recording;
+		result
+	}
+
+	pub fn prompt_solve(&mut self) -> Result<()> {
+		self.end_edit()?;
+		if self.last_macro.is_none() {
+			return Err(Error::ValueNotDefined);
+		}
+		self.input_state = InputState::Solve;
+		self.location_entry = LocationEntryState::new("Solve");
+		Ok(())
+	}
+
+	pub fn prompt_define_constant(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.context.top()?;
+		self.input_state = InputState::DefineConstant;
+		self.location_entry = LocationEntryState::new("Const");
+		Ok(())
+	}
+
+	pub fn prompt_character_code(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.input_state = InputState::CharacterCode;
+		self.location_entry = LocationEntryState::new("Char");
+		Ok(())
+	}
+
+	pub fn view_character(&mut self) -> Result<()> {
+		self.end_edit()?;
+
+		// Only valid Unicode code points can be shown as a character
+		let code =
+			u32::try_from(&self.context.top()?.exact_int()?).map_err(|_| Error::ValueOutOfRange)?;
+		let ch = char::from_u32(code).ok_or(Error::ValueOutOfRange)?;
+
+		self.viewer = Some(Viewer::new("Character", ch.to_string()));
+		self.input_state = InputState::View;
+		Ok(())
+	}
+
+	pub fn user_constant_names(&self) -> Vec<char> {
+		self.user_constants.keys().cloned().collect()
+	}
+
+	pub fn user_constant(&self, name: char) -> Result<Value> {
+		match self.user_constants.get(&name) {
+			Some(value) => value.get(),
+			None => Err(Error::ValueNotDefined),
+		}
+	}
+
+	pub fn define_user_constant(&mut self, name: char, value: Value) -> Result<()> {
+		self.user_constants.insert(name, store(value)?);
+		Ok(())
+	}
+
+	pub fn delete_user_constant(&mut self, name: char) -> Result<()> {
+		match self.user_constants.remove(&name) {
+			Some(_) => Ok(()),
+			None => Err(Error::ValueNotDefined),
+		}
+	}
+
+	pub fn prompt_integrate(&mut self) -> Result<()> {
+		self.end_edit()?;
+		if self.last_macro.is_none() {
+			return Err(Error::ValueNotDefined);
+		}
+		self.input_state = InputState::Integrate;
+		self.location_entry = LocationEntryState::new("∫");
+		Ok(())
+	}
+
+	fn evaluate_macro_at(
+		&mut self,
+		var: &Location,
+		events: &[InputEvent],
+		x: &Number,
+		screen: &dyn Screen,
+	) -> Result<Number> {
+		// Evaluate the macro with the variable set to the given value, then put the stack
+		// back the way it was so that each evaluation starts from the same state.
+		let snapshot = self.context.stack().snapshot();
+		let result = self
+			.context
+			.write(var.clone(), Value::Number(x.clone()))
+			.and_then(|_| self.run_macro(events, screen))
+			.and_then(|_| self.end_edit())
+			.and_then(|_| Ok(self.context.top()?.real_number()?.clone()));
+		self.context.stack_mut().restore_snapshot(snapshot);
+		result
+	}
+
+	fn evaluate_over_variable<T>(
+		&mut self,
+		var: &Location,
+		screen: &dyn Screen,
+		func: impl FnOnce(&mut dyn FnMut(&Number) -> Result<Number>) -> Result<T>,
+	) -> Result<T> {
+		let events = self.last_macro.clone().ok_or(Error::ValueNotDefined)?;
+		let old_value = self.context.read(var).ok();
+
+		// Intermediate evaluations of the macro must not be visible in the undo history
+		let undo = self.context.stack().undo_enabled();
+		self.context.stack_mut().set_undo_enabled(false);
+		let result = func(&mut |x| self.evaluate_macro_at(var, &events, x, screen));
+		self.context.stack_mut().set_undo_enabled(undo);
+
+		// Put the variable back to the value it had before evaluation
+		match old_value {
+			Some(value) => self.context.write(var.clone(), value)?,
+			None => {
+				let _ = self.context.delete(var);
+			}
+		}
+		result
+	}
+
+	pub fn solve(&mut self, var: Location, screen: &dyn Screen) -> Result<()> {
+		let guess = self.context.top()?.real_number()?.clone();
+		let precision = self.context.format().precision;
+		let root = self
+			.evaluate_over_variable(&var, screen, |func| solver::solve(func, &guess, precision))?;
+		self.context.write(var, Value::Number(root.clone()))?;
+		self.context.set_top(Value::Number(root))
+	}
+
+	pub fn integrate(&mut self, var: Location, screen: &dyn Screen) -> Result<()> {
+		// Limits of integration are taken from the stack, with the upper limit on top
+		let lower = self.context.entry(1)?.real_number()?.clone();
+		let upper = self.context.entry(0)?.real_number()?.clone();
+		let precision = self.context.format().precision;
+		let result = self.evaluate_over_variable(&var, screen, |func| {
+			solver::integrate(func, &lower, &upper, precision)
+		})?;
+		self.context.replace_entries(2, Value::Number(result))
+	}
+
+	pub fn prompt_precision(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.input_state = InputState::Precision;
+		self.location_entry = LocationEntryState::new("Digits");
+		Ok(())
+	}
+
+	pub fn view_entry(&mut self) -> Result<()> {
+		self.end_edit()?;
+
+		// Format the top entry without any size or precision limits
+		let mut format = self.context.format().clone();
+		format.limit_size = false;
+		format.precision = MAX_PRECISION;
+		let text = match self.context.top()?.format(&format) {
+			FormatResult::Integer(string)
+			| FormatResult::Float(string)
+			| FormatResult::Complex(string)
+			| FormatResult::Object(string) => string,
+		};
+
+		self.viewer = Some(Viewer::new("Full Value", text));
+		self.input_state = InputState::View;
+		Ok(())
+	}
+
+	pub fn view_all_bases(&mut self) -> Result<()> {
+		self.end_edit()?;
+
+		// Format the top integer in each of the common bases, one per line
+		let int = self.context.top()?.exact_int()?;
+		let mut format = self.context.format().clone();
+		let mut text = String::new();
+		for (name, radix) in [("BIN", 2), ("OCT", 8), ("DEC", 10), ("HEX", 16)].iter() {
+			format.integer_radix = *radix;
+			if text.len() > 0 {
+				text.push('\n');
+			}
+			text += name;
+			text.push(' ');
+			text += &format.format_bigint(&int);
+		}
+
+		self.viewer = Some(Viewer::new("All Bases", text));
+		self.input_state = InputState::View;
+		Ok(())
+	}
+
+	pub fn prompt_radix(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.input_state = InputState::Radix;
+		self.location_entry = LocationEntryState::new("Base");
+		Ok(())
+	}
+
+	pub fn prompt_grouping(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.input_state = InputState::Grouping;
+		self.location_entry = LocationEntryState::new("Group");
+		Ok(())
+	}
+
+	pub fn clear_all_variables(&mut self) {
+		self.context.clear_all_variables();
+		self.force_refresh = true;
+	}
+
+	pub fn stat_add(&mut self) -> Result<()> {
+		// The statistics dataset is kept separate from the stack and is not part of the
+		// undo history, so the value is consumed without recording it for undo. A number
+		// being edited is added directly, so that it never appears in the undo history.
+		let editing = self.editor.is_some();
+		let value = match &self.editor {
+			Some(editor) => editor.value()?,
+			None => self.context.top()?,
+		};
+		let point = match value {
+			Value::Number(x) => StatPoint { x, y: None },
+			Value::Vector(vector) if vector.len() == 2 => match (vector.get(0)?, vector.get(1)?) {
+				(Value::Number(x), Value::Number(y)) => StatPoint { x, y: Some(y) },
+				_ => return Err(Error::DataTypeMismatch),
+			},
+			_ => return Err(Error::DataTypeMismatch),
+		};
+		if editing {
+			self.editor = None;
+			self.input_state = InputState::Normal;
+			self.input_mode.alpha = AlphaMode::Normal;
+		} else {
+			let undo = self.context.stack().undo_enabled();
+			self.context.stack_mut().set_undo_enabled(false);
+			let result = self.context.pop();
+			self.context.stack_mut().set_undo_enabled(undo);
+			result?;
+		}
+		self.stat_data.push(point);
+		Ok(())
+	}
+
+	pub fn stat_remove(&mut self) -> Result<()> {
+		self.end_edit()?;
+		// Give the removed value back so that it can be corrected and added again
+		let point = self.stat_data.last().ok_or(Error::ValueNotDefined)?;
+		let value = match &point.y {
+			Some(y) => {
+				let mut vector = Vector::new()?;
+				vector.push(Value::Number(point.x.clone()))?;
+				vector.push(Value::Number(y.clone()))?;
+				Value::Vector(vector)
+			}
+			None => Value::Number(point.x.clone()),
+		};
+		self.context.push(value)?;
+		self.stat_data.pop();
+		Ok(())
+	}
+
+	pub fn stat_clear(&mut self) {
+		self.stat_data.clear();
+	}
+
+	pub fn stat_count(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.context
+			.push(Value::Number(self.stat_data.len().to_number()))
+	}
+
+	pub fn linear_regression(&mut self) -> Result<()> {
+		self.end_edit()?;
+		let mut points = Vec::new();
+		for point in &self.stat_data {
+			match &point.y {
+				Some(y) => points.push((point.x.clone(), y.clone())),
+				None => return Err(Error::DataTypeMismatch),
+			}
+		}
+		let result = stats::linear_regression(&points)?;
+
+		// Leave the correlation coefficient on top, with the intercept and slope above it.
+		// When all points have the same y value the correlation is undefined and only the
+		// slope and intercept are pushed.
+		self.context.push(Value::Number(result.slope))?;
+		self.context.push(Value::Number(result.intercept))?;
+		match result.correlation {
+			Some(correlation) => self.context.push(Value::Number(correlation)),
+			None => Ok(()),
+		}
+	}
+
+	fn clock_seed() -> u64 {
+		// Seed the random number generator from the real time clock so that each
+		// session produces a different sequence unless a seed is given.
+		match NaiveDateTime::now() {
+			Ok(now) => now.and_utc().timestamp_nanos_opt().unwrap_or(0) as u64,
+			Err(_) => 0,
+		}
+	}
+
+	pub fn random(&mut self) -> Result<()> {
+		self.end_edit()?;
+		self.context.push(Value::Number(self.random.next_decimal()))
+	}
+
+	pub fn random_int(&mut self) -> Result<()> {
+		self.end_edit()?;
+		let limit = self.context.top()?.real_number()?.to_int()?.into_owned();
+		let value = self.random.next_below(&limit)?;
+		self.context.set_top(Value::Number(Number::Integer(value)))
+	}
+
+	pub fn seed_random(&mut self) -> Result<()> {
+		self.end_edit()?;
+		// Use the low 64 bits of the integer on the stack as the seed
+		let seed = self.context.top()?.real_number()?.to_int()?.into_owned();
+		let seed: u64 = (&seed & &BigInt::from(u64::MAX)).try_into().unwrap();
+		self.random.seed(seed);
+		self.context.pop()?;
+		Ok(())
+	}
+
 	pub fn show_system_setup_menu(&mut self) {
 		#[cfg(feature = "dm42")]
 		show_system_setup_menu();
@@ -1354,3 +2331,354 @@ impl State {
 		result
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::dm42::screen::ScreenLayoutRenderer;
+	use rscalc_math::functions::StackFunction;
+	use rscalc_math::unit::{CompositeUnit, DistanceUnit};
+
+	struct TestScreen;
+
+	impl Screen for TestScreen {
+		fn width(&self) -> i32 {
+			400
+		}
+
+		fn height(&self) -> i32 {
+			240
+		}
+
+		fn clear(&mut self) {}
+		fn refresh(&mut self) {}
+		fn fill(&mut self, _rect: &Rect, _color: bool) {}
+		fn draw_bits(&mut self, _x: i32, _y: i32, _bits: u32, _width: u8, _color: bool) {}
+
+		fn renderer(&mut self, render_mode: RenderMode) -> ScreenLayoutRenderer {
+			ScreenLayoutRenderer::new(self, render_mode)
+		}
+	}
+
+	fn press(state: &mut State, events: &[InputEvent]) {
+		for event in events {
+			let _ = state.handle_input(*event, &TestScreen);
+		}
+	}
+
+	fn top(state: &State) -> f64 {
+		state.context().top().unwrap().to_f64().unwrap()
+	}
+
+	#[test]
+	fn record_and_replay_macro() {
+		let mut state = State::new();
+
+		// Catalog, Macro page, Start Recording
+		press(
+			&mut state,
+			&[
+				InputEvent::Catalog,
+				InputEvent::Character('4'),
+				InputEvent::Character('1'),
+			],
+		);
+		assert!(state.is_recording_macro());
+
+		// The failed swap with a single entry must not be recorded
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('2'),
+				InputEvent::Enter,
+				InputEvent::Character('3'),
+				InputEvent::Add,
+				InputEvent::Swap,
+			],
+		);
+		assert_eq!(top(&state), 5.0);
+
+		// Catalog, Macro page, Stop Recording
+		press(
+			&mut state,
+			&[
+				InputEvent::Catalog,
+				InputEvent::Character('4'),
+				InputEvent::Character('2'),
+			],
+		);
+		assert!(!state.is_recording_macro());
+
+		let events = state.last_macro.clone().unwrap();
+		assert_eq!(
+			events,
+			vec![
+				InputEvent::Character('2'),
+				InputEvent::Enter,
+				InputEvent::Character('3'),
+				InputEvent::Add,
+			]
+		);
+
+		// Replaying adds 2 + 3 again and does not start a new recording
+		press(&mut state, &[InputEvent::Exit]);
+		state.run_macro(&events, &TestScreen).unwrap();
+		assert!(!state.is_recording_macro());
+		assert_eq!(top(&state), 5.0);
+		assert_eq!(state.context().stack().len(), 2);
+	}
+
+	#[test]
+	fn serialize_round_trip() {
+		let mut state = State::new();
+		state.set_enter_duplicates(false);
+		state.set_keep_editing_on_error(true);
+		state.set_strict_operands(false);
+		state.context_mut().push(Value::Number(3.into())).unwrap();
+		state.stat_add().unwrap();
+		state.context_mut().push(Value::Number(4.into())).unwrap();
+		state.update_ans().unwrap();
+		state.last_macro = Some(vec![InputEvent::Character('2'), InputEvent::Mul]);
+		let data = state.serialize().unwrap();
+
+		let mut restored = State::new();
+		restored.deserialize(&data).unwrap();
+		assert!(!restored.enter_duplicates());
+		assert!(restored.keep_editing_on_error());
+		assert!(!restored.strict_operands());
+		assert_eq!(restored.stat_data.len(), 1);
+		assert_eq!(restored.stat_data[0].x.to_f64(), 3.0);
+		assert!(restored.stat_data[0].y.is_none());
+		assert_eq!(
+			restored
+				.ans
+				.as_ref()
+				.unwrap()
+				.get()
+				.unwrap()
+				.to_f64()
+				.unwrap(),
+			4.0
+		);
+		assert_eq!(
+			restored.last_macro,
+			Some(vec![InputEvent::Character('2'), InputEvent::Mul])
+		);
+		assert_eq!(restored.context().stack_len(), 1);
+		assert_eq!(top(&restored), 4.0);
+	}
+
+	#[test]
+	fn restore_preferences_in_radians() {
+		let mut state = State::new();
+		state.preferences_mut().angle_mode = AngleUnit::Radians;
+		let data = state.serialize().unwrap();
+
+		let preferences = Preferences::from_saved_state(&data).unwrap();
+		let restored = State::new_with_preferences(preferences);
+		assert!(restored.preferences().angle_mode == AngleUnit::Radians);
+		assert!(*restored.context().angle_mode() == AngleUnit::Radians);
+	}
+
+	#[test]
+	fn user_constant_with_unit_is_saved() {
+		let mut state = State::new();
+		state
+			.context_mut()
+			.push(Value::NumberWithUnit(
+				Number::from(98) / Number::from(10),
+				CompositeUnit::single_unit(Unit::Distance(DistanceUnit::Meters)),
+			))
+			.unwrap();
+		Function::DefineConstant
+			.execute(&mut state, &TestScreen)
+			.unwrap();
+		press(&mut state, &[InputEvent::Character('G')]);
+		let data = state.serialize().unwrap();
+
+		let mut restored = State::new();
+		restored.deserialize(&data).unwrap();
+		Function::RecallConstant('G')
+			.execute(&mut restored, &TestScreen)
+			.unwrap();
+		match restored.context().top().unwrap() {
+			Value::NumberWithUnit(value, mut unit) => {
+				let meters = unit
+					.convert_single_unit(&value, Unit::Distance(DistanceUnit::Meters))
+					.unwrap();
+				assert!((meters.to_f64() - 9.8).abs() < 1e-12);
+			}
+			_ => panic!("constant lost its unit"),
+		}
+	}
+
+	#[test]
+	fn strict_operands_require_two_values() {
+		let mut state = State::new();
+		press(&mut state, &[InputEvent::Character('5')]);
+		assert_eq!(
+			state.handle_input(InputEvent::Add, &TestScreen).err(),
+			Some(Error::NotEnoughValues)
+		);
+		assert_eq!(Error::NotEnoughValues.to_str(), "Not enough values");
+		assert_eq!(state.context().stack_len(), 1);
+		assert_eq!(top(&state), 5.0);
+
+		state.set_strict_operands(false);
+		press(&mut state, &[InputEvent::Sub]);
+		assert_eq!(state.context().stack_len(), 1);
+		assert_eq!(top(&state), -5.0);
+	}
+
+	#[test]
+	fn ans_ignores_stack_manipulation() {
+		let mut state = State::new();
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('2'),
+				InputEvent::Enter,
+				InputEvent::Character('2'),
+				InputEvent::Add,
+			],
+		);
+		Function::Stack(StackFunction::ClearX)
+			.execute(&mut state, &TestScreen)
+			.unwrap();
+		assert_eq!(top(&state), 0.0);
+		Function::Ans.execute(&mut state, &TestScreen).unwrap();
+		assert_eq!(top(&state), 4.0);
+	}
+
+	#[test]
+	fn clear_x_disables_stack_lift() {
+		// Enter completes number entry without duplicating, and CLx leaves the zero it
+		// puts on the stack to be replaced by the next number entered.
+		let mut state = State::new();
+		press(&mut state, &[InputEvent::Character('2'), InputEvent::Enter]);
+		Function::Stack(StackFunction::ClearX)
+			.execute(&mut state, &TestScreen)
+			.unwrap();
+		press(&mut state, &[InputEvent::Character('3'), InputEvent::Enter]);
+		assert_eq!(state.context().stack_len(), 1);
+		assert_eq!(top(&state), 3.0);
+	}
+
+	#[test]
+	fn stat_add_and_remove() {
+		let mut state = State::new();
+		for value in 1..=3 {
+			state
+				.context_mut()
+				.push(Value::Number(value.into()))
+				.unwrap();
+			state.stat_add().unwrap();
+		}
+		assert_eq!(state.context().stack_len(), 0);
+		state.stat_count().unwrap();
+		assert_eq!(top(&state), 3.0);
+		state.context_mut().pop().unwrap();
+
+		// Σ- gives the last point back and removes it from the dataset
+		state.stat_remove().unwrap();
+		assert_eq!(top(&state), 3.0);
+		state.stat_count().unwrap();
+		assert_eq!(top(&state), 2.0);
+
+		// Undo after adding a typed number leaves the stack as it was before typing it
+		press(&mut state, &[InputEvent::Character('4')]);
+		Function::StatAdd.execute(&mut state, &TestScreen).unwrap();
+		assert_eq!(state.stat_data.len(), 3);
+		assert_eq!(state.context().stack_len(), 2);
+		state.undo().unwrap();
+		assert_eq!(state.context().stack_len(), 1);
+		assert_eq!(top(&state), 3.0);
+	}
+
+	fn top_complex(state: &State) -> (f64, f64) {
+		match state.context().top().unwrap() {
+			Value::Complex(value) => (value.real_part().to_f64(), value.imaginary_part().to_f64()),
+			_ => panic!("expected a complex number"),
+		}
+	}
+
+	#[test]
+	fn enter_complex_number() {
+		let mut state = State::new();
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('3'),
+				InputEvent::Character('i'),
+				InputEvent::Character('4'),
+				InputEvent::Enter,
+			],
+		);
+		assert_eq!(top_complex(&state), (3.0, 4.0));
+
+		// A second i is ignored instead of starting another imaginary part
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('5'),
+				InputEvent::Character('i'),
+				InputEvent::Character('i'),
+				InputEvent::Character('2'),
+				InputEvent::Enter,
+			],
+		);
+		assert_eq!(top_complex(&state), (5.0, 2.0));
+	}
+
+	#[test]
+	fn i_is_a_digit_in_large_radix() {
+		let mut state = State::new();
+		state.context_mut().set_integer_radix(20);
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('1'),
+				InputEvent::Character('I'),
+				InputEvent::Enter,
+			],
+		);
+		assert_eq!(top(&state), 38.0);
+	}
+
+	#[test]
+	fn keep_editing_after_incompatible_units() {
+		let mut state = State::new();
+		state.set_keep_editing_on_error(true);
+		state.function_keys_mut().set_custom_function(
+			0,
+			Some(Function::Stack(StackFunction::ConvertToUnit(
+				Unit::Distance(DistanceUnit::Kilometers),
+			))),
+		);
+		press(
+			&mut state,
+			&[
+				InputEvent::Character('1'),
+				InputEvent::Enter,
+				InputEvent::Custom,
+				InputEvent::Character('5'),
+			],
+		);
+
+		// Converting a number without units fails
+		assert_eq!(
+			state
+				.handle_input(InputEvent::FunctionKey(1, false), &TestScreen)
+				.err(),
+			Some(Error::IncompatibleUnits)
+		);
+
+		// The stack is unchanged and the number is still being edited
+		assert_eq!(state.context().stack_len(), 1);
+		assert_eq!(top(&state), 1.0);
+		assert!(state.editor.is_some());
+		press(&mut state, &[InputEvent::Character('0'), InputEvent::Enter]);
+		assert_eq!(state.context().stack_len(), 2);
+		assert_eq!(top(&state), 50.0);
+	}
+}
@@ -57,6 +57,18 @@ pub fn unit_menu() -> Menu {
 			),
 		});
 	}
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Base SI")),
+		function: MenuItemFunction::InMenuAction(Function::Stack(StackFunction::ToBaseUnits)),
+	});
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Metric")),
+		function: MenuItemFunction::InMenuAction(Function::Stack(StackFunction::ToMetric)),
+	});
+	items.push(MenuItem {
+		layout: MenuItemLayout::Static(MenuItem::static_string_layout("Imperial")),
+		function: MenuItemFunction::InMenuAction(Function::Stack(StackFunction::ToImperial)),
+	});
 	let mut menu = Menu::new_with_bottom("Units", items, value_layout());
 	menu.set_columns(3);
 	menu
@@ -0,0 +1,115 @@
+use crate::dm42::screen::{RenderMode, Screen};
+use rscalc_layout::font::Font;
+use rscalc_layout::layout::{LayoutRenderer, Rect, TokenType};
+
+#[cfg(feature = "dm42")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "dm42")]
+use alloc::vec::Vec;
+
+/// Full screen, scrollable view of a long piece of text. This is used to display
+/// values that are too large to fit in the stack display.
+pub struct Viewer {
+	title: String,
+	text: String,
+	scroll: usize,
+}
+
+impl Viewer {
+	pub fn new(title: &str, text: String) -> Self {
+		Viewer {
+			title: title.to_string(),
+			text,
+			scroll: 0,
+		}
+	}
+
+	fn lines(&self, screen: &dyn Screen) -> Vec<String> {
+		// Break the text into lines that fit within the width of the screen, also
+		// starting a new line at every line break in the text
+		let width = screen.width() - 8;
+		let mut lines = Vec::new();
+		let mut line = String::new();
+		for ch in self.text.chars() {
+			if ch == '\n' {
+				lines.push(line);
+				line = String::new();
+				continue;
+			}
+			let mut candidate = line.clone();
+			candidate.push(ch);
+			if line.len() > 0 && screen.metrics().width(Font::Medium, &candidate) > width {
+				lines.push(line);
+				line = String::new();
+				line.push(ch);
+			} else {
+				line = candidate;
+			}
+		}
+		if line.len() > 0 || lines.len() == 0 {
+			lines.push(line);
+		}
+		lines
+	}
+
+	fn top(screen: &dyn Screen) -> i32 {
+		screen.metrics().height(Font::Small) + 3
+	}
+
+	fn visible_line_count(screen: &dyn Screen) -> usize {
+		let line_height = screen.metrics().height(Font::Medium);
+		core::cmp::max((screen.height() - Self::top(screen)) / line_height, 1) as usize
+	}
+
+	fn max_scroll(&self, screen: &dyn Screen) -> usize {
+		self.lines(screen)
+			.len()
+			.saturating_sub(Self::visible_line_count(screen))
+	}
+
+	pub fn up(&mut self) {
+		self.scroll = self.scroll.saturating_sub(1);
+	}
+
+	pub fn down(&mut self, screen: &dyn Screen) {
+		if self.scroll < self.max_scroll(screen) {
+			self.scroll += 1;
+		}
+	}
+
+	pub fn render(&self, screen: &mut dyn Screen) {
+		screen.clear();
+
+		// Draw title
+		let screen_rect = screen.screen_rect();
+		let mut renderer = screen.renderer(RenderMode::StatusBar);
+		renderer.erase(&Rect {
+			x: 0,
+			y: 0,
+			w: screen_rect.w,
+			h: renderer.metrics().height(Font::Small),
+		});
+		renderer.draw_text(
+			4,
+			0,
+			&self.title,
+			Font::Small,
+			TokenType::Text,
+			&screen_rect,
+		);
+
+		// Draw the visible lines of text
+		let top = Self::top(screen);
+		let line_height = screen.metrics().height(Font::Medium);
+		let lines = self.lines(screen);
+		let visible = Self::visible_line_count(screen);
+		let mut renderer = screen.renderer(RenderMode::Normal);
+		let mut y = top;
+		for line in lines.iter().skip(self.scroll).take(visible) {
+			renderer.draw_text(4, y, line, Font::Medium, TokenType::Text, &screen_rect);
+			y += line_height;
+		}
+
+		screen.refresh();
+	}
+}